@@ -0,0 +1,482 @@
+//! A command line interface for the make ten solver, e.g.
+//! `make-ten 3 7 2 8 --target 10 --ops +-*/ --max 20 --format json`
+//!
+//! `--quick` only prints one good solution, which is much faster for bigger puzzles.
+//!
+//! It can also generate batches of solvable puzzles, e.g. `make-ten --generate 100 --seed 1`,
+//! and `make-ten --regression` checks the solver against its corpus of known answers.
+//! `--export` writes every solution in a stable form that `--diff` can compare between
+//! releases.
+//! `--config <file>` reads the options from a JSON file (see `ConfigFile`), so that e.g. a
+//! classroom's worksheets can be generated the same way every time.
+//! Everything is written to stdout, so it also runs under WASI (see `cargo build-wasi`).
+
+use std::process::ExitCode;
+
+use make_ten_core::{
+    config_from_json, diff_solution_sets, export_solution_set, generate_puzzle,
+    maths::OperationKind, quick_solution, run_regression_suite, solutions_to_csv, solve, Profile,
+    Solution, SolverConfig, SolverMode, SplitStrategy, MAX_BEAM_INPUTS,
+};
+
+const USAGE: &str = "Usage: make-ten <numbers>... [--target <n>] [--ops <symbols>] [--max <n>] [--shuffle <seed>] [--profile grade-3|casual|expert] [--kid-safe] [--balanced] [--max-intermediate <n>] [--relative-cap <k>] [--max-depth <n>] [--max-operators <n>] [--beam <width>] [--all-partitions] [--mode fast|exhaustive] [--quick] [--export] [--format text|json|csv] [--config <file>]
+       make-ten --generate <puzzles> [--seed <n>] [--numbers <n>] [--target <n>] [--ops <symbols>] [--format text|json|csv] [--config <file>]
+       make-ten --regression
+       make-ten --diff <old export> <new export>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Result<Format, String> {
+        match name {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => Err(format!("Unknown format: {}", other)),
+        }
+    }
+}
+
+/// The options in a `--config` file besides the solver's, which are read from the same JSON
+/// object with `config_from_json`. E.g. for a worksheet of 20 puzzles:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "target": 10,
+///   "profile": "grade-3",
+///   "format": "csv",
+///   "generate": { "puzzles": 20, "numbers": 4, "seed": 1 }
+/// }
+/// ```
+///
+/// The command line options override the file's.
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    format: Option<String>,
+    generate: Option<GenerateOptions>,
+}
+
+/// The `--generate`, `--numbers` and `--seed` options in a config file
+#[derive(serde::Deserialize)]
+struct GenerateOptions {
+    puzzles: usize,
+    numbers: Option<usize>,
+    seed: Option<u64>,
+}
+
+struct Args {
+    inputs: Vec<i32>,
+    config: SolverConfig,
+    format: Format,
+    generate: Option<usize>,
+    quick: bool,
+    regression: bool,
+    export: bool,
+    /// The paths of the old and new exports to compare
+    diff: Option<(String, String)>,
+    seed: u64,
+    numbers: usize,
+}
+
+/// Read the options from a `--config` file as the starting point for the command line ones
+fn read_config_file(path: &str, args: &mut Args) -> Result<(), String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let invalid = |err: &dyn std::fmt::Display| format!("Invalid config in {}: {}", path, err);
+    args.config = config_from_json(&json).map_err(|err| invalid(&err))?;
+
+    let file: ConfigFile = serde_json::from_str(&json).map_err(|err| invalid(&err))?;
+    if let Some(format) = file.format {
+        args.format = Format::from_name(&format).map_err(|err| invalid(&err))?;
+    }
+    if let Some(generate) = file.generate {
+        args.generate = Some(generate.puzzles);
+        args.numbers = generate.numbers.unwrap_or(args.numbers);
+        args.seed = generate.seed.unwrap_or(args.seed);
+    }
+    Ok(())
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        inputs: Vec::new(),
+        config: SolverConfig::default(),
+        format: Format::Text,
+        generate: None,
+        quick: false,
+        regression: false,
+        export: false,
+        diff: None,
+        seed: 0,
+        numbers: 4,
+    };
+
+    // The config file is read first wherever it is, so the command line can override it
+    let args: Vec<String> = args.collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        let path = args.get(index + 1).ok_or("Missing value for --config")?;
+        read_config_file(path, &mut parsed)?;
+    }
+    let Args {
+        mut inputs,
+        mut config,
+        mut format,
+        mut generate,
+        mut quick,
+        mut regression,
+        mut export,
+        mut diff,
+        mut seed,
+        mut numbers,
+    } = parsed;
+
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+
+        match arg.as_str() {
+            "--target" => {
+                let target = value("--target")?;
+                config.target = target
+                    .parse()
+                    .map_err(|_| format!("Invalid target: {}", target))?;
+            }
+            "--ops" => {
+                let ops = value("--ops")?;
+                config.operations = ops
+                    .chars()
+                    .map(|c| {
+                        OperationKind::from_symbol(c)
+                            .ok_or_else(|| format!("Unknown operator: {}", c))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "--max" => {
+                let max = value("--max")?;
+                config.max_results = Some(
+                    max.parse()
+                        .map_err(|_| format!("Invalid maximum: {}", max))?,
+                );
+            }
+            "--shuffle" => {
+                let value = value("--shuffle")?;
+                config.shuffle_seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid seed: {}", value))?,
+                );
+            }
+            "--profile" => {
+                let name = value("--profile")?;
+                config.profile = Some(
+                    Profile::from_name(&name)
+                        .ok_or_else(|| format!("Unknown profile: {}", name))?,
+                );
+            }
+            "--kid-safe" => config.kid_safe = true,
+            "--balanced" => config.prefer_balanced = true,
+            "--max-intermediate" => {
+                let max = value("--max-intermediate")?;
+                config.max_intermediate = Some(
+                    max.parse()
+                        .map_err(|_| format!("Invalid maximum: {}", max))?,
+                );
+            }
+            "--relative-cap" => {
+                let factor = value("--relative-cap")?;
+                config.relative_intermediate_cap = Some(
+                    factor
+                        .parse()
+                        .map_err(|_| format!("Invalid factor: {}", factor))?,
+                );
+            }
+            "--max-depth" => {
+                let max = value("--max-depth")?;
+                config.max_depth =
+                    Some(max.parse().map_err(|_| format!("Invalid depth: {}", max))?);
+            }
+            "--max-operators" => {
+                let max = value("--max-operators")?;
+                config.max_operator_kinds = Some(
+                    max.parse()
+                        .map_err(|_| format!("Invalid operator count: {}", max))?,
+                );
+            }
+            "--beam" => {
+                let width = value("--beam")?;
+                config.beam_width = Some(
+                    width
+                        .parse()
+                        .map_err(|_| format!("Invalid beam width: {}", width))?,
+                );
+                // Beam search is for puzzles that are too big for the default limits
+                config.limits.max_inputs = MAX_BEAM_INPUTS;
+            }
+            "--all-partitions" => config.split_strategy = SplitStrategy::AllPartitions,
+            "--mode" => {
+                let name = value("--mode")?;
+                config.mode = SolverMode::from_name(&name)
+                    .ok_or_else(|| format!("Unknown mode: {}", name))?;
+            }
+            "--quick" => quick = true,
+            "--regression" => regression = true,
+            "--export" => export = true,
+            "--diff" => diff = Some((value("--diff")?, value("--diff")?)),
+            "--format" => format = Format::from_name(&value("--format")?)?,
+            "--config" => {
+                // Already read before the other options
+                value("--config")?;
+            }
+            "--generate" => {
+                let count = value("--generate")?;
+                generate = Some(
+                    count
+                        .parse()
+                        .map_err(|_| format!("Invalid puzzle count: {}", count))?,
+                );
+            }
+            "--seed" => {
+                let value = value("--seed")?;
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("Invalid seed: {}", value))?;
+            }
+            "--numbers" => {
+                let value = value("--numbers")?;
+                numbers = value
+                    .parse()
+                    .map_err(|_| format!("Invalid number count: {}", value))?;
+            }
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            number => inputs.push(
+                number
+                    .parse()
+                    .map_err(|_| format!("Invalid number: {}", number))?,
+            ),
+        }
+    }
+
+    Ok(Args {
+        inputs,
+        config,
+        format,
+        generate,
+        quick,
+        regression,
+        export,
+        diff,
+        seed,
+        numbers,
+    })
+}
+
+/// Escape a string for use inside a json string literal
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn print_solutions(solutions: &[Solution], format: Format) {
+    match format {
+        Format::Text => {
+            for solution in solutions {
+                println!("{}", solution.text);
+            }
+        }
+        Format::Json => {
+            let items: Vec<_> = solutions
+                .iter()
+                .map(|solution| {
+                    format!(
+                        "{{\"text\":\"{}\",\"complexity\":{},\"fingerprint\":\"{:016x}\",\"techniques\":[{}],\"mode\":\"{}\"}}",
+                        json_escape(&solution.text),
+                        solution.complexity,
+                        solution.fingerprint,
+                        solution
+                            .techniques
+                            .iter()
+                            .map(|technique| format!("\"{}\"", technique.name()))
+                            .collect::<Vec<_>>()
+                            .join(","),
+                        solution.mode.name()
+                    )
+                })
+                .collect();
+            println!("[{}]", items.join(","));
+        }
+        Format::Csv => print!("{}", solutions_to_csv(solutions)),
+    }
+}
+
+/// Generate `count` puzzles, where puzzle `i` uses the seed `seed + i`
+fn print_puzzles(args: &Args, count: usize) -> ExitCode {
+    let mut puzzles = Vec::new();
+    for i in 0..count {
+        match generate_puzzle(args.seed.wrapping_add(i as u64), args.numbers, &args.config) {
+            Ok(puzzle) => puzzles.push(puzzle),
+            Err(err) => {
+                eprintln!("{}", err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let rows = puzzles.iter().map(|puzzle| {
+        let numbers: Vec<_> = puzzle.iter().map(|n| n.to_string()).collect();
+        match args.format {
+            Format::Text => numbers.join(" "),
+            Format::Json => format!("[{}]", numbers.join(",")),
+            Format::Csv => numbers.join(","),
+        }
+    });
+
+    match args.format {
+        Format::Text | Format::Csv => rows.for_each(|row| println!("{}", row)),
+        Format::Json => println!("[{}]", rows.collect::<Vec<_>>().join(",")),
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Run the regression suite, printing each puzzle whose solutions changed
+fn print_regression_report() -> ExitCode {
+    let report = run_regression_suite();
+    for diff in &report.diffs {
+        let numbers: Vec<_> = diff.inputs.iter().map(|n| n.to_string()).collect();
+        println!("{} -> {}", numbers.join(" "), diff.target);
+        if diff.reordered {
+            println!("  reordered");
+        }
+        for text in &diff.missing {
+            println!("  - {}", text);
+        }
+        for text in &diff.unexpected {
+            println!("  + {}", text);
+        }
+    }
+    println!(
+        "{} puzzles checked, {} skipped, {} changed",
+        report.puzzles_checked,
+        report.puzzles_skipped,
+        report.diffs.len()
+    );
+
+    if report.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Compare two exports from `--export`, printing the diff. Like `diff`, this exits with 1 if
+/// they're different and 2 if they can't be read.
+fn print_export_diff(old_path: &str, new_path: &str) -> ExitCode {
+    let read = |path: &str| {
+        std::fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))
+    };
+    let exports = read(old_path).and_then(|old| Ok((old, read(new_path)?)));
+    let diff = exports
+        .and_then(|(old, new)| diff_solution_sets(&old, &new).map_err(|err| err.to_string()));
+
+    match diff {
+        Ok(diff) => {
+            print!("{}", diff.to_text());
+            println!(
+                "{} added, {} removed, {} changed, {} unchanged",
+                diff.added.len(),
+                diff.removed.len(),
+                diff.changed.len(),
+                diff.unchanged
+            );
+            if diff.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(count) = args.generate {
+        return print_puzzles(&args, count);
+    }
+
+    if args.regression {
+        return print_regression_report();
+    }
+
+    if let Some((old, new)) = &args.diff {
+        return print_export_diff(old, new);
+    }
+
+    if args.export {
+        return match export_solution_set(&args.inputs, &args.config) {
+            Ok(export) => {
+                print!("{}", export);
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    if args.quick {
+        return match quick_solution(&args.inputs, &args.config) {
+            Ok(solution) => {
+                print_solutions(&[solution], args.format);
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    match solve(&args.inputs, &args.config) {
+        Ok(solutions) => {
+            print_solutions(&solutions.collect::<Vec<_>>(), args.format);
+            if !args.config.is_exhaustive() {
+                eprintln!("Beam search can miss solutions, so there may be more than these");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,43 @@
+/* C interface to the make ten solver, built with `cargo build -p make-ten-core --features ffi` */
+
+#ifndef MAKE_TEN_H
+#define MAKE_TEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    MAKE_TEN_OK = 0,
+    MAKE_TEN_NULL_POINTER = 1,
+    MAKE_TEN_INVALID_INPUT = 2,
+    MAKE_TEN_INDEX_OUT_OF_RANGE = 3,
+    /* The solver panicked, which is a bug in the solver */
+    MAKE_TEN_INTERNAL = 4,
+} MakeTenStatus;
+
+typedef struct MakeTenSolutions MakeTenSolutions;
+
+/* Solve the puzzle, returns NULL and sets `status` on failure */
+MakeTenSolutions *make_ten_solve(const int32_t *inputs, size_t len, int32_t target, MakeTenStatus *status);
+
+size_t make_ten_solutions_len(const MakeTenSolutions *solutions);
+
+uint32_t make_ten_solution_complexity(const MakeTenSolutions *solutions, size_t index);
+
+/* A stable hash of the solution's canonical form, the same for equivalent solutions */
+uint64_t make_ten_solution_fingerprint(const MakeTenSolutions *solutions, size_t index);
+
+/* Copy the solution text into `buffer` (nul terminated), writing the full text length to `text_len` */
+MakeTenStatus make_ten_solution_text(const MakeTenSolutions *solutions, size_t index, char *buffer, size_t buffer_len, size_t *text_len);
+
+void make_ten_solutions_free(MakeTenSolutions *solutions);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
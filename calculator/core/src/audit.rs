@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::generate::SplitStrategy;
+use crate::maths::{EvaluatedExpr, Expression, OperationKind};
+use crate::validate::InputError;
+use crate::{solve, CalcError, SolverConfig};
+
+/// The most numbers that `audit_completeness` can check, as it builds every expression for
+/// them without any pruning
+pub const MAX_AUDIT_INPUTS: usize = 5;
+
+/// The result of `audit_completeness`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletenessReport {
+    /// How many distinct solutions the solver found
+    pub solutions_found: usize,
+    /// How many expressions the brute force built
+    pub expressions_checked: usize,
+    /// The text of a solution from each equivalence class that the brute force found but the
+    /// solver didn't, smallest fingerprint first
+    pub missed: Vec<String>,
+    /// The text of each solution that the solver found but the brute force didn't, which means
+    /// the solver's results aren't right rather than incomplete
+    pub extra: Vec<String>,
+}
+
+impl CompletenessReport {
+    /// Whether the solver found exactly the distinct solutions that the brute force did
+    pub fn passed(&self) -> bool {
+        self.missed.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Every expression of the inputs in this order, with every shape of tree and every
+/// operator, and with the operands of each operation both ways around if `swap` is set.
+/// Nothing is pruned except for operations that overflow.
+fn every_expression(
+    inputs: &[i32],
+    operations: &[OperationKind],
+    swap: bool,
+) -> Vec<EvaluatedExpr> {
+    if let [number] = inputs {
+        return vec![Expression::new_num(*number)];
+    }
+
+    let mut expressions = Vec::new();
+    for split in 1..inputs.len() {
+        let left = every_expression(&inputs[..split], operations, swap);
+        let right = every_expression(&inputs[split..], operations, swap);
+        for left_expr in &left {
+            for right_expr in &right {
+                for &kind in operations {
+                    expressions.extend(Expression::new_op_unpruned(
+                        left_expr.clone(),
+                        right_expr.clone(),
+                        kind,
+                    ));
+                    if swap {
+                        expressions.extend(Expression::new_op_unpruned(
+                            right_expr.clone(),
+                            left_expr.clone(),
+                            kind,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    expressions
+}
+
+/// Check that the solver finds every distinct solution for the inputs by comparing it against
+/// a brute force that builds every expression without any of the generator's pruning: every
+/// shape of tree over the inputs in order with the operands of each operation both ways
+/// around for `SplitStrategy::Contiguous`, or over every order of the inputs for
+/// `SplitStrategy::AllPartitions`. Solutions are compared by their canonical form, and the
+/// brute force's ones are filtered by the config's caps, `max_depth` and
+/// `max_operator_kinds` the same way as the solver's.
+///
+/// The solver only finds every solution in `SolverMode::Exhaustive`, as it leaves out the
+/// ones that a simpler solution stands in for otherwise, and beam search mode can miss any
+/// of them. `max_results` is ignored.
+/// Fails if there are more than `MAX_AUDIT_INPUTS` numbers, or if they're outside of the limits.
+pub fn audit_completeness(
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<CompletenessReport, CalcError> {
+    let config = SolverConfig {
+        max_results: None,
+        ..config.resolved()
+    };
+    config.validate(inputs)?;
+    if inputs.len() > MAX_AUDIT_INPUTS {
+        return Err(InputError::TooManyInputs {
+            max: MAX_AUDIT_INPUTS,
+            found: inputs.len(),
+        }
+        .into());
+    }
+
+    let solutions: Vec<_> = solve(inputs, &config)?.collect();
+    let found: HashSet<u64> = solutions
+        .iter()
+        .map(|solution| solution.fingerprint)
+        .collect();
+
+    let orders: Vec<Vec<i32>> = match config.split_strategy {
+        SplitStrategy::Contiguous => vec![inputs.to_vec()],
+        SplitStrategy::AllPartitions => inputs
+            .iter()
+            .copied()
+            .permutations(inputs.len())
+            .unique()
+            .collect(),
+    };
+    let swap = config.split_strategy == SplitStrategy::Contiguous;
+
+    let pruning = config.pruning(inputs, Some(config.target));
+    let normalizer = config.normalizer(inputs);
+    let mut expressions_checked = 0;
+    // The text of a solution for each fingerprint that the brute force found
+    let mut expected = BTreeMap::new();
+
+    for order in orders {
+        for mut expr in every_expression(&order, &config.operations, swap) {
+            expressions_checked += 1;
+            if expr.value() != config.target
+                || expr.exact_value().is_none()
+                || !pruning.within_caps(&expr)
+            {
+                continue;
+            }
+
+            let canonical = normalizer.canonical(&mut expr);
+            let fingerprint = canonical.fingerprint();
+            if expected.contains_key(&fingerprint) || !normalizer.within_depth(&canonical) {
+                continue;
+            }
+            if let Some(solution) = normalizer.solution(canonical, expr) {
+                expected.insert(fingerprint, solution.text);
+            }
+        }
+    }
+
+    Ok(CompletenessReport {
+        solutions_found: solutions.len(),
+        expressions_checked,
+        missed: expected
+            .iter()
+            .filter(|(fingerprint, _)| !found.contains(fingerprint))
+            .map(|(_, text)| text.clone())
+            .collect(),
+        extra: solutions
+            .into_iter()
+            .filter(|solution| !expected.contains_key(&solution.fingerprint))
+            .map(|solution| solution.text)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SolverMode;
+
+    #[test]
+    fn exhaustive_mode_is_complete() {
+        let puzzles: [&[i32]; 5] = [
+            &[3, 7, 2, 8],
+            &[1, 2, 3, 4],
+            &[1, 1, 9, 9, 5],
+            &[0, 0, 5, 5],
+            &[0, 2, 5, 1, 0],
+        ];
+        for split_strategy in [SplitStrategy::Contiguous, SplitStrategy::AllPartitions] {
+            let config = SolverConfig {
+                mode: SolverMode::Exhaustive,
+                split_strategy,
+                ..SolverConfig::default()
+            };
+            for inputs in puzzles {
+                let report = audit_completeness(inputs, &config).unwrap();
+                assert!(
+                    report.passed(),
+                    "{:?} with {:?}: {:?}",
+                    inputs,
+                    split_strategy,
+                    report
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+
+use crate::maths::canonical::canonicalize;
+use crate::maths::EvaluatedExpr;
+use crate::shuffle::fully_shuffle_expr;
+use crate::SolverConfig;
+
+/// A fixed set of puzzles to benchmark the solver with, so runs on different builds and
+/// devices can be compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BenchmarkPreset {
+    /// Puzzles with 4 numbers, which take a few milliseconds each
+    Quick,
+    /// Puzzles with 5 numbers
+    Standard,
+    /// Puzzles with 6 numbers, which can take seconds each in wasm
+    Large,
+}
+
+impl BenchmarkPreset {
+    /// Every preset
+    pub const ALL: &'static [BenchmarkPreset] = &[
+        BenchmarkPreset::Quick,
+        BenchmarkPreset::Standard,
+        BenchmarkPreset::Large,
+    ];
+
+    /// The name of the preset, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            BenchmarkPreset::Quick => "quick",
+            BenchmarkPreset::Standard => "standard",
+            BenchmarkPreset::Large => "large",
+        }
+    }
+
+    /// Get the preset with a name, the reverse of `name`
+    pub fn from_name(name: &str) -> Option<BenchmarkPreset> {
+        BenchmarkPreset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.name() == name)
+    }
+
+    /// The puzzles in the preset. Changing them makes old results incomparable, so new puzzles
+    /// should go in a new preset instead.
+    pub fn puzzles(self) -> &'static [&'static [i32]] {
+        match self {
+            BenchmarkPreset::Quick => &[
+                &[1, 2, 3, 4],
+                &[3, 7, 2, 8],
+                &[9, 9, 1, 1],
+                &[5, 5, 5, 5],
+                &[0, 4, 6, 8],
+                &[2, 3, 5, 7],
+            ],
+            BenchmarkPreset::Standard => &[
+                &[1, 2, 3, 4, 5],
+                &[3, 7, 2, 8, 5],
+                &[9, 8, 7, 6, 5],
+                &[2, 2, 3, 3, 4],
+                &[0, 1, 5, 6, 9],
+            ],
+            BenchmarkPreset::Large => &[
+                &[1, 2, 3, 4, 5, 6],
+                &[3, 7, 2, 8, 5, 1],
+                &[9, 8, 7, 6, 5, 4],
+            ],
+        }
+    }
+}
+
+/// A stage of solving, in the order that they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Generating every expression and keeping the ones that make the target
+    Generation,
+    /// Removing the equivalent solutions by their canonical form
+    Dedup,
+    /// Shuffling the distinct solutions into their display form
+    Shuffle,
+    /// Writing the solutions as text and working out their complexity
+    Render,
+}
+
+impl Stage {
+    /// Every stage, in the order that they run
+    pub const ALL: [Stage; 4] = [
+        Stage::Generation,
+        Stage::Dedup,
+        Stage::Shuffle,
+        Stage::Render,
+    ];
+
+    /// The name of the stage, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Generation => "generation",
+            Stage::Dedup => "dedup",
+            Stage::Shuffle => "shuffle",
+            Stage::Render => "render",
+        }
+    }
+}
+
+/// Where a benchmark gets the time and allocation counts from, which depends on the host, e.g.
+/// `Date.now()` in the browser
+pub trait Probe {
+    /// The current time in milliseconds, from any fixed starting point
+    fn now_ms(&self) -> f64;
+
+    /// How many allocations have been made so far and how many bytes they asked for in total,
+    /// or None if the host doesn't count them
+    fn allocations(&self) -> Option<(u64, u64)>;
+}
+
+/// What one stage took across every puzzle in a benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageStats {
+    pub stage: Stage,
+    /// The total time in milliseconds
+    pub time_ms: f64,
+    /// How many allocations were made, which is 0 if the probe doesn't count them
+    pub allocations: u64,
+    /// How many bytes the allocations asked for, which is 0 if the probe doesn't count them
+    pub allocated_bytes: u64,
+}
+
+/// The results of `benchmark`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    pub preset: BenchmarkPreset,
+    /// How many puzzles were solved
+    pub puzzles: usize,
+    /// How many expressions the generator produced across every puzzle. It's given the target
+    /// like in `solve`, so it only produces the ones that can make the target.
+    pub expressions: usize,
+    /// How many distinct solutions were found across every puzzle
+    pub solutions: usize,
+    /// Whether the probe counted allocations
+    pub counts_allocations: bool,
+    /// The stats for each stage, in the order of `Stage::ALL`
+    pub stages: Vec<StageStats>,
+}
+
+impl BenchmarkReport {
+    /// The total time of every stage in milliseconds
+    pub fn total_ms(&self) -> f64 {
+        self.stages.iter().map(|stats| stats.time_ms).sum()
+    }
+}
+
+/// Time some work and add it to a stage's stats
+fn measure<T>(probe: &dyn Probe, stats: &mut StageStats, work: impl FnOnce() -> T) -> T {
+    let allocations_before = probe.allocations();
+    let start = probe.now_ms();
+    let result = work();
+    stats.time_ms += probe.now_ms() - start;
+
+    if let (Some(before), Some(after)) = (allocations_before, probe.allocations()) {
+        stats.allocations += after.0 - before.0;
+        stats.allocated_bytes += after.1 - before.1;
+    }
+    result
+}
+
+/// Solve every puzzle in a preset with the default config and measure each stage of solving
+/// separately, so performance can be measured in the deployed build (e.g. wasm in a browser)
+/// and not just natively. Each stage runs over the whole puzzle before the next one starts,
+/// which is the same work as `solve` but in a different order.
+pub fn benchmark(preset: BenchmarkPreset, probe: &dyn Probe) -> BenchmarkReport {
+    let config = SolverConfig::default();
+    let mut stages: Vec<StageStats> = Stage::ALL
+        .iter()
+        .map(|&stage| StageStats {
+            stage,
+            time_ms: 0.0,
+            allocations: 0,
+            allocated_bytes: 0,
+        })
+        .collect();
+    let mut expressions = 0;
+    let mut solutions = 0;
+
+    for inputs in preset.puzzles() {
+        let found = measure(probe, &mut stages[0], || {
+            let mut found: Vec<EvaluatedExpr> = Vec::new();
+            for expr in config.expressions(inputs, Some(config.target)) {
+                expressions += 1;
+                if expr.value() == config.target {
+                    found.push(expr);
+                }
+            }
+            found
+        });
+
+        let distinct = measure(probe, &mut stages[1], || {
+            let mut seen = HashSet::new();
+            found
+                .into_iter()
+                .map(|expr| (canonicalize(&expr), expr))
+                .filter(|(canonical, _)| seen.insert(canonical.fingerprint()))
+                .collect::<Vec<_>>()
+        });
+
+        let shuffled = measure(probe, &mut stages[2], || {
+            distinct
+                .into_iter()
+                .map(|(canonical, expr)| {
+                    let mut expr = canonical.to_expr().unwrap_or(expr);
+                    fully_shuffle_expr(&mut expr);
+                    expr
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let rendered = measure(probe, &mut stages[3], || {
+            shuffled
+                .iter()
+                .map(|expr| {
+                    (
+                        expr.to_text(),
+                        expr.complexity_with(config.complexity_model),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        solutions += rendered.len();
+    }
+
+    BenchmarkReport {
+        preset,
+        puzzles: preset.puzzles().len(),
+        expressions,
+        solutions,
+        counts_allocations: probe.allocations().is_some(),
+        stages,
+    }
+}
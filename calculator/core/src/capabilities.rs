@@ -0,0 +1,31 @@
+use crate::maths::OperationKind;
+
+/// What this build of the solver supports, which depends on the enabled cargo features
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The operations that were compiled in
+    pub operations: Vec<OperationKind>,
+    /// The number type that expressions are evaluated with
+    pub numeric_backend: &'static str,
+    /// Whether solving can use multiple threads
+    pub parallel: bool,
+    /// The renderers that were compiled in, "text" and (with the `spoken` feature) "spoken"
+    pub renderers: Vec<&'static str>,
+    /// The version of the `make-ten-core` crate
+    pub version: &'static str,
+}
+
+/// Get the capabilities of this build of the solver
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        operations: OperationKind::ALL.to_vec(),
+        numeric_backend: "i32",
+        parallel: cfg!(feature = "parallel"),
+        renderers: if cfg!(feature = "spoken") {
+            vec!["text", "spoken"]
+        } else {
+            vec!["text"]
+        },
+        version: env!("CARGO_PKG_VERSION"),
+    }
+}
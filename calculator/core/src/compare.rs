@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use crate::{solve, CalcError, Solution, SolverConfig};
+
+/// The difference between the solutions for the same puzzle under two configs, see
+/// `compare_configs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// The solutions that are only found with the first config, in its usual order
+    pub only_first: Vec<Solution>,
+    /// The solutions that are only found with the second config, in its usual order
+    pub only_second: Vec<Solution>,
+    /// How many solutions both configs found
+    pub shared: usize,
+}
+
+/// Solve a puzzle with two configs and compare the solutions by their canonical form, e.g. to
+/// check which solutions a change to the pruning or dedup rules gains or loses before it
+/// ships. `max_results` is ignored, so every solution is compared.
+pub fn compare_configs(
+    inputs: &[i32],
+    first: &SolverConfig,
+    second: &SolverConfig,
+) -> Result<ConfigDiff, CalcError> {
+    let solve_all = |config: &SolverConfig| -> Result<Vec<Solution>, CalcError> {
+        let config = SolverConfig {
+            max_results: None,
+            ..config.clone()
+        };
+        Ok(solve(inputs, &config)?.collect())
+    };
+    let first = solve_all(first)?;
+    let second = solve_all(second)?;
+
+    let first_canonicals: HashSet<_> = first.iter().map(|s| s.canonical.clone()).collect();
+    let second_canonicals: HashSet<_> = second.iter().map(|s| s.canonical.clone()).collect();
+    let shared = first_canonicals.intersection(&second_canonicals).count();
+
+    Ok(ConfigDiff {
+        only_first: first
+            .into_iter()
+            .filter(|s| !second_canonicals.contains(&s.canonical))
+            .collect(),
+        only_second: second
+            .into_iter()
+            .filter(|s| !first_canonicals.contains(&s.canonical))
+            .collect(),
+        shared,
+    })
+}
@@ -0,0 +1,40 @@
+use crate::maths::{Depth, Evaluate};
+use crate::metrics::expression_metrics;
+use crate::{solve, CalcError, Solution, SolverConfig};
+
+/// The header row of `solutions_to_csv`
+const HEADER: &str = "expression,value,complexity,depth,operators";
+
+/// Solve the inputs and write the solutions as CSV, see `solutions_to_csv`
+pub fn export_csv(inputs: &[i32], config: &SolverConfig) -> Result<String, CalcError> {
+    let solutions: Vec<Solution> = solve(inputs, config)?.collect();
+    Ok(solutions_to_csv(&solutions))
+}
+
+/// Write the solutions as CSV, e.g. for printable answer sheets. There's a header row, then one
+/// row per solution with its expression, value, complexity, depth, and the symbols of the
+/// operators that it uses (e.g. `+*`). None of the fields can contain commas or quotes, so
+/// nothing needs to be quoted. Rows end with `\r\n`, as spreadsheets expect.
+pub fn solutions_to_csv(solutions: &[Solution]) -> String {
+    let mut csv = format!("{}\r\n", HEADER);
+
+    for solution in solutions {
+        let operators: String = expression_metrics(&solution.expression)
+            .operator_counts
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(op, _)| op.symbol())
+            .collect();
+
+        csv.push_str(&format!(
+            "{},{},{},{},{}\r\n",
+            solution.text,
+            solution.expression.evaluate(),
+            solution.complexity,
+            solution.expression.depth(),
+            operators
+        ));
+    }
+
+    csv
+}
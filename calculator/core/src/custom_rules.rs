@@ -0,0 +1,179 @@
+use std::ops::DerefMut;
+
+use crate::maths::{EvaluatedExpr, Expression, OperationKind};
+use crate::parse::parse_template;
+use crate::shuffle::RewriteRule;
+use crate::CalcError;
+
+/// An expression where letters stand for any sub-expression, see `parse_template`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Template {
+    Var(char),
+    Num(i32),
+    Op(Box<Template>, OperationKind, Box<Template>),
+}
+
+impl Template {
+    /// Match the expression against the template, binding each variable to the
+    /// sub-expression it stands for. A variable that's used more than once has to stand for
+    /// the same sub-expression each time.
+    fn matches<'a>(
+        &self,
+        expr: &'a EvaluatedExpr,
+        bindings: &mut Vec<(char, &'a EvaluatedExpr)>,
+    ) -> bool {
+        match (self, &**expr) {
+            (Template::Var(name), _) => match bindings.iter().find(|(bound, _)| bound == name) {
+                Some((_, bound)) => **bound == *expr,
+                None => {
+                    bindings.push((*name, expr));
+                    true
+                }
+            },
+            (Template::Num(num), Expression::Num(value)) => num == value,
+            (Template::Op(left, kind, right), Expression::Op(op)) => {
+                *kind == op.kind
+                    && left.matches(&op.left, bindings)
+                    && right.matches(&op.right, bindings)
+            }
+            _ => false,
+        }
+    }
+
+    /// Build the expression that the template stands for with the variables bound by
+    /// `matches`, or None if an operation overflows
+    fn build(&self, bindings: &[(char, &EvaluatedExpr)]) -> Option<EvaluatedExpr> {
+        match self {
+            Template::Var(name) => bindings
+                .iter()
+                .find(|(bound, _)| bound == name)
+                .map(|(_, expr)| (*expr).clone()),
+            Template::Num(num) => Some(Expression::new_num(*num)),
+            Template::Op(left, kind, right) => {
+                Expression::new_op_unpruned(left.build(bindings)?, right.build(bindings)?, *kind)
+            }
+        }
+    }
+
+    /// Every variable in the template, in the order they're first used
+    fn variables(&self, variables: &mut Vec<char>) {
+        match self {
+            Template::Var(name) if !variables.contains(name) => variables.push(*name),
+            Template::Op(left, _, right) => {
+                left.variables(variables);
+                right.variables(variables);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A rewrite rule written as a pair of templates, where lowercase letters stand for any
+/// sub-expression, e.g. `a * a` to `a ^ 2`. Unlike a `RewriteRule`, it can be made at runtime,
+/// e.g. from js.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateRule {
+    name: String,
+    from_text: String,
+    to_text: String,
+    from: Template,
+    to: Template,
+}
+
+impl TemplateRule {
+    /// Make a rule that rewrites expressions matching `from` into `to`, e.g. `a * a` into
+    /// `a ^ 2`. Fails with `CalcError::ParseError` if either template can't be parsed, if
+    /// `from` isn't an operation, or if `to` uses a variable that isn't in `from`, where the
+    /// span is in the template with the problem.
+    pub fn parse(name: &str, from: &str, to: &str) -> Result<TemplateRule, CalcError> {
+        let from_template = parse_template(from)?;
+        if !matches!(from_template, Template::Op(..)) {
+            return Err(CalcError::ParseError {
+                message: "The pattern has to be an operation".to_string(),
+                span: 0..from.len(),
+            });
+        }
+
+        let to_template = parse_template(to)?;
+        let (mut bound, mut used) = (Vec::new(), Vec::new());
+        from_template.variables(&mut bound);
+        to_template.variables(&mut used);
+        if let Some(name) = used.iter().find(|name| !bound.contains(name)) {
+            let start = to.find(*name).unwrap_or(0);
+            return Err(CalcError::ParseError {
+                message: format!("'{}' isn't in the pattern", name),
+                span: start..start + 1,
+            });
+        }
+
+        Ok(TemplateRule {
+            name: name.to_string(),
+            from_text: from.to_string(),
+            to_text: to.to_string(),
+            from: from_template,
+            to: to_template,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The template that the rule matches, as it was written
+    pub fn from(&self) -> &str {
+        &self.from_text
+    }
+
+    /// The template that the rule rewrites to, as it was written
+    pub fn to(&self) -> &str {
+        &self.to_text
+    }
+
+    /// Rewrite the expression if it matches, and return whether it did. Rewrites that would
+    /// change the expression's value (or don't divide exactly) are skipped.
+    fn apply(&self, expr: &mut EvaluatedExpr) -> bool {
+        let mut bindings = Vec::new();
+        if !self.from.matches(expr, &mut bindings) {
+            return false;
+        }
+        match self.to.build(&bindings) {
+            Some(rewritten)
+                if rewritten.exact_value() == Some(expr.value()) && rewritten != *expr =>
+            {
+                *expr = rewritten;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A normalization rule that's added to a solve with `SolverConfig::rewrite_rules`, on top of
+/// the built in `SHUFFLE_RULES`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomRule {
+    /// A rule written in rust, for embedders
+    Native(RewriteRule),
+    /// A rule written as templates, e.g. from js
+    Template(TemplateRule),
+}
+
+impl CustomRule {
+    pub fn name(&self) -> &str {
+        match self {
+            CustomRule::Native(rule) => rule.name,
+            CustomRule::Template(rule) => rule.name(),
+        }
+    }
+
+    /// Apply the rule to an expression if it matches, and return whether it did
+    pub(crate) fn apply(&self, expr: &mut EvaluatedExpr) -> bool {
+        match self {
+            CustomRule::Native(rule) => match expr.deref_mut() {
+                Expression::Op(op) => rule.apply(op),
+                Expression::Num(_) => false,
+            },
+            CustomRule::Template(rule) => rule.apply(expr),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{expression_metrics, solve, CalcError, Solution, SolverConfig, Technique};
+
+/// The complexity at which a puzzle's simplest solution counts as maximally hard
+const MAX_RATED_COMPLEXITY: u32 = 400;
+
+/// A puzzle with at most this many solutions counts as having few solutions
+const FEW_SOLUTIONS: usize = 3;
+
+/// Solutions with parenthesis nested at least this deep count as deeply nested
+const DEEP_NESTING: usize = 2;
+
+/// A reason that a puzzle is hard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DifficultyFactor {
+    /// There are only a few solutions to find
+    FewSolutions,
+    /// Every solution uses the power operator
+    RequiresPower,
+    /// Every solution goes through a large intermediate value
+    LargeIntermediates,
+    /// Every solution has deeply nested parenthesis
+    DeepNesting,
+}
+
+impl DifficultyFactor {
+    /// The name of the factor, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            DifficultyFactor::FewSolutions => "fewSolutions",
+            DifficultyFactor::RequiresPower => "requiresPower",
+            DifficultyFactor::LargeIntermediates => "largeIntermediates",
+            DifficultyFactor::DeepNesting => "deepNesting",
+        }
+    }
+}
+
+/// A difficulty rating along with what it was made up of, see `rate_difficulty`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifficultyBreakdown {
+    /// The rating from 0 (trivial) to 100 (very hard)
+    pub rating: u32,
+    /// The points (out of 60) from the complexity of the simplest solution
+    pub complexity_points: u32,
+    /// The points (out of 40) from how few solutions there are
+    pub scarcity_points: u32,
+    /// How many distinct solutions there are
+    pub solution_count: usize,
+    /// The reasons that the puzzle is hard, if there are any
+    pub factors: Vec<DifficultyFactor>,
+}
+
+/// Rate how hard a puzzle is on a scale from 0 (trivial) to 100 (very hard).
+/// Fails with `CalcError::Unsolvable` if the puzzle has no solutions.
+///
+/// Up to 60 points come from the complexity of the simplest solution, since that's the one
+/// a player is most likely to find. Up to 40 points come from how few solutions there are,
+/// as a puzzle with a single solution is much harder to stumble onto than one with dozens.
+pub fn rate_difficulty(inputs: &[i32], config: &SolverConfig) -> Result<u32, CalcError> {
+    Ok(difficulty_breakdown(inputs, config)?.rating)
+}
+
+/// Same as `rate_difficulty`, except the points and the factors that make the puzzle hard
+/// are returned too, so the rating can be explained
+pub fn difficulty_breakdown(
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<DifficultyBreakdown, CalcError> {
+    let config = SolverConfig {
+        max_results: None,
+        ..config.clone()
+    };
+
+    let solutions: Vec<Solution> = solve(inputs, &config)?.collect();
+    let simplest = solutions.first().ok_or(CalcError::Unsolvable)?;
+
+    let complexity_points =
+        simplest.complexity.min(MAX_RATED_COMPLEXITY) * 60 / MAX_RATED_COMPLEXITY;
+    let scarcity_points = 40 / solutions.len() as u32;
+
+    let all_use = |technique| {
+        solutions
+            .iter()
+            .all(|solution| solution.techniques.contains(&technique))
+    };
+
+    let mut factors = Vec::new();
+    if solutions.len() <= FEW_SOLUTIONS {
+        factors.push(DifficultyFactor::FewSolutions);
+    }
+    if all_use(Technique::Power) {
+        factors.push(DifficultyFactor::RequiresPower);
+    }
+    if all_use(Technique::LargeIntermediate) {
+        factors.push(DifficultyFactor::LargeIntermediates);
+    }
+    if solutions
+        .iter()
+        .all(|solution| expression_metrics(&solution.expression).nesting >= DEEP_NESTING)
+    {
+        factors.push(DifficultyFactor::DeepNesting);
+    }
+
+    Ok(DifficultyBreakdown {
+        rating: complexity_points + scarcity_points,
+        complexity_points,
+        scarcity_points,
+        solution_count: solutions.len(),
+        factors,
+    })
+}
@@ -0,0 +1,136 @@
+use crate::maths::{Expression, OperationKind};
+use crate::Solution;
+
+/// The label of a node in the tree, which is what gets compared when relabelling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Num(i32),
+    Op(OperationKind),
+}
+
+/// An expression tree flattened into post order, for the Zhang-Shasha algorithm
+struct PostOrderTree {
+    labels: Vec<Label>,
+    /// The index of the leftmost leaf under each node
+    leftmost: Vec<usize>,
+    /// The nodes that are the highest node with their leftmost leaf, in increasing order
+    keyroots: Vec<usize>,
+}
+
+impl PostOrderTree {
+    fn new(expr: &Expression) -> PostOrderTree {
+        let mut labels = Vec::new();
+        let mut leftmost = Vec::new();
+
+        // The indices of the subtrees that have been flattened but not yet attached to a parent
+        let mut finished = Vec::new();
+        let mut stack = vec![(expr, false)];
+        while let Some((node, children_visited)) = stack.pop() {
+            let index = labels.len();
+            match node {
+                Expression::Num(num) => {
+                    labels.push(Label::Num(*num));
+                    leftmost.push(index);
+                }
+                Expression::Op(op) if children_visited => {
+                    // Only the left subtree matters, since it has the leftmost leaf
+                    finished.pop();
+                    let left: usize = finished.pop().expect("Missing left subtree");
+                    labels.push(Label::Op(op.kind));
+                    leftmost.push(leftmost[left]);
+                }
+                Expression::Op(op) => {
+                    stack.push((node, true));
+                    stack.push((&op.right, false));
+                    stack.push((&op.left, false));
+                    continue;
+                }
+            }
+            finished.push(index);
+        }
+
+        let keyroots = (0..labels.len())
+            .filter(|&i| ((i + 1)..labels.len()).all(|k| leftmost[k] != leftmost[i]))
+            .collect();
+
+        PostOrderTree {
+            labels,
+            leftmost,
+            keyroots,
+        }
+    }
+}
+
+/// The ordered tree edit distance between two expressions: the fewest node insertions,
+/// deletions and relabellings (changing a number or an operation) that turn one into the
+/// other. This uses the Zhang-Shasha algorithm, which is fine for the small trees here.
+pub fn edit_distance(a: &Expression, b: &Expression) -> usize {
+    let a = PostOrderTree::new(a);
+    let b = PostOrderTree::new(b);
+
+    // The distance between the subtrees rooted at each pair of nodes
+    let mut tree_dist = vec![vec![0; b.labels.len()]; a.labels.len()];
+
+    for &i in &a.keyroots {
+        for &j in &b.keyroots {
+            let (a_start, b_start) = (a.leftmost[i], b.leftmost[j]);
+            let rows = i - a_start + 2;
+            let cols = j - b_start + 2;
+
+            // The distance between the forests a[a_start..a_start + x] and b[b_start..b_start + y]
+            let mut forest_dist = vec![vec![0; cols]; rows];
+            for x in 1..rows {
+                forest_dist[x][0] = forest_dist[x - 1][0] + 1;
+            }
+            for y in 1..cols {
+                forest_dist[0][y] = forest_dist[0][y - 1] + 1;
+            }
+
+            for x in 1..rows {
+                for y in 1..cols {
+                    let (node_a, node_b) = (a_start + x - 1, b_start + y - 1);
+                    let delete = forest_dist[x - 1][y] + 1;
+                    let insert = forest_dist[x][y - 1] + 1;
+
+                    if a.leftmost[node_a] == a_start && b.leftmost[node_b] == b_start {
+                        // Both forests are whole trees
+                        let relabel = (a.labels[node_a] != b.labels[node_b]) as usize;
+                        forest_dist[x][y] =
+                            delete.min(insert).min(forest_dist[x - 1][y - 1] + relabel);
+                        tree_dist[node_a][node_b] = forest_dist[x][y];
+                    } else {
+                        let p = a.leftmost[node_a] - a_start;
+                        let q = b.leftmost[node_b] - b_start;
+                        forest_dist[x][y] = delete
+                            .min(insert)
+                            .min(forest_dist[p][q] + tree_dist[node_a][node_b]);
+                    }
+                }
+            }
+        }
+    }
+
+    tree_dist[a.labels.len() - 1][b.labels.len() - 1]
+}
+
+/// Group solutions that are within `max_distance` edits of each other, e.g. to collapse near
+/// duplicates. Each solution joins the first group whose first solution is close enough,
+/// otherwise it starts a new group, so the groups keep the order of `solutions`.
+/// Returns the indices of the solutions in each group.
+pub fn cluster_solutions(solutions: &[Solution], max_distance: usize) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+
+    for (index, solution) in solutions.iter().enumerate() {
+        let cluster = clusters.iter_mut().find(|cluster| {
+            let representative = &solutions[cluster[0]].expression;
+            edit_distance(representative, &solution.expression) <= max_distance
+        });
+
+        match cluster {
+            Some(cluster) => cluster.push(index),
+            None => clusters.push(vec![index]),
+        }
+    }
+
+    clusters
+}
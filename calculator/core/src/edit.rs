@@ -0,0 +1,141 @@
+use std::ops::Range;
+
+use crate::maths::{EvaluatedExpr, Expression, OperationKind};
+use crate::CalcError;
+
+/// The nodes of an expression in pre-order (each operation comes before its left and then its
+/// right operand), so a node's id is its index here and the root's id is 0
+pub fn expression_nodes(expr: &EvaluatedExpr) -> Vec<&EvaluatedExpr> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![expr];
+    while let Some(node) = stack.pop() {
+        nodes.push(node);
+        if let Expression::Op(op) = &**node {
+            stack.push(&op.right);
+            stack.push(&op.left);
+        }
+    }
+    nodes
+}
+
+/// The byte range of each node in the expression's text (see `to_text`), in the same order as
+/// `expression_nodes`. A node's range includes any parenthesis around it, e.g. hovering over
+/// the `+` in `(3 + 7) * 2` can highlight `(3 + 7)`.
+pub fn node_spans(expr: &EvaluatedExpr) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    // Each node is paired with its parent's operation and whether it's the left child (if it
+    // has a parent), and where its text starts
+    type Parent = Option<(OperationKind, bool)>;
+    let mut stack: Vec<(&EvaluatedExpr, Parent, usize)> = vec![(expr, None, 0)];
+    while let Some((node, parent, start)) = stack.pop() {
+        let bare_len = node.to_text().len();
+        let len = match parent {
+            Some((parent_op, is_left)) => node.to_text_child(parent_op, is_left).len(),
+            None => bare_len,
+        };
+        spans.push(start..start + len);
+
+        if let Expression::Op(op) = &**node {
+            // Skip the opening parenthesis if there is one
+            let inner_start = start + (len - bare_len) / 2;
+            let left_len = op.left.to_text_child(op.kind, true).len();
+            // The operands are separated by the operator with a space on each side
+            let right_start = inner_start + left_len + 3;
+
+            stack.push((&op.right, Some((op.kind, false)), right_start));
+            stack.push((&op.left, Some((op.kind, true)), inner_start));
+        }
+    }
+    spans
+}
+
+/// An edit to a single operation
+#[derive(Clone, Copy)]
+enum Edit {
+    Operator(OperationKind),
+    SwapChildren,
+}
+
+/// Replace the operator of the operation with the id `node_id` (see `expression_nodes`),
+/// returning the edited expression with its values updated. Fails with `CalcError::InvalidEdit`
+/// if the node isn't an operation or a value stops being a whole number (e.g. an inexact
+/// division), or `CalcError::Overflow` if a value overflows.
+pub fn swap_operator(
+    expr: &EvaluatedExpr,
+    node_id: usize,
+    kind: OperationKind,
+) -> Result<EvaluatedExpr, CalcError> {
+    edit_node(expr, node_id, Edit::Operator(kind))
+}
+
+/// Swap the left and right operands of the operation with the id `node_id`, see `swap_operator`
+pub fn swap_children(expr: &EvaluatedExpr, node_id: usize) -> Result<EvaluatedExpr, CalcError> {
+    edit_node(expr, node_id, Edit::SwapChildren)
+}
+
+fn edit_node(expr: &EvaluatedExpr, node_id: usize, edit: Edit) -> Result<EvaluatedExpr, CalcError> {
+    let mut next_id = 0;
+    match rebuild(expr, node_id, edit, &mut next_id)? {
+        Some(edited) => Ok(edited),
+        None => Err(CalcError::InvalidEdit(format!(
+            "There's no node with the id {}",
+            node_id
+        ))),
+    }
+}
+
+/// Rebuild the expression with the edit applied, or None if the node isn't in it. `next_id`
+/// is the id of `expr`, and is moved past all of its nodes.
+fn rebuild(
+    expr: &EvaluatedExpr,
+    node_id: usize,
+    edit: Edit,
+    next_id: &mut usize,
+) -> Result<Option<EvaluatedExpr>, CalcError> {
+    let id = *next_id;
+    *next_id += 1;
+
+    let op = match &**expr {
+        Expression::Op(op) => op,
+        Expression::Num(_) if id == node_id => {
+            return Err(CalcError::InvalidEdit(format!(
+                "Node {} is a number, not an operation",
+                node_id
+            )));
+        }
+        Expression::Num(_) => return Ok(None),
+    };
+
+    let (mut left, mut right, mut kind) = (op.left.clone(), op.right.clone(), op.kind);
+    if id == node_id {
+        match edit {
+            Edit::Operator(new_kind) => kind = new_kind,
+            Edit::SwapChildren => std::mem::swap(&mut left, &mut right),
+        }
+    } else if let Some(edited) = rebuild(&op.left, node_id, edit, next_id)? {
+        left = edited;
+    } else if let Some(edited) = rebuild(&op.right, node_id, edit, next_id)? {
+        right = edited;
+    } else {
+        return Ok(None);
+    }
+
+    let (left_val, right_val) = (left.value(), right.value());
+    if kind == OperationKind::Divide && (right_val == 0 || left_val.wrapping_rem(right_val) != 0) {
+        return Err(CalcError::InvalidEdit(format!(
+            "{} / {} isn't a whole number",
+            left_val, right_val
+        )));
+    }
+    #[cfg(feature = "power")]
+    if kind == OperationKind::Power && right_val < 0 {
+        return Err(CalcError::InvalidEdit(format!(
+            "{} ^ {} has a negative exponent",
+            left_val, right_val
+        )));
+    }
+
+    Expression::new_op_unpruned(left, right, kind)
+        .map(Some)
+        .ok_or(CalcError::Overflow)
+}
@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use crate::maths::canonical::canonicalize;
+use crate::parse::parse_expression;
+use crate::CalcError;
+
+/// Group expressions from anywhere (e.g. answers submitted by different players) by their
+/// canonical form, so that answers which only differ by the order or grouping of their terms
+/// end up together. The groups are in the order of their first expression, and each group has
+/// the indices of its expressions in `texts`. Fails with the first text that doesn't parse.
+pub fn group_equivalent<S: AsRef<str>>(texts: &[S]) -> Result<Vec<Vec<usize>>, CalcError> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    // The index in `groups` of each canonical form
+    let mut group_of: HashMap<_, usize> = HashMap::new();
+
+    for (index, text) in texts.iter().enumerate() {
+        let expr = parse_expression(text.as_ref())?;
+        let canonical = canonicalize(&expr);
+        match group_of.get(&canonical) {
+            Some(&group) => groups[group].push(index),
+            None => {
+                group_of.insert(canonical, groups.len());
+                groups.push(vec![index]);
+            }
+        }
+    }
+
+    Ok(groups)
+}
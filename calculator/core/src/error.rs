@@ -0,0 +1,70 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::validate::InputError;
+
+/// Everything that can go wrong in the solver's public functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalcError {
+    /// The inputs were outside of the configured limits
+    InvalidInput(InputError),
+    /// A calculation went outside of the `i32` range
+    Overflow,
+    /// There are no solutions where at least one was needed
+    Unsolvable,
+    /// The work was cancelled before it finished
+    Cancelled,
+    /// Some text couldn't be parsed, `span` is the byte range of the problem in the text
+    ParseError { message: String, span: Range<usize> },
+    /// An edit to an expression would make it invalid, e.g. by making a division inexact
+    InvalidEdit(String),
+    /// A cursor to pick up from doesn't fit the generator, e.g. because it was saved for other
+    /// inputs or by a version that generates the expressions in a different order
+    InvalidCursor,
+}
+
+impl CalcError {
+    /// The name of the error's variant, which the bindings use as the error name/code
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CalcError::InvalidInput(_) => "InvalidInput",
+            CalcError::Overflow => "Overflow",
+            CalcError::Unsolvable => "Unsolvable",
+            CalcError::Cancelled => "Cancelled",
+            CalcError::ParseError { .. } => "ParseError",
+            CalcError::InvalidEdit(_) => "InvalidEdit",
+            CalcError::InvalidCursor => "InvalidCursor",
+        }
+    }
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalcError::InvalidInput(err) => err.fmt(f),
+            CalcError::Overflow => write!(f, "The calculation overflowed"),
+            CalcError::Unsolvable => write!(f, "There are no solutions"),
+            CalcError::Cancelled => write!(f, "The solve was cancelled"),
+            CalcError::ParseError { message, span } => {
+                write!(f, "{} at {}..{}", message, span.start, span.end)
+            }
+            CalcError::InvalidEdit(message) => write!(f, "{}", message),
+            CalcError::InvalidCursor => write!(f, "The cursor doesn't fit the generator"),
+        }
+    }
+}
+
+impl std::error::Error for CalcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CalcError::InvalidInput(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<InputError> for CalcError {
+    fn from(err: InputError) -> Self {
+        CalcError::InvalidInput(err)
+    }
+}
@@ -0,0 +1,50 @@
+use crate::maths::{Evaluate, EvaluatedExpr, Expression};
+use crate::phrases::Phrases;
+
+/// Explain how to work out an expression, as one sentence per operation in the order they
+/// have to be done (e.g. "First multiply 3 and 4 to get 12", "Finally subtract 2 from 12 to
+/// get 10"). Operations are explained children first, left before right.
+pub fn explain(expr: &EvaluatedExpr, phrases: &Phrases) -> Vec<String> {
+    let mut steps = Vec::new();
+
+    // A post order walk with an explicit stack, where the flag marks that the node's
+    // children have already been pushed
+    let mut stack = vec![(expr, false)];
+    while let Some((node, children_visited)) = stack.pop() {
+        if let Expression::Op(op) = &**node {
+            if children_visited {
+                steps.push(
+                    phrases
+                        .explanation(op.kind)
+                        .replace("{left}", &op.left.evaluate().to_string())
+                        .replace("{right}", &op.right.evaluate().to_string())
+                        .replace("{result}", &node.evaluate().to_string()),
+                );
+            } else {
+                stack.push((node, true));
+                stack.push((&op.right, false));
+                stack.push((&op.left, false));
+            }
+        }
+    }
+
+    if steps.is_empty() {
+        return vec![phrases
+            .number
+            .replace("{result}", &expr.evaluate().to_string())];
+    }
+
+    let last = steps.len() - 1;
+    steps
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let template = match i {
+                0 => &phrases.first,
+                i if i == last => &phrases.last,
+                _ => &phrases.then,
+            };
+            template.replace("{step}", &step)
+        })
+        .collect()
+}
@@ -0,0 +1,46 @@
+use crate::rng::Rng;
+use crate::{solve, CalcError, Solution, SolverConfig};
+
+/// Pick one solution to feature, e.g. for the daily puzzle's reveal screen. The pick leans
+/// towards the middle of the ranking so it's neither the trivial solution nor the monster:
+/// each solution's weight is how far it is from the nearest end of the ranking, so the
+/// simplest and most complex solutions are only picked if there are fewer than 3 solutions.
+/// The same seed always picks the same solution. `max_results` is ignored.
+/// Fails with `CalcError::Unsolvable` if there are no solutions.
+pub fn featured_solution(
+    inputs: &[i32],
+    config: &SolverConfig,
+    seed: u64,
+) -> Result<Solution, CalcError> {
+    let mut solutions: Vec<Solution> = solve(
+        inputs,
+        &SolverConfig {
+            max_results: None,
+            ..config.clone()
+        },
+    )?
+    .collect();
+
+    let count = solutions.len();
+    if count == 0 {
+        return Err(CalcError::Unsolvable);
+    }
+
+    let weight = |index: usize| index.min(count - 1 - index) as u64;
+    let total: u64 = (0..count).map(weight).sum();
+
+    let mut rng = Rng::new(seed);
+    let index = if total == 0 {
+        (rng.next_u64() % count as u64) as usize
+    } else {
+        let mut pick = rng.next_u64() % total;
+        let mut index = 0;
+        while pick >= weight(index) {
+            pick -= weight(index);
+            index += 1;
+        }
+        index
+    };
+
+    Ok(solutions.swap_remove(index))
+}
@@ -0,0 +1,183 @@
+//! A C interface to the solver, so native apps can ship the same engine as the website.
+//! See `include/make_ten.h` for the matching header.
+//!
+//! Panics can't unwind into C, so every function catches them and returns
+//! `MakeTenStatus::Internal` (or null or 0 if it doesn't return a status) instead.
+
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::{solve, Solution, SolverConfig};
+
+/// The status codes returned through the C interface
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MakeTenStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidInput = 2,
+    IndexOutOfRange = 3,
+    /// The solver panicked, which is a bug in the solver rather than in the caller
+    Internal = 4,
+}
+
+/// Run the body of an `extern "C"` function, returning None if it panicked. Nothing is shared
+/// between calls, so nothing can be seen in a broken state after a panic.
+fn catch_panic<T>(body: impl FnOnce() -> T) -> Option<T> {
+    catch_unwind(AssertUnwindSafe(body)).ok()
+}
+
+/// An opaque handle to the solutions of a solve, freed with `make_ten_solutions_free`
+pub struct MakeTenSolutions {
+    solutions: Vec<Solution>,
+}
+
+/// Solve the puzzle for `len` numbers at `inputs`, with the default config except for the target.
+/// Returns null and writes the reason to `status` (if it isn't null) when the solve fails.
+///
+/// # Safety
+/// `inputs` must point to `len` readable `i32`s, and `status` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn make_ten_solve(
+    inputs: *const i32,
+    len: usize,
+    target: i32,
+    status: *mut MakeTenStatus,
+) -> *mut MakeTenSolutions {
+    let set_status = |value: MakeTenStatus| {
+        if !status.is_null() {
+            *status = value;
+        }
+    };
+
+    let (solutions, value) = catch_panic(|| {
+        if inputs.is_null() {
+            return (std::ptr::null_mut(), MakeTenStatus::NullPointer);
+        }
+
+        let inputs = std::slice::from_raw_parts(inputs, len);
+        let config = SolverConfig {
+            target,
+            ..Default::default()
+        };
+
+        match solve(inputs, &config) {
+            Ok(solutions) => {
+                let solutions = Box::new(MakeTenSolutions {
+                    solutions: solutions.collect(),
+                });
+                (Box::into_raw(solutions), MakeTenStatus::Ok)
+            }
+            Err(_) => (std::ptr::null_mut(), MakeTenStatus::InvalidInput),
+        }
+    })
+    .unwrap_or((std::ptr::null_mut(), MakeTenStatus::Internal));
+
+    set_status(value);
+    solutions
+}
+
+/// Get the number of solutions in the handle
+///
+/// # Safety
+/// `solutions` must be null or a handle returned by `make_ten_solve` that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn make_ten_solutions_len(solutions: *const MakeTenSolutions) -> usize {
+    catch_panic(|| solutions.as_ref().map_or(0, |s| s.solutions.len())).unwrap_or(0)
+}
+
+/// Get the complexity of the solution at `index`, or 0 if the index is out of range
+///
+/// # Safety
+/// `solutions` must be null or a handle returned by `make_ten_solve` that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn make_ten_solution_complexity(
+    solutions: *const MakeTenSolutions,
+    index: usize,
+) -> u32 {
+    catch_panic(|| {
+        solutions
+            .as_ref()
+            .and_then(|s| s.solutions.get(index))
+            .map_or(0, |s| s.complexity)
+    })
+    .unwrap_or(0)
+}
+
+/// Get the fingerprint of the solution at `index` (see `CanonicalExpr::fingerprint`),
+/// or 0 if the index is out of range
+///
+/// # Safety
+/// `solutions` must be null or a handle returned by `make_ten_solve` that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn make_ten_solution_fingerprint(
+    solutions: *const MakeTenSolutions,
+    index: usize,
+) -> u64 {
+    catch_panic(|| {
+        solutions
+            .as_ref()
+            .and_then(|s| s.solutions.get(index))
+            .map_or(0, |s| s.fingerprint)
+    })
+    .unwrap_or(0)
+}
+
+/// Copy the text of the solution at `index` into the caller's buffer as a nul terminated string.
+/// The length of the text (without the nul) is written to `text_len` if it isn't null, so a
+/// buffer that was too small can be retried with `text_len + 1` bytes. If the buffer is too
+/// small the text is truncated, but it's always nul terminated when `buffer_len > 0`.
+///
+/// # Safety
+/// `solutions` must be null or a live handle, `buffer` must point to `buffer_len` writable
+/// bytes, and `text_len` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn make_ten_solution_text(
+    solutions: *const MakeTenSolutions,
+    index: usize,
+    buffer: *mut c_char,
+    buffer_len: usize,
+    text_len: *mut usize,
+) -> MakeTenStatus {
+    catch_panic(|| {
+        let solutions = match solutions.as_ref() {
+            Some(solutions) => solutions,
+            None => return MakeTenStatus::NullPointer,
+        };
+
+        let solution = match solutions.solutions.get(index) {
+            Some(solution) => solution,
+            None => return MakeTenStatus::IndexOutOfRange,
+        };
+
+        let text = solution.text.as_bytes();
+        if !text_len.is_null() {
+            *text_len = text.len();
+        }
+
+        if buffer_len > 0 {
+            if buffer.is_null() {
+                return MakeTenStatus::NullPointer;
+            }
+
+            let copied = text.len().min(buffer_len - 1);
+            std::ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buffer, copied);
+            *buffer.add(copied) = 0;
+        }
+
+        MakeTenStatus::Ok
+    })
+    .unwrap_or(MakeTenStatus::Internal)
+}
+
+/// Free a handle returned by `make_ten_solve`. Passing null does nothing.
+///
+/// # Safety
+/// `solutions` must be null or a handle returned by `make_ten_solve` that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn make_ten_solutions_free(solutions: *mut MakeTenSolutions) {
+    if !solutions.is_null() {
+        // If dropping panics, whatever wasn't dropped yet is leaked
+        let _ = catch_panic(|| drop(Box::from_raw(solutions)));
+    }
+}
@@ -0,0 +1,697 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::maths::{
+    canonical::canonicalize, Beam, ComplexityModel, EvaluatedExpr, Expression, OperationKind,
+    Pruning,
+};
+use crate::telemetry::RejectionReason;
+use crate::CalcError;
+
+/// The most inputs the generator supports, regardless of the configured limits. Natively, 6
+/// inputs solve in tens of milliseconds and 7 in one or two hundred, but the number of expressions
+/// explodes past this point and 8 inputs take seconds.
+pub const MAX_SUPPORTED_INPUTS: usize = 7;
+
+/// The most inputs the generator supports in beam search mode (see `SolverConfig::beam_width`),
+/// where the work grows with the square of the beam's width rather than exploding
+pub const MAX_BEAM_INPUTS: usize = 12;
+
+/// The most inputs the generator supports with `SplitStrategy::AllPartitions` (outside of beam
+/// search mode), where 6 inputs take a few hundred milliseconds natively
+pub const MAX_PARTITION_INPUTS: usize = 6;
+
+/// Check if any number appears more than once in the inputs
+fn has_repeated_inputs(inputs: &[i32]) -> bool {
+    inputs
+        .iter()
+        .enumerate()
+        .any(|(i, n)| inputs[i + 1..].contains(n))
+}
+
+/// How the inputs are divided between the two operands of each operation. Parts of the inputs
+/// are bitmasks of their indexes, e.g. `0b101` for the first and third inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SplitStrategy {
+    /// Each operand is a contiguous run of the inputs, so the numbers stay in the order that
+    /// they're given in, like in the train game
+    Contiguous,
+    /// Each operand can be any part of the inputs, so the numbers can be used in any order,
+    /// like in Countdown. This finds every solution that `Contiguous` does and more, e.g.
+    /// `(1 + 3) * (2 + 4)` for `1 2 3 4`, but there are a lot more expressions to search.
+    AllPartitions,
+}
+
+// `#[default]` on enum variants isn't stable on the pinned toolchain
+#[allow(clippy::derivable_impls)]
+impl Default for SplitStrategy {
+    fn default() -> Self {
+        SplitStrategy::Contiguous
+    }
+}
+
+impl SplitStrategy {
+    /// Every strategy
+    pub const ALL: &'static [SplitStrategy] =
+        &[SplitStrategy::Contiguous, SplitStrategy::AllPartitions];
+
+    /// The name of the strategy, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            SplitStrategy::Contiguous => "contiguous",
+            SplitStrategy::AllPartitions => "allPartitions",
+        }
+    }
+
+    /// Get the strategy with a name, the reverse of `name`
+    pub fn from_name(name: &str) -> Option<SplitStrategy> {
+        SplitStrategy::ALL
+            .iter()
+            .copied()
+            .find(|strategy| strategy.name() == name)
+    }
+
+    /// The parts of `len` inputs that operands can be made from, except for all of them.
+    /// Smaller parts come first, so each part comes after the parts that it's split into.
+    pub(crate) fn parts(self, len: usize) -> Vec<u32> {
+        let full = (1u32 << len) - 1;
+        match self {
+            SplitStrategy::Contiguous => (1..len)
+                .flat_map(|run| (0..=(len - run)).map(move |start| ((1 << run) - 1) << start))
+                .collect(),
+            SplitStrategy::AllPartitions => (1..full)
+                .sorted_by_key(|part: &u32| (part.count_ones(), *part))
+                .collect(),
+        }
+    }
+
+    /// The ways to split a part of the inputs into a left and right operand. Each pair of
+    /// operands only comes up once, as both orientations of each operation are generated
+    /// anyway, so the left operand always has the first of the part's inputs.
+    pub(crate) fn splits(self, part: u32) -> Vec<(u32, u32)> {
+        let first = part & part.wrapping_neg();
+        match self {
+            SplitStrategy::Contiguous => (1..part.count_ones())
+                .map(|len| {
+                    let left = ((1 << len) - 1) * first;
+                    (left, part ^ left)
+                })
+                .collect(),
+            SplitStrategy::AllPartitions => {
+                let rest = part ^ first;
+                (0..rest)
+                    .filter(|others| others & !rest == 0)
+                    .map(|others| (first | others, rest ^ others))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl SplitStrategy {
+    /// Whether operations whose operands have equal values are also generated the other way
+    /// around. Contiguous runs leave them out, as they can only make the same value, which
+    /// loses e.g. `7 / (4 + 3)` for `4 3 7` but not for `7 4 3`. With partitions there isn't
+    /// another order of the inputs to find them in, so they're kept. They're also kept if the
+    /// pruning keeps the stand ins, see `Pruning::stand_ins`.
+    fn reverses_equal_values(self, pruning: Pruning) -> bool {
+        self == SplitStrategy::AllPartitions || !pruning.stand_ins
+    }
+}
+
+/// The inputs in a part of them, see `SplitStrategy`
+fn part_inputs(inputs: &[i32], part: u32) -> Vec<i32> {
+    (0..inputs.len())
+        .filter(|index| part & (1 << index) != 0)
+        .map(|index| inputs[index])
+        .collect()
+}
+
+/// Check if the operation depends on the orientation of its operands.
+/// Add and multiply don't, so only one orientation is generated for them.
+pub(crate) fn is_orientation_dependent(kind: OperationKind) -> bool {
+    !matches!(kind, OperationKind::Add | OperationKind::Multiply)
+}
+
+/// Check if one operand of an operation makes the other irrelevant, returning whether it's the
+/// left operand. E.g. the `0` in `0 * x` or `x ^ 0`, or the `1` in `1 ^ x`. The canonical form
+/// only keeps the leaves of the other operand, so it's the same whatever their structure is.
+fn absorbing_operand(kind: OperationKind, left: i32, right: i32) -> Option<bool> {
+    match kind {
+        OperationKind::Multiply if left == 0 && right != 0 => Some(true),
+        OperationKind::Multiply if right == 0 && left != 0 => Some(false),
+        #[cfg(feature = "power")]
+        OperationKind::Power if right == 0 => Some(false),
+        #[cfg(feature = "power")]
+        OperationKind::Power if left == 1 => Some(true),
+        _ => None,
+    }
+}
+
+/// Narrow the expressions for a run down to the beam, see `Beam`. Expressions with the same value
+/// always combine into the same values, so keeping more than one of them would only waste the
+/// beam's width.
+fn narrow_to_beam(expressions: &mut Vec<EvaluatedExpr>, beam: Beam) {
+    expressions.sort_by_cached_key(|expr| {
+        let distance = (expr.value() as i64 - beam.target as i64).unsigned_abs();
+        (
+            distance,
+            expr.value(),
+            expr.complexity_with(ComplexityModel::V1),
+        )
+    });
+    expressions.dedup_by_key(|expr| expr.value());
+    expressions.truncate(beam.width);
+}
+
+/// An operand from one side of a split, identified by its side and its index in that side's run
+#[derive(Clone, Copy)]
+struct Operand<'a> {
+    expr: &'a EvaluatedExpr,
+    from_left: bool,
+    index: usize,
+}
+
+/// The absorbing operands that have already been used, along with the operation and whether
+/// they were the operation's left operand, see `absorbing_operand`
+type Absorbers = HashSet<(OperationKind, bool, bool, usize)>;
+
+/// Combine two expressions with every operator in both orientations (where the orientation
+/// matters, and only if the values aren't equal unless `reverse_equal` is set), passing every
+/// valid result to `output`.
+///
+/// Each absorbing operand is only combined with the first expression it meets from the other
+/// side of the split that makes a valid operation (e.g. not a negative exponent for `1 ^ x`),
+/// as combining it with the others would only build equivalent expressions.
+/// This matters a lot for inputs with zeros, which would otherwise multiply every possible
+/// expression of the rest of the run by zero.
+fn combine_pair(
+    left: Operand,
+    right: Operand,
+    operations: &[OperationKind],
+    pruning: Pruning,
+    reverse_equal: bool,
+    absorbers: &mut Absorbers,
+    output: &mut impl FnMut(EvaluatedExpr),
+) {
+    let mut combine = |kind, first: Operand, second: Operand| {
+        let (first_val, second_val) = (first.expr.value(), second.expr.value());
+        if Expression::pruned_value(first_val, second_val, kind, pruning).is_none() {
+            return;
+        }
+        if let Some(is_left) = absorbing_operand(kind, first_val, second_val) {
+            let absorber = if is_left { first } else { second };
+            if !absorbers.insert((kind, is_left, absorber.from_left, absorber.index)) {
+                return;
+            }
+        }
+
+        let expr = Expression::new_op_unpruned(first.expr.clone(), second.expr.clone(), kind);
+        if let Some(expr) = expr {
+            output(expr);
+        }
+    };
+
+    for &kind in operations {
+        combine(kind, left, right);
+
+        if is_orientation_dependent(kind)
+            && (reverse_equal || left.expr.value() != right.expr.value())
+        {
+            combine(kind, right, left);
+        }
+    }
+}
+
+/// Every expression that can be built from each part of the inputs (see `SplitStrategy`),
+/// except for all of them together which are streamed instead. The table is built bottom up
+/// (smallest parts first) so that generation doesn't need to recurse, and each part is only
+/// generated once.
+struct ExpressionTable {
+    /// The expressions for each part, indexed by the part's bitmask
+    parts: Vec<Vec<EvaluatedExpr>>,
+}
+
+impl ExpressionTable {
+    fn build(
+        inputs: &[i32],
+        operations: &[OperationKind],
+        pruning: Pruning,
+        strategy: SplitStrategy,
+    ) -> ExpressionTable {
+        let mut parts: Vec<Vec<EvaluatedExpr>> = vec![Vec::new(); 1 << inputs.len()];
+
+        for part in strategy.parts(inputs.len()) {
+            let numbers = part_inputs(inputs, part);
+
+            let expressions = if let [number] = numbers[..] {
+                vec![Expression::new_num(number)]
+            } else {
+                let mut expressions = Vec::new();
+                for (left_part, right_part) in strategy.splits(part) {
+                    let left = &parts[left_part as usize];
+                    let right = &parts[right_part as usize];
+                    let mut absorbers = Absorbers::new();
+
+                    for (right_index, right_expr) in right.iter().enumerate() {
+                        for (left_index, left_expr) in left.iter().enumerate() {
+                            let left = Operand {
+                                expr: left_expr,
+                                from_left: true,
+                                index: left_index,
+                            };
+                            let right = Operand {
+                                expr: right_expr,
+                                from_left: false,
+                                index: right_index,
+                            };
+
+                            combine_pair(
+                                left,
+                                right,
+                                operations,
+                                pruning,
+                                strategy.reverses_equal_values(pruning),
+                                &mut absorbers,
+                                &mut |e| expressions.push(e),
+                            );
+                        }
+                    }
+                }
+
+                // If the part contains repeated numbers then different splits build lots of
+                // equivalent expressions (often identical ones that only differ by which copy
+                // of a number they used), so only one from each equivalence class is kept.
+                // Equivalent expressions always build equivalent expressions when they're
+                // combined further, and solutions are rebuilt from their canonical form
+                // anyway, so the others could only ever produce duplicates. Without repeats
+                // there are too few of them to be worth canonicalizing everything for.
+                if has_repeated_inputs(&numbers) {
+                    let mut seen = HashSet::new();
+                    expressions.retain(|expr| seen.insert(canonicalize(expr)));
+                }
+
+                if let Some(beam) = pruning.beam {
+                    narrow_to_beam(&mut expressions, beam);
+                }
+
+                expressions
+            };
+
+            parts[part as usize] = expressions;
+        }
+
+        ExpressionTable { parts }
+    }
+
+    fn get(&self, part: u32) -> &[EvaluatedExpr] {
+        &self.parts[part as usize]
+    }
+}
+
+/// The values on each side of a split that can be combined with a value from the other side
+/// into the target, which are both empty if the split can't make the target at all
+fn values_reaching(
+    left: &[EvaluatedExpr],
+    right: &[EvaluatedExpr],
+    target: i32,
+    operations: &[OperationKind],
+    pruning: Pruning,
+) -> (HashSet<i32>, HashSet<i32>) {
+    let distinct = |expressions: &[EvaluatedExpr]| -> Vec<i32> {
+        expressions
+            .iter()
+            .map(|expr| expr.value())
+            .sorted()
+            .dedup()
+            .collect()
+    };
+    let (left_values, right_values) = (distinct(left), distinct(right));
+
+    let mut reaching = (HashSet::new(), HashSet::new());
+    for &left_val in &left_values {
+        for &right_val in &right_values {
+            let reaches = operations.iter().any(|&kind| {
+                Expression::pruned_value(left_val, right_val, kind, pruning) == Some(target)
+                    || Expression::pruned_value(right_val, left_val, kind, pruning) == Some(target)
+            });
+            if reaches {
+                reaching.0.insert(left_val);
+                reaching.1.insert(right_val);
+            }
+        }
+    }
+    reaching
+}
+
+/// The expressions on one side of a split, from simplest to most complex
+struct Side {
+    /// The index of each expression in the table's run
+    order: Vec<usize>,
+    /// The complexity of each expression
+    complexities: Vec<u32>,
+}
+
+impl Side {
+    /// Sort a run's expressions by complexity, leaving out the ones whose values aren't in
+    /// `values` (if there are any). Only their indexes are sorted, as moving the expressions
+    /// themselves scatters them in memory and makes freeing the table a lot slower.
+    fn new(expressions: &[EvaluatedExpr], values: Option<&HashSet<i32>>) -> Side {
+        let (complexities, order) = expressions
+            .iter()
+            .enumerate()
+            .filter(|(_, expr)| match values {
+                Some(values) => values.contains(&expr.value()),
+                None => true,
+            })
+            .map(|(index, expr)| (expr.complexity_with(ComplexityModel::V1), index))
+            .sorted()
+            .unzip();
+        Side {
+            order,
+            complexities,
+        }
+    }
+}
+
+/// The pairs of expressions that the top operation combines, as (split, left, right) positions
+/// in each side's order, which are visited in order of the total complexity of the pair. Each
+/// side is sorted by complexity, so like when merging sorted lists, only the next pair after
+/// each visited one has to be queued rather than every pair up front.
+struct Frontier {
+    /// The left side of each split, at `split - 1`
+    left: Vec<Side>,
+    /// The right side of each split, at `split - 1`
+    right: Vec<Side>,
+    /// The queued pairs, bucketed by their total complexity. Pairs are only ever queued after
+    /// a pair that's at least as simple, so the buckets below `lowest` stay empty.
+    buckets: Vec<Vec<(usize, usize, usize)>>,
+    lowest: usize,
+}
+
+impl Frontier {
+    /// Sort the table's expressions for each side of every split and queue the first pairs.
+    /// If there's a target, each side only keeps the expressions whose values can be combined
+    /// with a value from the other side into the target, so the splits and expressions that
+    /// can't be part of a solution are never paired up at all.
+    fn new(
+        table: &ExpressionTable,
+        splits: &[(u32, u32)],
+        target: Option<i32>,
+        operations: &[OperationKind],
+        pruning: Pruning,
+    ) -> Frontier {
+        let (left, right) = splits
+            .iter()
+            .map(|&(left_part, right_part)| {
+                let (left, right) = (table.get(left_part), table.get(right_part));
+                let reaching =
+                    target.map(|target| values_reaching(left, right, target, operations, pruning));
+                (
+                    Side::new(left, reaching.as_ref().map(|values| &values.0)),
+                    Side::new(right, reaching.as_ref().map(|values| &values.1)),
+                )
+            })
+            .unzip();
+
+        let mut frontier = Frontier {
+            left,
+            right,
+            buckets: Vec::new(),
+            lowest: 0,
+        };
+        frontier.reset();
+        frontier
+    }
+
+    /// Go back to the simplest pair of each split
+    fn reset(&mut self) {
+        self.buckets.clear();
+        self.lowest = 0;
+        for split in 1..=self.left.len() {
+            if !self.left[split - 1].order.is_empty() && !self.right[split - 1].order.is_empty() {
+                self.push(split, 0, 0);
+            }
+        }
+    }
+
+    fn push(&mut self, split: usize, left: usize, right: usize) {
+        let complexity = (self.left[split - 1].complexities[left]
+            + self.right[split - 1].complexities[right]) as usize;
+        if self.buckets.len() <= complexity {
+            self.buckets.resize_with(complexity + 1, Vec::new);
+        }
+        self.buckets[complexity].push((split, left, right));
+    }
+
+    /// Take the simplest queued pair, queueing the pairs after it
+    fn pop(&mut self) -> Option<(usize, usize, usize)> {
+        while self.lowest < self.buckets.len() {
+            if let Some((split, left, right)) = self.buckets[self.lowest].pop() {
+                if right + 1 < self.right[split - 1].order.len() {
+                    self.push(split, left, right + 1);
+                }
+                if right == 0 && left + 1 < self.left[split - 1].order.len() {
+                    self.push(split, left + 1, 0);
+                }
+                return Some((split, left, right));
+            }
+            self.lowest += 1;
+        }
+        None
+    }
+}
+
+/// The position of the generator within the full run of the inputs, which is enough to pick
+/// up where it left off since the rest of its state can be built again from the inputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cursor {
+    /// Which split of the inputs between the operands of the top operation it's up to,
+    /// counting from 1, which is 0 before the first pair and one past the last split once
+    /// every pair is done. With `SplitStrategy::Contiguous` it's how many of the inputs are on
+    /// the left.
+    pub split: usize,
+    /// The position of the expression on the right of the split, from simplest to most complex
+    pub right: usize,
+    /// The position of the expression on the left of the split, from simplest to most complex
+    pub left: usize,
+    /// The index of the operation in the config's operations
+    pub operation: usize,
+    /// Whether the operands are swapped
+    pub reversed: bool,
+}
+
+/// An iterator over every possible expression that uses all of the inputs.
+/// Because this is an iterator, the whole set of all possible equations
+/// isn't stored in memory at once, rather they're created on the go.
+///
+/// The top operation's operands are paired up in order of their total complexity, so the
+/// expressions come out roughly simplest first, e.g. so a UI streaming the solutions can show
+/// the best ones before the search finishes. If there's a target, operands whose values can't
+/// be combined into it aren't paired up at all.
+pub struct ExpressionIter {
+    table: ExpressionTable,
+    frontier: Frontier,
+    operations: Vec<OperationKind>,
+    /// The ways to split all of the inputs between the top operation's operands
+    splits: Vec<(u32, u32)>,
+    len: usize,
+    single: Option<EvaluatedExpr>,
+    target: Option<i32>,
+    pruning: Pruning,
+    /// See `SplitStrategy::reverses_equal_values`
+    reverse_equal: bool,
+    cursor: Cursor,
+}
+
+impl ExpressionIter {
+    /// Generate every expression for the inputs, or if there's a target then only the
+    /// expressions that evaluate to it. The full expressions are by far the most numerous, so
+    /// checking their values before building them saves most of the work of a solve. The
+    /// pruning rules should suit the puzzle, see `Pruning::for_puzzle`.
+    pub fn new(
+        inputs: &[i32],
+        operations: &[OperationKind],
+        target: Option<i32>,
+        pruning: Pruning,
+        strategy: SplitStrategy,
+    ) -> ExpressionIter {
+        let table = ExpressionTable::build(inputs, operations, pruning, strategy);
+        let splits = match inputs.len() {
+            0 | 1 => Vec::new(),
+            len => strategy.splits((1 << len) - 1),
+        };
+        ExpressionIter {
+            frontier: Frontier::new(&table, &splits, target, operations, pruning),
+            table,
+            operations: operations.to_vec(),
+            splits,
+            len: inputs.len(),
+            target,
+            pruning,
+            single: match inputs {
+                [num] => Some(Expression::new_num(*num)),
+                _ => None,
+            },
+            reverse_equal: strategy.reverses_equal_values(pruning),
+            cursor: Cursor::default(),
+        }
+    }
+
+    /// Where the generator is up to, see `seek`
+    pub fn cursor(&self) -> Cursor {
+        self.cursor
+    }
+
+    /// Continue generating from a cursor that came from `cursor`, on a generator for the same
+    /// inputs and operations. A generator for a single input always produces its expression again.
+    /// Fails with `CalcError::InvalidCursor` if the cursor can't have come from this generator,
+    /// in which case the generator starts again from the beginning.
+    pub fn seek(&mut self, cursor: Cursor) -> Result<(), CalcError> {
+        // The frontier's queue can't be saved, but it's the same after the same pairs have
+        // been taken, and taking pairs without combining them is quick
+        self.frontier.reset();
+        let found = if cursor.split == 0 || cursor.split == self.splits.len() + 1 {
+            // Before the first pair or after the last one, where there's nothing else to it
+            Cursor {
+                split: cursor.split,
+                ..Default::default()
+            } == cursor
+        } else if cursor.split <= self.splits.len()
+            && cursor.left < self.frontier.left[cursor.split - 1].order.len()
+            && cursor.right < self.frontier.right[cursor.split - 1].order.len()
+            && cursor.operation <= self.operations.len()
+            && !(cursor.reversed && cursor.operation == self.operations.len())
+        {
+            let pair = (cursor.split, cursor.left, cursor.right);
+            std::iter::from_fn(|| self.frontier.pop()).any(|next| next == pair)
+        } else {
+            false
+        };
+
+        if !found {
+            self.frontier.reset();
+            self.cursor = Cursor::default();
+            return Err(CalcError::InvalidCursor);
+        }
+        self.cursor = cursor;
+        Ok(())
+    }
+
+    /// Check if every expression has been produced
+    pub fn is_finished(&self) -> bool {
+        self.single.is_none() && (self.len < 2 || self.cursor.split > self.splits.len())
+    }
+
+    /// Same as `next`, except at most `budget` operations are tried (and taken off the
+    /// budget) before giving up, so the work can be split up. Returns None if the budget runs
+    /// out first, which can be told apart from the end with `is_finished`.
+    pub fn next_within(&mut self, budget: &mut usize) -> Option<EvaluatedExpr> {
+        self.next_observed(budget, |_, _| {})
+    }
+
+    /// Same as `next_within`, except `rejected` is called for each operation that's tried but
+    /// doesn't produce an expression, with why and its value if it missed the target (or None
+    /// if it was pruned). Only the top operation is reported, and not the ones in the table.
+    pub(crate) fn next_observed(
+        &mut self,
+        budget: &mut usize,
+        mut rejected: impl FnMut(Option<i32>, RejectionReason),
+    ) -> Option<EvaluatedExpr> {
+        if self.len < 2 {
+            return self.single.take();
+        }
+
+        // For each pair of expressions from the frontier (across every split of the inputs),
+        // and each possible operator generate a new expression
+        loop {
+            if *budget == 0 {
+                return None;
+            }
+
+            let cursor = &mut self.cursor;
+            if cursor.split > self.splits.len() {
+                return None;
+            }
+
+            if cursor.split == 0 || cursor.operation >= self.operations.len() {
+                *cursor = match self.frontier.pop() {
+                    Some((split, left, right)) => Cursor {
+                        split,
+                        left,
+                        right,
+                        ..Default::default()
+                    },
+                    None => Cursor {
+                        split: self.splits.len() + 1,
+                        ..Default::default()
+                    },
+                };
+                continue;
+            }
+
+            let (left_part, right_part) = self.splits[cursor.split - 1];
+            let (left, right) = (self.table.get(left_part), self.table.get(right_part));
+
+            let left_expr = &left[self.frontier.left[cursor.split - 1].order[cursor.left]];
+            let right_expr = &right[self.frontier.right[cursor.split - 1].order[cursor.right]];
+            let kind = self.operations[cursor.operation];
+
+            let (first, second) = if cursor.reversed {
+                (right_expr, left_expr)
+            } else {
+                (left_expr, right_expr)
+            };
+            let value = Expression::pruned_value_or_reason(
+                first.value(),
+                second.value(),
+                kind,
+                self.pruning,
+            );
+            *budget -= 1;
+            let candidate = match (value, self.target) {
+                (Ok(value), Some(target)) if value != target => {
+                    rejected(Some(value), RejectionReason::MissedTarget);
+                    None
+                }
+                (Ok(_), _) => {
+                    let expr =
+                        Expression::new_op_with(first.clone(), second.clone(), kind, self.pruning);
+                    if expr.is_none() {
+                        rejected(None, RejectionReason::Overflow);
+                    }
+                    expr
+                }
+                (Err(reason), _) => {
+                    rejected(None, reason);
+                    None
+                }
+            };
+
+            // Move on to the reverse orientation if it matters, otherwise the next operator
+            if !cursor.reversed
+                && is_orientation_dependent(kind)
+                && (self.reverse_equal || left_expr.value() != right_expr.value())
+            {
+                cursor.reversed = true;
+            } else {
+                cursor.reversed = false;
+                cursor.operation += 1;
+            }
+
+            if candidate.is_some() {
+                return candidate;
+            }
+        }
+    }
+}
+
+impl Iterator for ExpressionIter {
+    type Item = EvaluatedExpr;
+
+    fn next(&mut self) -> Option<EvaluatedExpr> {
+        let mut budget = usize::MAX;
+        self.next_within(&mut budget)
+    }
+}
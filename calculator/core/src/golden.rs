@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::{solve, CalcError, SolverConfig};
+
+/// The first line of an export, which changes if the format does
+const HEADER: &str = "make-ten solution set v1";
+
+/// Export every distinct solution for the inputs in a stable textual form, e.g. to check in
+/// the solver's output for a release and review what changed in the next one with
+/// `diff_solution_sets`. The export starts with a header line, then the `inputs`, `target`
+/// and the config's `fingerprint`, followed by a line for each solution with its fingerprint
+/// (as hex), complexity and text:
+///
+/// ```text
+/// make-ten solution set v1
+/// inputs 3 7 2 8
+/// target 10
+/// config e8d2e495afbdc0cf
+/// 4a6cd1b9183cf8dc 40 8 + 7 - 3 - 2
+/// 8e7422cdd6f1d055 90 (7 - 3) / 2 + 8
+/// ```
+///
+/// The solutions are sorted by their fingerprint rather than ranked, so the export only
+/// changes when the solutions do. `max_results` is ignored.
+pub fn export_solution_set(inputs: &[i32], config: &SolverConfig) -> Result<String, CalcError> {
+    let config = SolverConfig {
+        max_results: None,
+        ..config.clone()
+    };
+    let mut solutions: Vec<_> = solve(inputs, &config)?.collect();
+    solutions.sort_by_key(|solution| solution.fingerprint);
+
+    let numbers: Vec<_> = inputs.iter().map(|n| n.to_string()).collect();
+    let mut out = format!(
+        "{}\ninputs {}\ntarget {}\nconfig {:016x}\n",
+        HEADER,
+        numbers.join(" "),
+        config.target,
+        config.fingerprint()
+    );
+    for solution in &solutions {
+        out.push_str(&format!(
+            "{:016x} {} {}\n",
+            solution.fingerprint, solution.complexity, solution.text
+        ));
+    }
+    Ok(out)
+}
+
+/// A solution in an export
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExportedSolution {
+    complexity: u32,
+    text: String,
+}
+
+/// An export read back with `parse_export`
+struct Export<'a> {
+    inputs: &'a str,
+    target: &'a str,
+    config: &'a str,
+    /// The solutions by their fingerprint
+    solutions: BTreeMap<u64, ExportedSolution>,
+}
+
+/// Read an export written by `export_solution_set`. Errors have the span of the line that
+/// couldn't be read.
+fn parse_export(export: &str) -> Result<Export<'_>, CalcError> {
+    let mut lines = export.lines().scan(0, |start, line| {
+        let span = *start..*start + line.len();
+        *start += line.len() + 1;
+        Some((line, span))
+    });
+    let error = |message: &str, span: Range<usize>| CalcError::ParseError {
+        message: message.to_string(),
+        span,
+    };
+
+    match lines.next() {
+        Some((HEADER, _)) => {}
+        Some((_, span)) => return Err(error("Expected a solution set export", span)),
+        None => return Err(error("Expected a solution set export", 0..0)),
+    }
+    let mut field = |name: &str| match lines.next() {
+        Some((line, span)) => line
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix(' '))
+            .ok_or_else(|| error(&format!("Expected the {}", name), span)),
+        None => Err(error(
+            &format!("Expected the {}", name),
+            export.len()..export.len(),
+        )),
+    };
+    let inputs = field("inputs")?;
+    let target = field("target")?;
+    let config = field("config")?;
+
+    let mut solutions = BTreeMap::new();
+    for (line, span) in lines {
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.splitn(3, ' ');
+        let fingerprint = parts
+            .next()
+            .and_then(|fingerprint| u64::from_str_radix(fingerprint, 16).ok());
+        let complexity = parts.next().and_then(|complexity| complexity.parse().ok());
+        match (fingerprint, complexity, parts.next()) {
+            (Some(fingerprint), Some(complexity), Some(text)) => {
+                let solution = ExportedSolution {
+                    complexity,
+                    text: text.to_string(),
+                };
+                if solutions.insert(fingerprint, solution).is_some() {
+                    return Err(error("The solution is in the export twice", span));
+                }
+            }
+            _ => {
+                return Err(error(
+                    "Expected a fingerprint, complexity and solution",
+                    span,
+                ))
+            }
+        }
+    }
+
+    Ok(Export {
+        inputs,
+        target,
+        config,
+        solutions,
+    })
+}
+
+/// A solution that's in both exports but is displayed differently or has a different
+/// complexity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedSolution {
+    pub fingerprint: u64,
+    pub old_text: String,
+    pub new_text: String,
+    pub old_complexity: u32,
+    pub new_complexity: u32,
+}
+
+/// The differences between two exports from `export_solution_set`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionSetDiff {
+    /// Whether both exports are for the same inputs and target
+    pub same_puzzle: bool,
+    /// Whether both exports were made with configs that have the same fingerprint
+    pub same_config: bool,
+    /// The text of the solutions that are only in the new export, by fingerprint
+    pub added: Vec<String>,
+    /// The text of the solutions that are only in the old export, by fingerprint
+    pub removed: Vec<String>,
+    /// The solutions in both exports that changed, by fingerprint
+    pub changed: Vec<ChangedSolution>,
+    /// How many solutions are exactly the same in both exports
+    pub unchanged: usize,
+}
+
+impl SolutionSetDiff {
+    /// Whether the exports have exactly the same solutions
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The diff as text for reviewing, with a line for each solution that was removed
+    /// (`- `), added (`+ `) or changed (`~ old -> new`)
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        if !self.same_puzzle {
+            out.push_str("! the exports are for different puzzles\n");
+        }
+        if !self.same_config {
+            out.push_str("! the exports were made with different configs\n");
+        }
+        for text in &self.removed {
+            out.push_str(&format!("- {}\n", text));
+        }
+        for text in &self.added {
+            out.push_str(&format!("+ {}\n", text));
+        }
+        for changed in &self.changed {
+            out.push_str(&format!("~ {} -> {}", changed.old_text, changed.new_text));
+            if changed.old_complexity != changed.new_complexity {
+                out.push_str(&format!(
+                    " (complexity {} -> {})",
+                    changed.old_complexity, changed.new_complexity
+                ));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Compare two exports from `export_solution_set`, e.g. from the last release and the current
+/// build, matching up the solutions by their fingerprint so that a solution that's only
+/// displayed differently shows up as changed rather than as removed and added.
+/// Fails with `CalcError::ParseError` if either export can't be read, where the span is in
+/// the export that couldn't be read.
+pub fn diff_solution_sets(old: &str, new: &str) -> Result<SolutionSetDiff, CalcError> {
+    let old = parse_export(old)?;
+    let new = parse_export(new)?;
+
+    let mut diff = SolutionSetDiff {
+        same_puzzle: old.inputs == new.inputs && old.target == new.target,
+        same_config: old.config == new.config,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+    for (fingerprint, old_solution) in &old.solutions {
+        match new.solutions.get(fingerprint) {
+            None => diff.removed.push(old_solution.text.clone()),
+            Some(new_solution) if new_solution == old_solution => diff.unchanged += 1,
+            Some(new_solution) => diff.changed.push(ChangedSolution {
+                fingerprint: *fingerprint,
+                old_text: old_solution.text.clone(),
+                new_text: new_solution.text.clone(),
+                old_complexity: old_solution.complexity,
+                new_complexity: new_solution.complexity,
+            }),
+        }
+    }
+    diff.added = new
+        .solutions
+        .iter()
+        .filter(|(fingerprint, _)| !old.solutions.contains_key(fingerprint))
+        .map(|(_, solution)| solution.text.clone())
+        .collect();
+
+    Ok(diff)
+}
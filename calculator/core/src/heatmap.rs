@@ -0,0 +1,25 @@
+use crate::{is_solvable, CalcError, SolverConfig};
+
+/// Work out which puzzles starting with `prefix` are solvable, for every pair of digits that
+/// can follow it. `grid[a][b]` is whether `prefix` followed by `a` and `b` can make the
+/// config's target, e.g. for the heatmap of all the puzzles that start with `[1, 2]`.
+/// Fails with `CalcError::InvalidInput` if any of the puzzles are outside of the input limits.
+pub fn solvability_grid(
+    prefix: &[i32],
+    config: &SolverConfig,
+) -> Result<[[bool; 10]; 10], CalcError> {
+    let mut grid = [[false; 10]; 10];
+    let mut inputs = prefix.to_vec();
+    inputs.extend([0, 0]);
+    let last = inputs.len() - 1;
+
+    for (a, row) in grid.iter_mut().enumerate() {
+        for (b, solvable) in row.iter_mut().enumerate() {
+            inputs[last - 1] = a as i32;
+            inputs[last] = b as i32;
+            *solvable = is_solvable(&inputs, config)?;
+        }
+    }
+
+    Ok(grid)
+}
@@ -0,0 +1,89 @@
+use crate::distance::edit_distance;
+use crate::generate::is_orientation_dependent;
+use crate::maths::{Evaluate, EvaluatedExpr, Expression, ExpressionEquals};
+use crate::{solve, CalcError, Solution, SolverConfig};
+
+/// A suggestion for fixing a wrong answer, pointing at the part that needs to change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    /// The known solution that's closest to the answer
+    pub solution: Solution,
+    /// The edit distance between the answer and the solution
+    pub distance: usize,
+    /// The smallest part of the answer that differs from the solution
+    pub part: EvaluatedExpr,
+    /// What that part is in the solution
+    pub replacement: EvaluatedExpr,
+}
+
+/// Walk down both trees while only one side of each operation differs, to find the smallest
+/// subtrees that contain all of the differences. Operands are compared by their canonical
+/// forms, so swapped operands of an addition or multiplication still count as matching.
+fn differing_parts<'a>(
+    mut answer: &'a EvaluatedExpr,
+    mut solution: &'a EvaluatedExpr,
+) -> (&'a EvaluatedExpr, &'a EvaluatedExpr) {
+    loop {
+        let (answer_op, solution_op) = match (&**answer, &**solution) {
+            (Expression::Op(a), Expression::Op(s)) if a.kind == s.kind => (a, s),
+            _ => return (answer, solution),
+        };
+
+        let (answer_left, answer_right) = (&answer_op.left, &answer_op.right);
+        let (solution_left, solution_right) = (&solution_op.left, &solution_op.right);
+        let swappable = !is_orientation_dependent(answer_op.kind);
+
+        // If one side matches then the difference is all on the other side
+        let next = if answer_left.expr_equals(solution_left) {
+            Some((answer_right, solution_right))
+        } else if answer_right.expr_equals(solution_right) {
+            Some((answer_left, solution_left))
+        } else if swappable && answer_left.expr_equals(solution_right) {
+            Some((answer_right, solution_left))
+        } else if swappable && answer_right.expr_equals(solution_left) {
+            Some((answer_left, solution_right))
+        } else {
+            None
+        };
+
+        match next {
+            Some((next_answer, next_solution)) if !next_answer.expr_equals(next_solution) => {
+                answer = next_answer;
+                solution = next_solution;
+            }
+            _ => return (answer, solution),
+        }
+    }
+}
+
+/// Find the known solution that's closest (by tree edit distance) to a wrong answer, and the
+/// part of the answer that would need to change to match it, for "you were so close" hints.
+/// Returns None if the answer already reaches the target or there are no solutions.
+pub fn nearest_solution(
+    answer: &EvaluatedExpr,
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<Option<Hint>, CalcError> {
+    if answer.evaluate() == config.target {
+        return Ok(None);
+    }
+
+    let config = SolverConfig {
+        max_results: None,
+        ..config.clone()
+    };
+
+    let nearest = solve(inputs, &config)?
+        .map(|solution| (edit_distance(answer, &solution.expression), solution))
+        .min_by_key(|(distance, _)| *distance);
+
+    Ok(nearest.map(|(distance, solution)| {
+        let (part, replacement) = differing_parts(answer, &solution.expression);
+        Hint {
+            part: part.clone(),
+            replacement: replacement.clone(),
+            distance,
+            solution,
+        }
+    }))
+}
@@ -0,0 +1,246 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::generate::SplitStrategy;
+use crate::maths::{ComplexityModel, OperationKind};
+use crate::{solve, CalcError, InputLimits, Profile, Solution, SolverConfig, SolverMode};
+
+/// The newest version of the JSON config that `config_from_json` reads
+pub const JSON_CONFIG_VERSION: u32 = 1;
+
+/// The JSON config, where every option besides the version can be left out to keep its
+/// default. Fields that aren't known are ignored, so a newer frontend can still pass options
+/// that this build doesn't have yet.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonConfig {
+    /// Only checked, as there's only been one version so far
+    #[allow(dead_code)]
+    #[serde(deserialize_with = "version")]
+    version: u32,
+    target: Option<i32>,
+    #[serde(default, deserialize_with = "operators")]
+    operators: Option<Vec<OperationKind>>,
+    #[serde(default, deserialize_with = "mode")]
+    mode: Option<SolverMode>,
+    #[serde(default, deserialize_with = "split_strategy")]
+    split_strategy: Option<SplitStrategy>,
+    #[serde(default, deserialize_with = "complexity_model")]
+    complexity_model: Option<ComplexityModel>,
+    #[serde(default, deserialize_with = "profile")]
+    profile: Option<Profile>,
+    max_results: Option<usize>,
+    shuffle_seed: Option<u64>,
+    fewest_operations: Option<bool>,
+    prefer_balanced: Option<bool>,
+    beam_width: Option<usize>,
+    #[serde(default)]
+    filters: JsonFilters,
+    #[serde(default)]
+    limits: JsonLimits,
+}
+
+/// The options that leave out some of the solutions
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFilters {
+    kid_safe: Option<bool>,
+    kid_safe_threshold: Option<u32>,
+    max_intermediate: Option<u32>,
+    relative_intermediate_cap: Option<u32>,
+    max_depth: Option<usize>,
+    max_operator_kinds: Option<usize>,
+}
+
+/// The bounds that the inputs have to be within, see `InputLimits`
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLimits {
+    min_inputs: Option<usize>,
+    max_inputs: Option<usize>,
+    min_value: Option<i32>,
+    max_value: Option<i32>,
+}
+
+fn version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version == 0 || version > JSON_CONFIG_VERSION {
+        return Err(D::Error::custom(format!(
+            "Unsupported config version {}, the newest supported version is {}",
+            version, JSON_CONFIG_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// Read a name with the `from_name` of an option's enum
+fn by_name<'de, D: Deserializer<'de>, T>(
+    deserializer: D,
+    what: &str,
+    from_name: impl Fn(&str) -> Option<T>,
+) -> Result<Option<T>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match from_name(&name) {
+        Some(value) => Ok(Some(value)),
+        None => Err(D::Error::custom(format!("Unknown {}: {}", what, name))),
+    }
+}
+
+fn mode<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SolverMode>, D::Error> {
+    by_name(deserializer, "mode", SolverMode::from_name)
+}
+
+fn split_strategy<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SplitStrategy>, D::Error> {
+    by_name(deserializer, "split strategy", SplitStrategy::from_name)
+}
+
+fn complexity_model<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<ComplexityModel>, D::Error> {
+    by_name(deserializer, "complexity model", |name| match name {
+        "v1" => Some(ComplexityModel::V1),
+        "v2" => Some(ComplexityModel::V2),
+        _ => None,
+    })
+}
+
+fn profile<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Profile>, D::Error> {
+    by_name(deserializer, "profile", Profile::from_name)
+}
+
+/// Read the operators as a string of their symbols, e.g. `"+-*/"`
+fn operators<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<OperationKind>>, D::Error> {
+    let symbols = String::deserialize(deserializer)?;
+    symbols
+        .chars()
+        .map(|symbol| {
+            OperationKind::from_symbol(symbol).ok_or_else(|| {
+                D::Error::custom(format!("Unknown or unsupported operator: {}", symbol))
+            })
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Convert a serde_json error into a `CalcError::ParseError` with the span of the character
+/// that it happened at
+fn parse_error(json: &str, err: serde_json::Error) -> CalcError {
+    let line_start: usize = json
+        .split('\n')
+        .take(err.line().saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum();
+    let start = (line_start + err.column().saturating_sub(1)).min(json.len());
+    let end = json[start..]
+        .chars()
+        .next()
+        .map_or(start, |c| start + c.len_utf8());
+
+    // serde_json's messages end with where the error is, which the span already says
+    let message = err.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    };
+    CalcError::ParseError {
+        message,
+        span: start..end,
+    }
+}
+
+/// Read a `SolverConfig` from JSON, e.g.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "target": 24,
+///   "operators": "+-*/",
+///   "mode": "exhaustive",
+///   "filters": { "kidSafe": true, "maxDepth": 2 },
+///   "limits": { "maxInputs": 4 }
+/// }
+/// ```
+///
+/// Only `version` is required, and every other option keeps its default when it's left out.
+/// The top level options are `target`, `operators` (a string of symbols), `mode`,
+/// `splitStrategy`, `complexityModel` (`"v1"` or `"v2"`), `profile`, `maxResults`,
+/// `shuffleSeed`, `fewestOperations`, `preferBalanced` and `beamWidth`. `filters` has
+/// `kidSafe`, `kidSafeThreshold`, `maxIntermediate`, `relativeIntermediateCap`, `maxDepth`
+/// and `maxOperatorKinds`, and `limits` has the fields of `InputLimits`.
+///
+/// Unknown fields are ignored so that newer configs still work with older builds, but a
+/// `version` newer than `JSON_CONFIG_VERSION` is an error as its options may mean something
+/// else. Fails with `CalcError::ParseError` (with the span of where the JSON went wrong) if
+/// the JSON can't be read or an option isn't valid.
+pub fn config_from_json(json: &str) -> Result<SolverConfig, CalcError> {
+    let JsonConfig {
+        version: _,
+        target,
+        operators,
+        mode,
+        split_strategy,
+        complexity_model,
+        profile,
+        max_results,
+        shuffle_seed,
+        fewest_operations,
+        prefer_balanced,
+        beam_width,
+        filters,
+        limits,
+    } = serde_json::from_str(json).map_err(|err| parse_error(json, err))?;
+
+    let mut config = SolverConfig::default();
+    if let Some(target) = target {
+        config.target = target;
+    }
+    if let Some(operations) = operators {
+        config.operations = operations;
+    }
+    if let Some(mode) = mode {
+        config.mode = mode;
+    }
+    if let Some(split_strategy) = split_strategy {
+        config.split_strategy = split_strategy;
+    }
+    if let Some(complexity_model) = complexity_model {
+        config.complexity_model = complexity_model;
+    }
+    config.profile = profile;
+    config.max_results = max_results;
+    config.shuffle_seed = shuffle_seed;
+    config.fewest_operations = fewest_operations.unwrap_or(config.fewest_operations);
+    config.prefer_balanced = prefer_balanced.unwrap_or(config.prefer_balanced);
+    config.beam_width = beam_width;
+
+    config.kid_safe = filters.kid_safe.unwrap_or(config.kid_safe);
+    if let Some(threshold) = filters.kid_safe_threshold {
+        config.kid_safe_threshold = threshold;
+    }
+    config.max_intermediate = filters.max_intermediate;
+    config.relative_intermediate_cap = filters.relative_intermediate_cap;
+    config.max_depth = filters.max_depth;
+    config.max_operator_kinds = filters.max_operator_kinds;
+
+    let defaults = InputLimits::default();
+    config.limits = InputLimits {
+        min_inputs: limits.min_inputs.unwrap_or(defaults.min_inputs),
+        max_inputs: limits.max_inputs.unwrap_or(defaults.max_inputs),
+        min_value: limits.min_value.unwrap_or(defaults.min_value),
+        max_value: limits.max_value.unwrap_or(defaults.max_value),
+    };
+
+    Ok(config)
+}
+
+/// Solve the inputs with a config read from JSON by `config_from_json`, so a frontend can
+/// pass every option in one forward compatible object instead of setting them one by one.
+/// Fails if the config can't be read or the inputs are outside of its limits.
+pub fn run_with_json(inputs: &[i32], config_json: &str) -> Result<Vec<Solution>, CalcError> {
+    let config = config_from_json(config_json)?;
+    Ok(solve(inputs, &config)?.collect())
+}
@@ -0,0 +1,104 @@
+//! The make ten solver. This crate is pure rust with no wasm or js dependencies,
+//! the js bindings live in the `make-ten-wasm` crate.
+//!
+//! The main entry point is `solve`, which takes the input numbers and a `SolverConfig` and
+//! returns the distinct solutions sorted from simplest to most complex.
+
+mod audit;
+mod benchmark;
+mod capabilities;
+mod compare;
+mod csv;
+mod custom_rules;
+mod difficulty;
+mod distance;
+mod edit;
+mod equivalence;
+mod error;
+#[cfg(feature = "spoken")]
+mod explain;
+mod featured;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod generate;
+mod golden;
+mod heatmap;
+mod hint;
+#[cfg(feature = "json")]
+mod json_config;
+pub mod maths;
+mod metrics;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parse;
+#[cfg(feature = "spoken")]
+mod phrases;
+mod profile;
+mod provenance;
+mod puzzle;
+#[cfg(feature = "python")]
+mod python;
+mod reachable;
+mod regression;
+mod report;
+mod rng;
+mod self_test;
+mod shuffle;
+mod solver;
+#[cfg(feature = "spoken")]
+mod story;
+mod techniques;
+mod telemetry;
+mod validate;
+mod value_sets;
+
+pub use audit::{audit_completeness, CompletenessReport, MAX_AUDIT_INPUTS};
+pub use benchmark::{benchmark, BenchmarkPreset, BenchmarkReport, Probe, Stage, StageStats};
+pub use capabilities::{capabilities, Capabilities};
+pub use compare::{compare_configs, ConfigDiff};
+pub use csv::{export_csv, solutions_to_csv};
+pub use custom_rules::{CustomRule, TemplateRule};
+pub use difficulty::{
+    difficulty_breakdown, rate_difficulty, DifficultyBreakdown, DifficultyFactor,
+};
+pub use distance::{cluster_solutions, edit_distance};
+pub use edit::{expression_nodes, node_spans, swap_children, swap_operator};
+pub use equivalence::group_equivalent;
+pub use error::CalcError;
+#[cfg(feature = "spoken")]
+pub use explain::explain;
+pub use featured::featured_solution;
+pub use generate::{
+    Cursor, SplitStrategy, MAX_BEAM_INPUTS, MAX_PARTITION_INPUTS, MAX_SUPPORTED_INPUTS,
+};
+pub use golden::{diff_solution_sets, export_solution_set, ChangedSolution, SolutionSetDiff};
+pub use heatmap::solvability_grid;
+pub use hint::{nearest_solution, Hint};
+#[cfg(feature = "json")]
+pub use json_config::{config_from_json, run_with_json, JSON_CONFIG_VERSION};
+pub use maths::canonical::hash_canonical;
+pub use metrics::{expression_metrics, operation_usage, ExpressionMetrics};
+pub use parse::parse_expression;
+#[cfg(feature = "spoken")]
+pub use phrases::Phrases;
+pub use profile::Profile;
+pub use provenance::leaf_slots;
+pub use puzzle::generate_puzzle;
+pub use reachable::{reachable_values, ReachableValue};
+pub use regression::{run_regression_suite, RegressionDiff, RegressionReport};
+pub use report::{near_misses, unsolvable_report, NearMiss, UnsolvableReport};
+pub use self_test::{self_test, Invariant, SelfTestReport, Violation};
+pub use shuffle::{
+    normalization_trace, shuffle_with_rules, ChildPattern, Rewrite, RewriteRule, RulePattern,
+    SHUFFLE_RULES,
+};
+pub use solver::{
+    cache_key, rank_solutions, resume_lazy, solve, solve_lazy, solve_with_rejections, Solution,
+    SolutionIter, SolverConfig, SolverMode,
+};
+#[cfg(feature = "spoken")]
+pub use story::story_problem;
+pub use techniques::{techniques, Technique};
+pub use telemetry::{Candidate, CandidateOutcome, RejectionCounts, RejectionReason};
+pub use validate::{validate_inputs, InputError, InputLimits};
+pub use value_sets::{is_solvable, quick_solution};
@@ -0,0 +1,547 @@
+use super::nary::NaryExpr;
+use super::{Depth, Evaluate, EvaluatedExpr, Expression, OperationKind};
+
+/// A normalized form of an expression where every chain of additions/subtractions and every
+/// chain of multiplications/divisions is flattened into a single n-ary node with sorted terms.
+/// Two expressions that only differ by commutativity or associativity (e.g. `a + (b - c)` and
+/// `(a - c) + b`) have exactly the same canonical form, so it can be used as a dedup key.
+///
+/// Redundant operations are also folded in here: when a subtree can't affect the result
+/// (e.g. `x` in `x ^ 0`, `1 ^ x` or `0 * x`) it's replaced by a sum of its leaves, so all the
+/// ways of arranging those leaves collapse into one form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CanonicalExpr {
+    Num(i32),
+    Sum {
+        added: Vec<CanonicalExpr>,
+        subtracted: Vec<CanonicalExpr>,
+    },
+    Product {
+        multiplied: Vec<CanonicalExpr>,
+        divided: Vec<CanonicalExpr>,
+    },
+    #[cfg(feature = "power")]
+    Power(Box<CanonicalExpr>, Box<CanonicalExpr>),
+}
+
+/// Convert an expression into its canonical form
+pub fn canonicalize(expr: &Expression) -> CanonicalExpr {
+    canonicalize_nary(&NaryExpr::from_expr(expr))
+}
+
+/// Convert a flattened expression into its canonical form, which only has to sort the terms
+/// of each chain and fold in the redundant operations
+fn canonicalize_nary(expr: &NaryExpr) -> CanonicalExpr {
+    match expr {
+        NaryExpr::Num(n) => CanonicalExpr::Num(*n),
+        NaryExpr::Sum(terms) => {
+            let (mut added, mut subtracted) =
+                split_sides(terms.iter().map(|term| (&term.expr, term.negated)));
+
+            // Subtracting zero is the same as adding it
+            move_identity_terms(&mut subtracted, &mut added, 0);
+
+            added.sort();
+            subtracted.sort();
+            CanonicalExpr::Sum { added, subtracted }
+        }
+        NaryExpr::Product(factors) => {
+            let (mut multiplied, mut divided) = split_sides(
+                factors
+                    .iter()
+                    .map(|factor| (&factor.expr, factor.reciprocal)),
+            );
+
+            // Dividing by one is the same as multiplying by it
+            move_identity_terms(&mut divided, &mut multiplied, 1);
+
+            multiplied.sort();
+            divided.sort();
+
+            // Multiplying by zero makes every other factor irrelevant
+            // E.g. `0 * a * b / c` and `0 * (a + b + c)` are the same
+            if let Some(zero_index) = multiplied.iter().position(|t| t.evaluate() == 0) {
+                let zero = multiplied.remove(zero_index);
+                let absorbed = absorb(multiplied.iter().chain(divided.iter()));
+
+                let mut multiplied = vec![zero, absorbed];
+                multiplied.sort();
+                return CanonicalExpr::Product {
+                    multiplied,
+                    divided: Vec::new(),
+                };
+            }
+
+            CanonicalExpr::Product {
+                multiplied,
+                divided,
+            }
+        }
+        #[cfg(feature = "power")]
+        NaryExpr::Power(base, exponent) => {
+            let mut base = canonicalize_nary(base);
+            let mut exponent = canonicalize_nary(exponent);
+
+            // Anything to the power of zero is one, and one to the power of anything is one,
+            // so in those cases the other side is irrelevant
+            if exponent.evaluate() == 0 {
+                base = absorb(std::iter::once(&base));
+            } else if base.evaluate() == 1 {
+                exponent = absorb(std::iter::once(&exponent));
+            }
+
+            CanonicalExpr::Power(Box::new(base), Box::new(exponent))
+        }
+    }
+}
+
+/// Canonicalize the terms of a chain, split into the forward (+, *) and inverse (-, /) sides
+fn split_sides<'a>(
+    terms: impl Iterator<Item = (&'a NaryExpr, bool)>,
+) -> (Vec<CanonicalExpr>, Vec<CanonicalExpr>) {
+    let (mut forward, mut inverse) = (Vec::new(), Vec::new());
+    for (term, is_inverse) in terms {
+        if is_inverse {
+            inverse.push(canonicalize_nary(term));
+        } else {
+            forward.push(canonicalize_nary(term));
+        }
+    }
+    (forward, inverse)
+}
+
+/// Hash an expression by its canonical form, so every equivalent expression has the same hash,
+/// e.g. as a key for caches in js or for deduplicating answers on a server. This is the same
+/// as the `fingerprint` of a solution, see `CanonicalExpr::fingerprint`.
+pub fn hash_canonical(expr: &Expression) -> u64 {
+    canonicalize(expr).fingerprint()
+}
+
+/// Collapse terms whose structure doesn't affect the result into a sorted sum of their leaves
+fn absorb<'a>(terms: impl Iterator<Item = &'a CanonicalExpr>) -> CanonicalExpr {
+    let mut leaves = Vec::new();
+    for term in terms {
+        term.collect_leaves(&mut leaves);
+    }
+    leaves.sort_unstable();
+
+    if leaves.len() == 1 {
+        CanonicalExpr::Num(leaves[0])
+    } else {
+        CanonicalExpr::Sum {
+            added: leaves.into_iter().map(CanonicalExpr::Num).collect(),
+            subtracted: Vec::new(),
+        }
+    }
+}
+
+/// Move terms that evaluate to the identity value from the inverse side to the forward side,
+/// as e.g. `a - 0` and `a + 0` are equivalent
+fn move_identity_terms(
+    inverse: &mut Vec<CanonicalExpr>,
+    forward: &mut Vec<CanonicalExpr>,
+    identity: i32,
+) {
+    let (identities, rest) = inverse
+        .drain(..)
+        .partition::<Vec<_>, _>(|term| term.evaluate() == identity);
+    *inverse = rest;
+    forward.extend(identities);
+}
+
+/// The terms of a chain are combined with wider integers, since e.g. every multiplied term of
+/// `a * b * c / d / e` comes before the divisions even though the expression that the chain came
+/// from never went through `a * b * c`. They can still overflow, as `max_value` can be raised
+/// and beam mode takes more inputs, in which case they wrap around like `Operation::evaluate`
+/// does.
+impl Evaluate for CanonicalExpr {
+    fn evaluate(&self) -> i32 {
+        match self {
+            CanonicalExpr::Num(n) => *n,
+            CanonicalExpr::Sum { added, subtracted } => {
+                let added: i64 = added.iter().map(|t| t.evaluate() as i64).sum();
+                let subtracted: i64 = subtracted.iter().map(|t| t.evaluate() as i64).sum();
+                (added - subtracted) as i32
+            }
+            CanonicalExpr::Product {
+                multiplied,
+                divided,
+            } => {
+                let product = |terms: &[CanonicalExpr]| {
+                    terms.iter().fold(1i128, |product, t| {
+                        product.wrapping_mul(t.evaluate() as i128)
+                    })
+                };
+                product(multiplied).wrapping_div(product(divided)) as i32
+            }
+            #[cfg(feature = "power")]
+            CanonicalExpr::Power(base, exponent) => {
+                // The exponent of one is absorbed into the sum of its leaves (see `absorb`),
+                // which can be negative, e.g. `1 ^ (0 - -4)`
+                let base = base.evaluate();
+                if base == 1 {
+                    return 1;
+                }
+                // A wrapped exponent can come out negative, and a power of wrapped values can
+                // overflow. Either way it's far too big to be the 0 or 1 that chains compare
+                // their terms against, so it saturates instead.
+                u32::try_from(exponent.evaluate())
+                    .ok()
+                    .and_then(|exponent| base.checked_pow(exponent))
+                    .unwrap_or(i32::MAX)
+            }
+        }
+    }
+}
+
+/// A chain counts as a single level however many terms it has, so `a + b + c + d` has a depth
+/// of 2 while `(a + b) * (c + d)` has a depth of 3
+impl Depth for CanonicalExpr {
+    fn depth(&self) -> usize {
+        match self {
+            CanonicalExpr::Num(_) => 1,
+            CanonicalExpr::Sum {
+                added: forward,
+                subtracted: inverse,
+            }
+            | CanonicalExpr::Product {
+                multiplied: forward,
+                divided: inverse,
+            } => {
+                let deepest = forward
+                    .iter()
+                    .chain(inverse.iter())
+                    .map(|t| t.depth())
+                    .max();
+                deepest.unwrap_or(0) + 1
+            }
+            #[cfg(feature = "power")]
+            CanonicalExpr::Power(base, exponent) => base.depth().max(exponent.depth()) + 1,
+        }
+    }
+}
+
+/// The FNV-1a offset basis and prime, used by `CanonicalExpr::fingerprint`
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+pub(crate) fn fnv_write(hash: &mut u64, bytes: &[u8]) {
+    for &byte in bytes {
+        *hash ^= byte as u64;
+        *hash = hash.wrapping_mul(FNV_PRIME);
+    }
+}
+
+impl CanonicalExpr {
+    /// A 64-bit hash of the canonical form, so equivalent solutions share a fingerprint.
+    /// Unlike `Hash`, this is FNV-1a over a fixed encoding, so it stays the same between
+    /// builds, platforms and versions and can be stored or sent elsewhere. Changing the
+    /// encoding or the canonical form in a way that changes fingerprints is a breaking change,
+    /// so it only happens between major versions.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        self.write_fingerprint(&mut hash);
+        hash
+    }
+
+    /// Write a tag byte for the variant, then its contents, with every list prefixed by
+    /// its length so that different trees can't produce the same bytes
+    fn write_fingerprint(&self, hash: &mut u64) {
+        let write_terms = |hash: &mut u64, terms: &[CanonicalExpr]| {
+            fnv_write(hash, &(terms.len() as u32).to_le_bytes());
+            for term in terms {
+                term.write_fingerprint(hash);
+            }
+        };
+
+        match self {
+            CanonicalExpr::Num(n) => {
+                fnv_write(hash, &[0]);
+                fnv_write(hash, &n.to_le_bytes());
+            }
+            CanonicalExpr::Sum { added, subtracted } => {
+                fnv_write(hash, &[1]);
+                write_terms(hash, added);
+                write_terms(hash, subtracted);
+            }
+            CanonicalExpr::Product {
+                multiplied,
+                divided,
+            } => {
+                fnv_write(hash, &[2]);
+                write_terms(hash, multiplied);
+                write_terms(hash, divided);
+            }
+            #[cfg(feature = "power")]
+            CanonicalExpr::Power(base, exponent) => {
+                fnv_write(hash, &[3]);
+                base.write_fingerprint(hash);
+                exponent.write_fingerprint(hash);
+            }
+        }
+    }
+
+    /// How many different operations the canonical form uses, e.g. 2 for `1 + 2 * 3 + 4`. This
+    /// is the same for every expression in the equivalence class, but it can be different to
+    /// the expression that was canonicalized, e.g. 2 for `0 * 3 * 4` as the factors after
+    /// the zero are absorbed into `0 * (3 + 4)`.
+    pub fn distinct_operators(&self) -> usize {
+        let mut kinds = Vec::new();
+        self.collect_operators(&mut kinds);
+        kinds.len()
+    }
+
+    fn collect_operators(&self, kinds: &mut Vec<OperationKind>) {
+        let mut add = |kind: OperationKind| {
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        };
+        let terms = match self {
+            CanonicalExpr::Num(_) => return,
+            CanonicalExpr::Sum { added, subtracted } => {
+                if added.len() > 1 {
+                    add(OperationKind::Add);
+                }
+                if !subtracted.is_empty() {
+                    add(OperationKind::Subtract);
+                }
+                added.iter().chain(subtracted.iter())
+            }
+            CanonicalExpr::Product {
+                multiplied,
+                divided,
+            } => {
+                if multiplied.len() > 1 {
+                    add(OperationKind::Multiply);
+                }
+                if !divided.is_empty() {
+                    add(OperationKind::Divide);
+                }
+                multiplied.iter().chain(divided.iter())
+            }
+            #[cfg(feature = "power")]
+            CanonicalExpr::Power(base, exponent) => {
+                add(OperationKind::Power);
+                base.collect_operators(kinds);
+                exponent.collect_operators(kinds);
+                return;
+            }
+        };
+        for term in terms {
+            term.collect_operators(kinds);
+        }
+    }
+
+    /// Recursively collect the numbers at the leaves of the expression
+    pub fn collect_leaves(&self, leaves: &mut Vec<i32>) {
+        match self {
+            CanonicalExpr::Num(n) => leaves.push(*n),
+            CanonicalExpr::Sum {
+                added: forward,
+                subtracted: inverse,
+            }
+            | CanonicalExpr::Product {
+                multiplied: forward,
+                divided: inverse,
+            } => {
+                for term in forward.iter().chain(inverse.iter()) {
+                    term.collect_leaves(leaves);
+                }
+            }
+            #[cfg(feature = "power")]
+            CanonicalExpr::Power(base, exponent) => {
+                base.collect_leaves(leaves);
+                exponent.collect_leaves(leaves);
+            }
+        }
+    }
+
+    /// Rebuild a binary expression tree from the canonical form. The forward terms are
+    /// combined first and then the inverse terms are applied, e.g. `a + b + c - d - e`.
+    /// Returns None if rebuilding would overflow.
+    pub fn to_expr(&self) -> Option<EvaluatedExpr> {
+        match self {
+            CanonicalExpr::Num(n) => Some(Expression::new_num(*n)),
+            CanonicalExpr::Sum { added, subtracted } => rebuild_chain(
+                added,
+                subtracted,
+                OperationKind::Add,
+                OperationKind::Subtract,
+            ),
+            CanonicalExpr::Product {
+                multiplied,
+                divided,
+            } => rebuild_chain(
+                multiplied,
+                divided,
+                OperationKind::Multiply,
+                OperationKind::Divide,
+            ),
+            #[cfg(feature = "power")]
+            CanonicalExpr::Power(base, exponent) => Expression::new_op_unpruned(
+                base.to_expr()?,
+                exponent.to_expr()?,
+                OperationKind::Power,
+            ),
+        }
+    }
+}
+
+fn rebuild_chain(
+    forward: &[CanonicalExpr],
+    inverse: &[CanonicalExpr],
+    forward_kind: OperationKind,
+    inverse_kind: OperationKind,
+) -> Option<EvaluatedExpr> {
+    // The leftmost leaf of a chain is always on the forward side, so this is never empty
+    let (first, rest) = forward.split_first()?;
+
+    let mut expr = first.to_expr()?;
+    for term in rest {
+        expr = Expression::new_op_unpruned(expr, term.to_expr()?, forward_kind)?;
+    }
+    for term in inverse {
+        expr = Expression::new_op_unpruned(expr, term.to_expr()?, inverse_kind)?;
+    }
+
+    Some(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ExpressionEquals, OperationKind};
+    use super::{canonicalize, EvaluatedExpr, Expression};
+
+    fn num(n: i32) -> EvaluatedExpr {
+        Expression::new_num(n)
+    }
+
+    fn op(left: EvaluatedExpr, kind: OperationKind, right: EvaluatedExpr) -> EvaluatedExpr {
+        Expression::new_op_unpruned(left, right, kind).unwrap()
+    }
+
+    fn add(left: EvaluatedExpr, right: EvaluatedExpr) -> EvaluatedExpr {
+        op(left, OperationKind::Add, right)
+    }
+
+    fn sub(left: EvaluatedExpr, right: EvaluatedExpr) -> EvaluatedExpr {
+        op(left, OperationKind::Subtract, right)
+    }
+
+    fn mul(left: EvaluatedExpr, right: EvaluatedExpr) -> EvaluatedExpr {
+        op(left, OperationKind::Multiply, right)
+    }
+
+    fn div(left: EvaluatedExpr, right: EvaluatedExpr) -> EvaluatedExpr {
+        op(left, OperationKind::Divide, right)
+    }
+
+    #[cfg(feature = "power")]
+    fn pow(left: EvaluatedExpr, right: EvaluatedExpr) -> EvaluatedExpr {
+        op(left, OperationKind::Power, right)
+    }
+
+    /// Check that `expr_equals` is reflexive, symmetric and transitive over the expressions
+    fn assert_equivalence(exprs: &[EvaluatedExpr]) {
+        for a in exprs {
+            assert!(a.expr_equals(a), "{} isn't equal to itself", a.to_text());
+            for b in exprs {
+                assert_eq!(
+                    a.expr_equals(b),
+                    b.expr_equals(a),
+                    "{} and {} aren't symmetric",
+                    a.to_text(),
+                    b.to_text()
+                );
+                for c in exprs {
+                    if a.expr_equals(b) && b.expr_equals(c) {
+                        assert!(
+                            a.expr_equals(c),
+                            "{} = {} = {} isn't transitive",
+                            a.to_text(),
+                            b.to_text(),
+                            c.to_text()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn equivalence_relation() {
+        assert_equivalence(&[
+            add(num(7), sub(num(5), num(2))),
+            add(sub(num(7), num(2)), num(5)),
+            sub(add(num(5), num(7)), num(2)),
+            sub(add(num(7), num(5)), num(2)),
+            div(mul(num(8), num(2)), num(2)),
+            mul(div(num(8), num(2)), num(2)),
+            div(mul(num(2), num(8)), num(2)),
+            sub(num(8), num(0)),
+            add(num(8), num(0)),
+            div(num(8), num(1)),
+            mul(num(8), num(1)),
+            mul(num(0), add(num(3), num(4))),
+            mul(mul(num(0), num(3)), num(4)),
+            mul(mul(num(4), num(0)), num(3)),
+            mul(num(0), sub(num(3), num(4))),
+            mul(add(num(3), num(4)), num(0)),
+            mul(num(0), add(num(3), num(5))),
+            mul(num(0), num(7)),
+            div(num(0), num(7)),
+        ]);
+    }
+
+    #[cfg(feature = "power")]
+    #[test]
+    fn equivalence_relation_with_powers() {
+        assert_equivalence(&[
+            pow(mul(num(3), num(2)), num(0)),
+            pow(add(num(2), num(3)), num(0)),
+            pow(mul(num(2), num(3)), num(0)),
+            pow(sub(num(5), num(1)), num(0)),
+            pow(num(2), num(0)),
+            pow(num(1), mul(num(3), num(2))),
+            pow(num(1), add(num(2), num(3))),
+            pow(num(1), num(5)),
+            mul(num(0), pow(num(2), num(3))),
+            mul(num(0), add(num(2), num(3))),
+            mul(pow(num(2), num(3)), num(0)),
+        ]);
+    }
+
+    #[test]
+    fn distinct_operators() {
+        let count = |expr: EvaluatedExpr| canonicalize(&expr).distinct_operators();
+        assert_eq!(count(num(7)), 0);
+        assert_eq!(count(mul(mul(mul(num(2), num(5)), num(1)), num(1))), 1);
+        assert_eq!(count(sub(add(mul(num(2), num(5)), num(1)), num(1))), 3);
+        assert_eq!(count(sub(num(8), num(0))), 1);
+        assert_eq!(count(mul(mul(num(0), num(3)), num(4))), 2);
+    }
+
+    #[test]
+    fn multiplying_by_zero_absorbs_the_other_factors() {
+        let zero_times_sum = mul(num(0), add(num(3), num(4)));
+        let zero_times_product = mul(mul(num(0), num(3)), num(4));
+        assert!(zero_times_sum.expr_equals(&zero_times_product));
+        assert!(zero_times_product.expr_equals(&mul(mul(num(4), num(0)), num(3))));
+        assert!(zero_times_product.expr_equals(&mul(num(0), sub(num(3), num(4)))));
+        assert!(!zero_times_sum.expr_equals(&mul(num(0), add(num(3), num(5)))));
+        assert!(!zero_times_sum.expr_equals(&mul(num(0), num(7))));
+    }
+
+    #[cfg(feature = "power")]
+    #[test]
+    fn zero_exponents_absorb_the_base() {
+        let six_to_zero = pow(mul(num(3), num(2)), num(0));
+        let four_to_zero = pow(sub(num(5), num(1)), num(0));
+        let two_to_zero = pow(num(2), num(0));
+        assert!(six_to_zero.expr_equals(&pow(add(num(2), num(3)), num(0))));
+        assert!(!six_to_zero.expr_equals(&four_to_zero));
+        assert!(!four_to_zero.expr_equals(&two_to_zero));
+        assert!(!six_to_zero.expr_equals(&two_to_zero));
+    }
+}
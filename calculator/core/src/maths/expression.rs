@@ -0,0 +1,681 @@
+use std::cmp::Ordering;
+
+use super::canonical::canonicalize;
+use super::operation::{
+    checked_apply, needs_parenthesis, operation_complexity, Operation, OperationKind,
+};
+#[cfg(feature = "spoken")]
+use super::words::spoken_number;
+use super::*;
+#[cfg(feature = "spoken")]
+use crate::phrases::Phrases;
+use crate::telemetry::RejectionReason;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Expression {
+    Op(Box<Operation>),
+    Num(i32),
+}
+
+/// Deserializing checks that the value matches the expression, see the `Deserialize` impl
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EvaluatedExpr {
+    value: i32,
+    /// The complexity with `ComplexityModel::V1`, which is cached like the value so that
+    /// sorting expressions by complexity doesn't walk each of them for every comparison. It
+    /// fits in the padding after the value, so it doesn't make the generator's tables bigger.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    complexity: u32,
+    /// The depth (see `Depth`), which is cached the same way so that the depth of an operation
+    /// only looks at its operands rather than walking the whole tree under it. Unlike the
+    /// complexity it doesn't fit in the padding, so it makes each expression 8 bytes bigger.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    depth: u32,
+    expression: Expression,
+}
+
+/// How much complexity each digit of an intermediate value past the second adds in
+/// `ComplexityModel::V2`, e.g. a single intermediate of 512 adds as much as a pair of
+/// parenthesis
+const INTERMEDIATE_PENALTY: u32 = 10;
+
+/// How much complexity an intermediate value adds in `ComplexityModel::V2`
+fn intermediate_penalty(value: i32) -> u32 {
+    let mut digits: u32 = 1;
+    let mut rest = value.unsigned_abs() / 10;
+    while rest > 0 {
+        digits += 1;
+        rest /= 10;
+    }
+    INTERMEDIATE_PENALTY * digits.saturating_sub(2)
+}
+
+/// Which of the optional pruning rules in `Expression::new_op` apply. The rules only skip
+/// expressions that another expression with the same numbers can stand in for, and none of
+/// them depend on the size of the target. Pruning negative results does depend on the signs
+/// of the puzzle though, see `Pruning::for_puzzle`. Some of the expressions that are stood in
+/// for have a different canonical form though, which `stand_ins` (and `negative_results`)
+/// can be turned off for, see `SolverMode::Exhaustive`. The exceptions are the caps
+/// (`max_intermediate` and `max_exponent`), which do lose solutions, as they're for puzzles
+/// that should stay within small numbers, and the `beam`, which is for puzzles too big to search
+/// exhaustively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pruning {
+    /// Skip subtractions with negative results, as the reversed subtraction can stand in for
+    /// them when the inputs and the target aren't negative
+    pub negative_results: bool,
+    /// Skip `x ^ 1` (as `x * 1` stands in for it), and operations whose operands have equal
+    /// values in the other orientation (as they make the same value), even though they have
+    /// a different canonical form
+    pub stand_ins: bool,
+    /// Skip operations with a value bigger than this (either way from zero), see
+    /// `Profile::max_intermediate`
+    pub max_intermediate: Option<u32>,
+    /// Skip powers with an exponent bigger than this, see `SolverConfig::kid_safe`
+    pub max_exponent: Option<u32>,
+    /// Only keep the most promising expressions for each part of the inputs, see
+    /// `SolverConfig::beam_width`
+    pub beam: Option<Beam>,
+}
+
+/// The expressions that the generator keeps for each contiguous run of the inputs in beam search
+/// mode: one expression (the simplest) for each of the `width` values closest to the `target`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Beam {
+    pub width: usize,
+    pub target: i32,
+}
+
+impl Pruning {
+    /// The pruning rules that can't lose any solutions for the inputs and target (if there is
+    /// one). Negative numbers need negative intermediate values, e.g. `(1 - 3) * -5` for 10.
+    pub fn for_puzzle(inputs: &[i32], target: Option<i32>) -> Pruning {
+        Pruning {
+            negative_results: inputs.iter().all(|&n| n >= 0) && !matches!(target, Some(t) if t < 0),
+            stand_ins: true,
+            max_intermediate: None,
+            max_exponent: None,
+            beam: None,
+        }
+    }
+
+    /// Check if every value and exponent in an expression is within the caps, e.g. for an
+    /// equivalent expression that wasn't built with this pruning
+    pub fn within_caps(&self, expr: &EvaluatedExpr) -> bool {
+        if matches!(self.max_intermediate, Some(max) if expr.value.unsigned_abs() > max) {
+            return false;
+        }
+
+        match &expr.expression {
+            Expression::Num(_) => true,
+            Expression::Op(op) => {
+                #[cfg(feature = "power")]
+                if op.kind == OperationKind::Power
+                    && matches!(self.max_exponent, Some(max) if op.right.value as i64 > max as i64)
+                {
+                    return false;
+                }
+
+                self.within_caps(&op.left) && self.within_caps(&op.right)
+            }
+        }
+    }
+}
+
+impl Default for Pruning {
+    fn default() -> Self {
+        Pruning {
+            negative_results: true,
+            stand_ins: true,
+            max_intermediate: None,
+            max_exponent: None,
+            beam: None,
+        }
+    }
+}
+
+/// What the complexity of the expressions built on top of an expression depends on, so the
+/// complexity of an operation can be worked out from its operands without building it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ComplexityParts {
+    /// The complexity with `ComplexityModel::V1`
+    v1: u32,
+    /// What `ComplexityModel::V2` adds for the intermediate values
+    penalty: u32,
+    /// The top operation, or None for a number
+    kind: Option<OperationKind>,
+    value: i32,
+}
+
+impl ComplexityParts {
+    pub fn num(value: i32) -> ComplexityParts {
+        ComplexityParts {
+            v1: Expression::Num(value).get_complexity(),
+            penalty: 0,
+            kind: None,
+            value,
+        }
+    }
+
+    /// The parts of an operation on expressions with these parts, which makes `value`
+    pub fn op(
+        left: ComplexityParts,
+        right: ComplexityParts,
+        kind: OperationKind,
+        value: i32,
+    ) -> ComplexityParts {
+        let internal = |parts: ComplexityParts, is_left| match parts.kind {
+            Some(child) if needs_parenthesis(child, kind, is_left) => parts.v1 + 10,
+            _ => parts.v1,
+        };
+        let penalty = |parts: ComplexityParts| match parts.kind {
+            Some(_) => parts.penalty + intermediate_penalty(parts.value),
+            None => 0,
+        };
+
+        ComplexityParts {
+            v1: operation_complexity(kind, internal(left, true), internal(right, false)),
+            penalty: penalty(left) + penalty(right),
+            kind: Some(kind),
+            value,
+        }
+    }
+
+    /// The complexity with a model, the same as `EvaluatedExpr::complexity_with`
+    pub fn complexity(&self, model: ComplexityModel) -> u32 {
+        match model {
+            ComplexityModel::V1 => self.v1,
+            ComplexityModel::V2 => self.v1 + self.penalty,
+        }
+    }
+}
+
+/// Check if a number needs parenthesis around it because of its minus sign. Negative numbers
+/// on the right of an operator would read as two operators in a row (e.g. `5 - (-3)`), and
+/// a negative base would read as the negative of a power (e.g. `(-3) ^ 2`).
+fn needs_sign_parenthesis(num: i32, parent_op: OperationKind, is_left: bool) -> bool {
+    num < 0 && (!is_left || is_power(parent_op))
+}
+
+#[cfg(feature = "power")]
+fn is_power(kind: OperationKind) -> bool {
+    kind == OperationKind::Power
+}
+
+#[cfg(not(feature = "power"))]
+fn is_power(_kind: OperationKind) -> bool {
+    false
+}
+
+impl Expression {
+    pub fn to_text(&self) -> String {
+        match self {
+            Expression::Op(op) => op.to_text(),
+            Expression::Num(num) => num.to_string(),
+        }
+    }
+
+    pub fn to_text_child(&self, parent_op: OperationKind, is_left: bool) -> String {
+        match self {
+            Expression::Op(op) => op.to_text_child(parent_op, is_left),
+            Expression::Num(num) if needs_sign_parenthesis(*num, parent_op, is_left) => {
+                format!("({})", num)
+            }
+            Expression::Num(num) => num.to_string(),
+        }
+    }
+
+    /// Converts the expression into english words for screen readers, e.g.
+    /// "open bracket three plus seven close bracket times one"
+    #[cfg(feature = "spoken")]
+    pub fn to_spoken_text(&self) -> String {
+        self.to_spoken_text_with(&Phrases::default())
+    }
+
+    /// Converts the expression into words for screen readers, using the words in `phrases`
+    #[cfg(feature = "spoken")]
+    pub fn to_spoken_text_with(&self, phrases: &Phrases) -> String {
+        match self {
+            Expression::Op(op) => op.to_spoken_text(phrases),
+            Expression::Num(num) => spoken_number(*num, phrases),
+        }
+    }
+
+    #[cfg(feature = "spoken")]
+    pub fn to_spoken_text_child(
+        &self,
+        phrases: &Phrases,
+        parent_op: OperationKind,
+        is_left: bool,
+    ) -> String {
+        match self {
+            Expression::Op(op) => op.to_spoken_text_child(phrases, parent_op, is_left),
+            Expression::Num(num) if needs_sign_parenthesis(*num, parent_op, is_left) => format!(
+                "{} {} {}",
+                phrases.open_bracket,
+                spoken_number(*num, phrases),
+                phrases.close_bracket
+            ),
+            Expression::Num(num) => spoken_number(*num, phrases),
+        }
+    }
+
+    /// Get the numbers at the leaves of the expression, from left to right
+    pub fn leaves(&self) -> Vec<i32> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    /// The complexity of the expression with the given model. For `ComplexityModel::V2`,
+    /// every intermediate value (the value of an operation inside another operation) with
+    /// three or more digits adds `INTERMEDIATE_PENALTY` for each digit past the second.
+    pub fn complexity_with(&self, model: ComplexityModel) -> u32 {
+        let mut complexity = self.get_complexity();
+        if model == ComplexityModel::V1 {
+            return complexity;
+        }
+
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if let Expression::Op(op) = node {
+                for child in [&op.left, &op.right] {
+                    if let Expression::Op(_) = &**child {
+                        complexity += intermediate_penalty(child.value());
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        complexity
+    }
+
+    /// How far the tree is from balanced, see `Operation::imbalance`
+    pub fn imbalance(&self) -> usize {
+        match self {
+            Expression::Num(_) => 0,
+            Expression::Op(op) => op.imbalance(),
+        }
+    }
+
+    /// Count the operations in the expression
+    pub fn operation_count(&self) -> usize {
+        match self {
+            Expression::Num(_) => 0,
+            Expression::Op(op) => 1 + op.left.operation_count() + op.right.operation_count(),
+        }
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<i32>) {
+        match self {
+            Expression::Num(num) => leaves.push(*num),
+            Expression::Op(op) => {
+                op.left.collect_leaves(leaves);
+                op.right.collect_leaves(leaves);
+            }
+        }
+    }
+
+    /// Create a new expression from a number
+    pub fn new_num(num: i32) -> EvaluatedExpr {
+        EvaluatedExpr::new(Expression::Num(num))
+    }
+
+    /// Create a new expression from an operation, or None if it's pruned with the default
+    /// pruning rules (see `new_op_with`)
+    pub fn new_op(
+        left: EvaluatedExpr,
+        right: EvaluatedExpr,
+        kind: OperationKind,
+    ) -> Option<EvaluatedExpr> {
+        Expression::new_op_with(left, right, kind, Pruning::default())
+    }
+
+    /// Create a new expression from an operation, or None if it's pruned. Expressions are
+    /// pruned if they can't be computed, or if a simpler expression with the same numbers
+    /// always has the same value (e.g. `a * 1` instead of `a / 1`).
+    pub fn new_op_with(
+        left: EvaluatedExpr,
+        right: EvaluatedExpr,
+        kind: OperationKind,
+        pruning: Pruning,
+    ) -> Option<EvaluatedExpr> {
+        Expression::pruned_value(left.value, right.value, kind, pruning)?;
+        Expression::new_op_unpruned(left, right, kind)
+    }
+
+    /// Get the value that `new_op_with` would give an operation on these values, without
+    /// building the expression. This is None if `new_op_with` would be None.
+    pub fn pruned_value(
+        left_val: i32,
+        right_val: i32,
+        kind: OperationKind,
+        pruning: Pruning,
+    ) -> Option<i32> {
+        Expression::pruned_value_or_reason(left_val, right_val, kind, pruning).ok()
+    }
+
+    /// Same as `pruned_value`, except with why the operation was pruned
+    pub(crate) fn pruned_value_or_reason(
+        left_val: i32,
+        right_val: i32,
+        kind: OperationKind,
+        pruning: Pruning,
+    ) -> Result<i32, RejectionReason> {
+        match kind {
+            OperationKind::Divide => {
+                if left_val.checked_rem(right_val) != Some(0) {
+                    return Err(RejectionReason::NonExactDivision);
+                }
+
+                // Only leave multiply by zero instead
+                if left_val == 0 {
+                    return Err(RejectionReason::StandIn);
+                }
+
+                // Only leave multiply by one instead
+                if right_val == 1 {
+                    return Err(RejectionReason::StandIn);
+                }
+            }
+            OperationKind::Subtract => {
+                if pruning.negative_results && left_val < right_val {
+                    return Err(RejectionReason::NegativeSubtraction);
+                }
+
+                // Only leave add zero instead
+                if right_val == 0 {
+                    return Err(RejectionReason::StandIn);
+                }
+            }
+            #[cfg(feature = "power")]
+            OperationKind::Power => {
+                if right_val < 0 {
+                    return Err(RejectionReason::NegativeExponent);
+                }
+
+                if matches!(pruning.max_exponent, Some(max) if right_val as i64 > max as i64) {
+                    return Err(RejectionReason::ExponentCap);
+                }
+
+                // Only leave multiply by one instead
+                if pruning.stand_ins && right_val == 1 {
+                    return Err(RejectionReason::StandIn);
+                }
+            }
+            _ => {}
+        }
+
+        let value = checked_apply(kind, left_val, right_val).ok_or(RejectionReason::Overflow)?;
+        match pruning.max_intermediate {
+            Some(max) if value.unsigned_abs() > max => Err(RejectionReason::IntermediateCap),
+            _ => Ok(value),
+        }
+    }
+
+    /// Create a new expression from an operation without applying any of the pruning rules
+    /// in `new_op`. This is only None if the result overflows or can't be computed.
+    pub fn new_op_unpruned(
+        left: EvaluatedExpr,
+        right: EvaluatedExpr,
+        kind: OperationKind,
+    ) -> Option<EvaluatedExpr> {
+        // If the result is overflowing, then ignore
+        let value = checked_apply(kind, left.value, right.value)?;
+
+        // The value comes from the operands' values, rather than evaluating the whole expression
+        let expression = Expression::Op(Box::new(Operation { left, right, kind }));
+        Some(EvaluatedExpr {
+            value,
+            complexity: expression.get_complexity(),
+            depth: expression.depth() as u32,
+            expression,
+        })
+    }
+}
+
+/// Expressions are totally ordered so that they can be shuffled and sorted deterministically.
+/// Numbers always come before operations and are ordered by value. Operations are ordered by
+/// their kind (see the `Ord` implementation of `OperationKind`), then by their left operand, then by
+/// their right operand. Two expressions that compare equal are structurally identical.
+impl Ord for Expression {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Expression::Num(n1), Expression::Num(n2)) => n1.cmp(n2),
+            (Expression::Num(_), Expression::Op(_)) => Ordering::Less,
+            (Expression::Op(_), Expression::Num(_)) => Ordering::Greater,
+            (Expression::Op(op1), Expression::Op(op2)) => op1.cmp(op2),
+        }
+    }
+}
+
+impl PartialOrd for Expression {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Evaluate for Expression {
+    fn evaluate(&self) -> i32 {
+        match self {
+            Expression::Num(n) => *n,
+            Expression::Op(op) => op.evaluate(),
+        }
+    }
+}
+
+impl Depth for Expression {
+    fn depth(&self) -> usize {
+        match self {
+            Expression::Num(_) => 1,
+            Expression::Op(op) => op.depth() + 1,
+        }
+    }
+}
+
+impl ExpressionEquals for Expression {
+    fn expr_equals(&self, other: &Expression) -> bool {
+        canonicalize(self) == canonicalize(other)
+    }
+}
+
+impl Complexity for Expression {
+    fn get_complexity(&self) -> u32 {
+        match self {
+            Expression::Num(_) => 10,
+            Expression::Op(op) => op.get_complexity(),
+        }
+    }
+
+    fn get_complexity_internal(&self, parent_op: OperationKind, is_left: bool) -> u32 {
+        match self {
+            Expression::Num(_) => 10,
+            Expression::Op(op) => op.get_complexity_internal(parent_op, is_left),
+        }
+    }
+}
+
+/// The cached depth of an expression is only computed once, from the cached depth of its
+/// operands
+impl Depth for EvaluatedExpr {
+    fn depth(&self) -> usize {
+        self.depth as usize
+    }
+}
+
+/// The cached complexity of an expression is only computed once, from the cached complexity of
+/// its operands
+impl Complexity for EvaluatedExpr {
+    fn get_complexity(&self) -> u32 {
+        self.complexity
+    }
+
+    fn get_complexity_internal(&self, parent_op: OperationKind, is_left: bool) -> u32 {
+        match &self.expression {
+            Expression::Op(op) if needs_parenthesis(op.kind, parent_op, is_left) => {
+                self.complexity + 10
+            }
+            _ => self.complexity,
+        }
+    }
+}
+
+/// Expressions are compared by their value and expression, as the cached complexity only
+/// depends on the expression
+impl PartialEq for EvaluatedExpr {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.expression == other.expression
+    }
+}
+
+impl Eq for EvaluatedExpr {}
+
+impl std::hash::Hash for EvaluatedExpr {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        self.expression.hash(state);
+    }
+}
+
+impl std::ops::Deref for EvaluatedExpr {
+    type Target = Expression;
+
+    fn deref(&self) -> &Self::Target {
+        &self.expression
+    }
+}
+
+impl std::ops::DerefMut for EvaluatedExpr {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.expression
+    }
+}
+
+/// Evaluated expressions are ordered by their expression, with the cached value as a tie-break
+impl Ord for EvaluatedExpr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.expression
+            .cmp(&other.expression)
+            .then_with(|| self.value.cmp(&other.value))
+    }
+}
+
+impl PartialOrd for EvaluatedExpr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl EvaluatedExpr {
+    fn new(expression: Expression) -> EvaluatedExpr {
+        EvaluatedExpr {
+            value: expression.evaluate(),
+            complexity: expression.get_complexity(),
+            depth: expression.depth() as u32,
+            expression,
+        }
+    }
+
+    /// The value of the expression as of when it was created (or last re-evaluated). Unlike
+    /// `evaluate` this doesn't walk the whole expression, so it's used in the generator's hot
+    /// loops.
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    /// Evaluate the expression with checked arithmetic, returning None if any operation
+    /// overflows or doesn't divide exactly
+    pub fn exact_value(&self) -> Option<i32> {
+        match &self.expression {
+            Expression::Num(num) => Some(*num),
+            Expression::Op(op) => {
+                let (left, right) = (op.left.exact_value()?, op.right.exact_value()?);
+                if op.kind == OperationKind::Divide && left.checked_rem(right) != Some(0) {
+                    return None;
+                }
+                checked_apply(op.kind, left, right)
+            }
+        }
+    }
+
+    /// Recompute the cached value, complexity and depth of the expression and everything in it,
+    /// e.g. after rearranging it in place through `DerefMut`
+    pub fn re_evaluate(&mut self) {
+        // The complexity and depth come from the operands' cached ones, so they go first
+        if let Expression::Op(op) = &mut self.expression {
+            op.re_evaluate();
+        }
+        self.value = self.expression.evaluate();
+        self.complexity = self.expression.get_complexity();
+        self.depth = self.expression.depth() as u32;
+        debug_assert_eq!(
+            self.exact_value(),
+            Some(self.value),
+            "{} doesn't evaluate exactly",
+            self.to_text()
+        );
+    }
+}
+
+/// Expressions are deserialized bottom up, so by the time an operation is checked its children
+/// already have valid values. The operation's value is then recomputed with checked arithmetic
+/// (and exact division), and has to match the stored value.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EvaluatedExpr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        struct Unchecked {
+            value: i32,
+            expression: Expression,
+        }
+
+        let Unchecked { value, expression } = Unchecked::deserialize(deserializer)?;
+
+        let expected = match &expression {
+            Expression::Num(num) => Some(*num),
+            Expression::Op(op) => {
+                let (left, right) = (op.left.value, op.right.value);
+                if op.kind == OperationKind::Divide && left.checked_rem(right) != Some(0) {
+                    None
+                } else {
+                    checked_apply(op.kind, left, right)
+                }
+            }
+        };
+
+        match expected {
+            Some(expected) if expected == value => Ok(EvaluatedExpr {
+                value,
+                complexity: expression.get_complexity(),
+                depth: expression.depth() as u32,
+                expression,
+            }),
+            Some(expected) => Err(D::Error::custom(format!(
+                "expression evaluates to {} but has the value {}",
+                expected, value
+            ))),
+            None => Err(D::Error::custom(
+                "expression overflows or doesn't divide exactly",
+            )),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::EvaluatedExpr;
+
+    #[test]
+    fn deserializing_rejects_overflowing_division() {
+        // `i32::MIN / -1` is the one division whose remainder overflows too
+        let json = r#"{"value":-2147483648,"expression":{"Op":{
+            "left":{"value":-2147483648,"expression":{"Num":-2147483648}},
+            "right":{"value":-1,"expression":{"Num":-1}},
+            "kind":"Divide"}}}"#;
+        assert!(serde_json::from_str::<EvaluatedExpr>(json).is_err());
+    }
+}
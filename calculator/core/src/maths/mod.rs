@@ -0,0 +1,54 @@
+pub mod canonical;
+pub mod expression;
+pub mod nary;
+pub mod operation;
+#[cfg(feature = "spoken")]
+mod words;
+
+pub use expression::{Beam, EvaluatedExpr, Expression, Pruning};
+pub use operation::{Operation, OperationKind};
+
+// Below are traits for functionality that is shared between both expression and operation
+
+pub trait Complexity {
+    /// An aribtary recursive complexity metric that I came up with, where
+    /// addition and subtraction are simple, multiplication and division are more complex
+    /// and powers are the most complex.
+    fn get_complexity(&self) -> u32;
+
+    fn get_complexity_internal(&self, parent_op: OperationKind, is_left: bool) -> u32;
+}
+
+/// Which version of the complexity metric to rank solutions with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComplexityModel {
+    /// Only the operations and parenthesis count, see `Complexity`
+    V1,
+    /// Same as `V1`, except large intermediate values also make an expression more complex,
+    /// as working through e.g. `2 ^ 9 - 502` is harder than staying in small numbers. See
+    /// `Expression::complexity_with`.
+    V2,
+}
+
+// `#[default]` on enum variants isn't stable on the pinned toolchain
+#[allow(clippy::derivable_impls)]
+impl Default for ComplexityModel {
+    fn default() -> Self {
+        ComplexityModel::V2
+    }
+}
+
+pub trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+pub trait ExpressionEquals {
+    /// Check if the expression is equivalent to another expression. This compares canonical
+    /// forms, so it's a proper equivalence relation (reflexive, symmetric and transitive).
+    fn expr_equals(&self, other: &Self) -> bool;
+}
+
+pub trait Depth {
+    /// Recursively get the depth of the expression
+    fn depth(&self) -> usize;
+}
@@ -0,0 +1,196 @@
+use std::cmp::Reverse;
+
+use super::{EvaluatedExpr, Expression, OperationKind};
+
+/// A term of a `NaryExpr::Sum`, which is subtracted rather than added if it's `negated`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub expr: NaryExpr,
+    pub negated: bool,
+}
+
+/// A factor of a `NaryExpr::Product`, which is divided by rather than multiplied by if it's
+/// a `reciprocal`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factor {
+    pub expr: NaryExpr,
+    pub reciprocal: bool,
+}
+
+/// An expression where every chain of additions/subtractions and every chain of
+/// multiplications/divisions is flattened into a single n-ary node, e.g. `a - (b - c) + d` is
+/// a sum of `a`, `-b`, `c` and `d`. Unlike the binary tree, how a chain is grouped and which
+/// order its terms are in are just the order of a list, so sorting the lists is enough to put
+/// commutative and associative rearrangements of an expression into the same form. It's the
+/// form that `canonicalize` dedups by, and the shuffle sorts chains in it for display.
+///
+/// The terms are never chains of their own kind, e.g. a term of a sum is never a sum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NaryExpr {
+    Num(i32),
+    Sum(Vec<Term>),
+    Product(Vec<Factor>),
+    #[cfg(feature = "power")]
+    Power(Box<NaryExpr>, Box<NaryExpr>),
+}
+
+impl NaryExpr {
+    /// Flatten a binary expression tree
+    pub fn from_expr(expr: &Expression) -> NaryExpr {
+        let op = match expr {
+            Expression::Num(n) => return NaryExpr::Num(*n),
+            Expression::Op(op) => op,
+        };
+
+        match op.kind {
+            OperationKind::Add | OperationKind::Subtract => {
+                let mut terms = Vec::new();
+                flatten_chain(expr, &SUM, false, &mut terms);
+                NaryExpr::Sum(
+                    terms
+                        .into_iter()
+                        .map(|(expr, negated)| Term { expr, negated })
+                        .collect(),
+                )
+            }
+            OperationKind::Multiply | OperationKind::Divide => {
+                let mut factors = Vec::new();
+                flatten_chain(expr, &PRODUCT, false, &mut factors);
+                NaryExpr::Product(
+                    factors
+                        .into_iter()
+                        .map(|(expr, reciprocal)| Factor { expr, reciprocal })
+                        .collect(),
+                )
+            }
+            #[cfg(feature = "power")]
+            OperationKind::Power => NaryExpr::Power(
+                Box::new(NaryExpr::from_expr(&op.left)),
+                Box::new(NaryExpr::from_expr(&op.right)),
+            ),
+        }
+    }
+
+    /// Rebuild a binary expression tree, where the forward terms of each chain are combined
+    /// first (in the order they're in) and then the inverse terms are applied, e.g.
+    /// `a + b + c - d - e`. Returns None if rebuilding would overflow, since regrouping can
+    /// go through bigger values than the expression it came from did, e.g. `a * b / c` from
+    /// `a * (b / c)`. Every chain has to have a forward term, which chains from `from_expr`
+    /// always do.
+    pub fn to_expr(&self) -> Option<EvaluatedExpr> {
+        match self {
+            NaryExpr::Num(n) => Some(Expression::new_num(*n)),
+            NaryExpr::Sum(terms) => {
+                rebuild_chain(terms.iter().map(|term| (&term.expr, term.negated)), &SUM)
+            }
+            NaryExpr::Product(factors) => rebuild_chain(
+                factors
+                    .iter()
+                    .map(|factor| (&factor.expr, factor.reciprocal)),
+                &PRODUCT,
+            ),
+            #[cfg(feature = "power")]
+            NaryExpr::Power(base, exponent) => Expression::new_op_unpruned(
+                base.to_expr()?,
+                exponent.to_expr()?,
+                OperationKind::Power,
+            ),
+        }
+    }
+
+    /// Sort every chain into its display order, with the forward terms before the inverse
+    /// ones and each side from the biggest term to the smallest (by the order of the rebuilt
+    /// expressions, see `Expression`'s `Ord`), e.g. `8 + 7 - 3 - 2`. Chains are sorted
+    /// inside out, so the order of a term is the order of its own sorted form. Terms that
+    /// can't be rebuilt (see `to_expr`) sort after the rest of their side.
+    pub fn sort(&mut self) {
+        match self {
+            NaryExpr::Num(_) => {}
+            NaryExpr::Sum(terms) => {
+                for term in terms.iter_mut() {
+                    term.expr.sort();
+                }
+                terms.sort_by_cached_key(|term| (term.negated, Reverse(term.expr.to_expr())));
+            }
+            NaryExpr::Product(factors) => {
+                for factor in factors.iter_mut() {
+                    factor.expr.sort();
+                }
+                factors.sort_by_cached_key(|factor| {
+                    (factor.reciprocal, Reverse(factor.expr.to_expr()))
+                });
+            }
+            #[cfg(feature = "power")]
+            NaryExpr::Power(base, exponent) => {
+                base.sort();
+                exponent.sort();
+            }
+        }
+    }
+}
+
+/// The operations that make up one kind of chain
+struct Chain {
+    forward: OperationKind,
+    inverse: OperationKind,
+}
+
+const SUM: Chain = Chain {
+    forward: OperationKind::Add,
+    inverse: OperationKind::Subtract,
+};
+
+const PRODUCT: Chain = Chain {
+    forward: OperationKind::Multiply,
+    inverse: OperationKind::Divide,
+};
+
+/// Recursively collect the terms of a chain, each with whether it's on the inverse side
+fn flatten_chain(
+    expr: &Expression,
+    chain: &Chain,
+    inverse: bool,
+    terms: &mut Vec<(NaryExpr, bool)>,
+) {
+    if let Expression::Op(op) = expr {
+        if op.kind == chain.forward || op.kind == chain.inverse {
+            flatten_chain(&op.left, chain, inverse, terms);
+            flatten_chain(
+                &op.right,
+                chain,
+                inverse ^ (op.kind == chain.inverse),
+                terms,
+            );
+            return;
+        }
+    }
+
+    terms.push((NaryExpr::from_expr(expr), inverse));
+}
+
+fn rebuild_chain<'a>(
+    terms: impl Iterator<Item = (&'a NaryExpr, bool)> + Clone,
+    chain: &Chain,
+) -> Option<EvaluatedExpr> {
+    let forward = terms.clone().filter(|(_, inverse)| !inverse);
+    let inverse = terms.filter(|(_, inverse)| *inverse);
+
+    let mut expr: Option<EvaluatedExpr> = None;
+    for (term, is_inverse) in forward.chain(inverse) {
+        let term = term.to_expr()?;
+        expr = Some(match expr {
+            None => term,
+            Some(left) => {
+                let kind = if is_inverse {
+                    chain.inverse
+                } else {
+                    chain.forward
+                };
+                Expression::new_op_unpruned(left, term, kind)?
+            }
+        });
+    }
+
+    // The leftmost leaf of a chain is always on the forward side, so this is never empty
+    expr
+}
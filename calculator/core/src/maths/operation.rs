@@ -0,0 +1,281 @@
+use std::cmp::Ordering;
+
+use super::expression::EvaluatedExpr;
+use super::*;
+#[cfg(feature = "spoken")]
+use crate::phrases::Phrases;
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OperationKind {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    #[cfg(feature = "power")]
+    Power,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Operation {
+    pub left: EvaluatedExpr,
+    pub right: EvaluatedExpr,
+    pub kind: OperationKind,
+}
+
+impl Operation {
+    /// Converts the operation into text
+    pub fn to_text(&self) -> String {
+        let left = self.left.to_text_child(self.kind, true);
+        let right = self.right.to_text_child(self.kind, false);
+
+        format!("{} {} {}", left, self.kind.symbol(), right)
+    }
+
+    /// Converts the operation into text, except considering the operator precedence to include or ignore parenthises
+    pub fn to_text_child(&self, parent_op: OperationKind, is_left: bool) -> String {
+        let use_parenthises = is_operator_greater_than(self.kind, parent_op) || !is_left;
+
+        if use_parenthises {
+            format!("({})", self.to_text())
+        } else {
+            self.to_text()
+        }
+    }
+
+    /// Converts the operation into words for screen readers, see `Expression::to_spoken_text_with`
+    #[cfg(feature = "spoken")]
+    pub fn to_spoken_text(&self, phrases: &Phrases) -> String {
+        let left = self.left.to_spoken_text_child(phrases, self.kind, true);
+        let right = self.right.to_spoken_text_child(phrases, self.kind, false);
+
+        format!("{} {} {}", left, phrases.spoken(self.kind), right)
+    }
+
+    /// Converts the operation into words, grouping it with brackets where `to_text_child`
+    /// would use parenthesis
+    #[cfg(feature = "spoken")]
+    pub fn to_spoken_text_child(
+        &self,
+        phrases: &Phrases,
+        parent_op: OperationKind,
+        is_left: bool,
+    ) -> String {
+        let use_parenthises = is_operator_greater_than(self.kind, parent_op) || !is_left;
+
+        if use_parenthises {
+            format!(
+                "{} {} {}",
+                phrases.open_bracket,
+                self.to_spoken_text(phrases),
+                phrases.close_bracket
+            )
+        } else {
+            self.to_spoken_text(phrases)
+        }
+    }
+
+    /// Recursively update the EvaluatedExpr cache
+    pub fn re_evaluate(&mut self) {
+        self.left.re_evaluate();
+        self.right.re_evaluate();
+    }
+
+    /// How far the tree is from balanced, which is the difference in depth between the two
+    /// sides of every operation added up. E.g. `(a + b) * (c + d)` is 0 while the left leaning
+    /// chain `a + b + c + d` is 3.
+    pub fn imbalance(&self) -> usize {
+        // The operands' depths are cached, so this only walks the tree once
+        let (left, right) = (self.left.depth(), self.right.depth());
+        let own = left.max(right) - left.min(right);
+        own + self.left.imbalance() + self.right.imbalance()
+    }
+}
+
+impl OperationKind {
+    /// Every operation that was compiled in
+    pub const ALL: &'static [OperationKind] = &[
+        OperationKind::Add,
+        OperationKind::Subtract,
+        OperationKind::Multiply,
+        OperationKind::Divide,
+        #[cfg(feature = "power")]
+        OperationKind::Power,
+    ];
+
+    /// The symbol used for the operation in text
+    pub fn symbol(self) -> char {
+        match self {
+            OperationKind::Add => '+',
+            OperationKind::Subtract => '-',
+            OperationKind::Multiply => '*',
+            OperationKind::Divide => '/',
+            #[cfg(feature = "power")]
+            OperationKind::Power => '^',
+        }
+    }
+
+    /// Get the operation for a symbol, the reverse of `symbol`
+    pub fn from_symbol(symbol: char) -> Option<OperationKind> {
+        OperationKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.symbol() == symbol)
+    }
+
+    /// The position of the operation in the ordering, where operations that bind
+    /// tighter come first
+    fn order_rank(self) -> u8 {
+        match self {
+            #[cfg(feature = "power")]
+            OperationKind::Power => 0,
+            OperationKind::Multiply => 1,
+            OperationKind::Divide => 2,
+            OperationKind::Add => 3,
+            OperationKind::Subtract => 4,
+        }
+    }
+
+    /// How tightly the operation binds, where operations that bind tighter are higher
+    pub(crate) fn precedence(self) -> u8 {
+        match self {
+            OperationKind::Add | OperationKind::Subtract => 0,
+            OperationKind::Multiply | OperationKind::Divide => 1,
+            #[cfg(feature = "power")]
+            OperationKind::Power => 2,
+        }
+    }
+}
+
+/// Operation kinds are ordered by how tightly they bind, tightest first. When shuffling,
+/// the greater operand goes on the left of an addition/multiplication, so this keeps looser
+/// operations on the left where they don't need parenthesis.
+impl Ord for OperationKind {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order_rank().cmp(&other.order_rank())
+    }
+}
+
+impl PartialOrd for OperationKind {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Operations are ordered by kind, then by the left operand, then by the right operand
+impl Ord for Operation {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind
+            .cmp(&other.kind)
+            .then_with(|| self.left.cmp(&other.left))
+            .then_with(|| self.right.cmp(&other.right))
+    }
+}
+
+impl PartialOrd for Operation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Evaluate for Operation {
+    fn evaluate(&self) -> i32 {
+        let (left, right) = (self.left.evaluate(), self.right.evaluate());
+        match self.kind {
+            OperationKind::Add => left + right,
+            OperationKind::Subtract => left - right,
+            OperationKind::Multiply => left * right,
+            OperationKind::Divide => left / right,
+            #[cfg(feature = "power")]
+            OperationKind::Power => {
+                // Negative exponents are rejected when the operation is created
+                let exponent = u32::try_from(right).expect("Negative exponent");
+                left.pow(exponent)
+            }
+        }
+    }
+}
+
+impl Depth for Operation {
+    fn depth(&self) -> usize {
+        let left_depth = self.left.depth();
+        let right_depth = self.right.depth();
+
+        left_depth.max(right_depth)
+    }
+}
+
+impl Complexity for Operation {
+    fn get_complexity(&self) -> u32 {
+        let left = self.left.get_complexity_internal(self.kind, true);
+        let right = self.right.get_complexity_internal(self.kind, false);
+
+        operation_complexity(self.kind, left, right)
+    }
+
+    fn get_complexity_internal(&self, parent_op: OperationKind, is_left: bool) -> u32 {
+        let internal_complexity = self.get_complexity();
+
+        if needs_parenthesis(self.kind, parent_op, is_left) {
+            internal_complexity + 10
+        } else {
+            internal_complexity
+        }
+    }
+}
+
+/// The complexity of an operation from the complexity of its operands, including their
+/// parenthesis
+pub(crate) fn operation_complexity(kind: OperationKind, left: u32, right: u32) -> u32 {
+    let complexity = left + right;
+
+    match kind {
+        OperationKind::Add | OperationKind::Subtract => complexity,
+        OperationKind::Multiply | OperationKind::Divide => complexity * 2,
+        #[cfg(feature = "power")]
+        OperationKind::Power => complexity * 5,
+    }
+}
+
+/// Check if an operand that's an operation counts as parenthesised for the complexity metric
+pub(crate) fn needs_parenthesis(
+    kind: OperationKind,
+    parent_op: OperationKind,
+    is_left: bool,
+) -> bool {
+    is_operator_greater_than(kind, parent_op) || !is_left
+}
+
+pub fn is_operator_greater_than(op1: OperationKind, op2: OperationKind) -> bool {
+    op1.precedence() < op2.precedence()
+}
+
+/// Apply the operation to two values, returning None if the result overflows
+/// or can't be represented (e.g. division by zero)
+pub fn checked_apply(kind: OperationKind, left: i32, right: i32) -> Option<i32> {
+    match kind {
+        OperationKind::Add => left.checked_add(right),
+        OperationKind::Subtract => left.checked_sub(right),
+        OperationKind::Multiply => left.checked_mul(right),
+        OperationKind::Divide => left.checked_div(right),
+        #[cfg(feature = "power")]
+        OperationKind::Power => left.checked_pow(u32::try_from(right).ok()?),
+    }
+}
+
+/// Get the operation that undoes the given operation, if there is one
+pub fn reverse_operation(op: OperationKind) -> Option<OperationKind> {
+    match op {
+        OperationKind::Add => Some(OperationKind::Subtract),
+        OperationKind::Subtract => Some(OperationKind::Add),
+        OperationKind::Multiply => Some(OperationKind::Divide),
+        OperationKind::Divide => Some(OperationKind::Multiply),
+        #[cfg(feature = "power")]
+        OperationKind::Power => None,
+    }
+}
+
+pub fn are_operations_reverse(op1: OperationKind, op2: OperationKind) -> bool {
+    reverse_operation(op1) == Some(op2)
+}
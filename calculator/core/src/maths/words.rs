@@ -0,0 +1,91 @@
+use crate::phrases::Phrases;
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// The scales for each group of three digits, from the largest down
+const SCALES: [(u64, &str); 3] = [
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+/// Write a number below 1000 in words
+fn small_number_to_words(n: u64, words: &mut Vec<String>) {
+    let (hundreds, rest) = (n / 100, n % 100);
+
+    if hundreds > 0 {
+        words.push(format!("{} hundred", ONES[hundreds as usize]));
+    }
+
+    if rest >= 20 {
+        match rest % 10 {
+            0 => words.push(TENS[rest as usize / 10].to_string()),
+            ones => words.push(format!(
+                "{}-{}",
+                TENS[rest as usize / 10],
+                ONES[ones as usize]
+            )),
+        }
+    } else if rest > 0 || hundreds == 0 {
+        words.push(ONES[rest as usize].to_string());
+    }
+}
+
+/// Write a number for `to_spoken_text`, either in english words or as digits
+pub fn spoken_number(n: i32, phrases: &Phrases) -> String {
+    if phrases.number_words {
+        number_to_words(n)
+    } else {
+        n.to_string()
+    }
+}
+
+/// Write a number in english words, e.g. "minus one thousand two hundred thirty-four"
+pub fn number_to_words(n: i32) -> String {
+    let mut words = Vec::new();
+    if n < 0 {
+        words.push("minus".to_string());
+    }
+
+    let sign_words = words.len();
+    let mut rest = (n as i64).unsigned_abs();
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            small_number_to_words(rest / scale, &mut words);
+            words.push(name.to_string());
+            rest %= scale;
+        }
+    }
+
+    // Zero is only written out when it's the whole number
+    if rest > 0 || words.len() == sign_words {
+        small_number_to_words(rest, &mut words);
+    }
+
+    words.join(" ")
+}
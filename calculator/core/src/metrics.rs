@@ -0,0 +1,87 @@
+use crate::maths::{
+    operation::is_operator_greater_than, Depth, Evaluate, EvaluatedExpr, Expression, OperationKind,
+};
+use crate::solver::Solution;
+
+/// Structural statistics about an expression, e.g. for achievements and analytics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionMetrics {
+    /// The depth of the expression tree, where a single number has a depth of 1
+    pub depth: usize,
+    /// How many times each compiled in operation is used, in the order of `OperationKind::ALL`
+    pub operator_counts: Vec<(OperationKind, usize)>,
+    /// The largest value of any number or sub expression, including the result
+    pub largest_value: i32,
+    /// How many pairs of parenthesis the expression's text has
+    pub parentheses: usize,
+    /// How deeply the parenthesis are nested, e.g. 2 for `((1 + 2) * 3 + 4) * 5`
+    pub nesting: usize,
+}
+
+impl ExpressionMetrics {
+    /// How many different operations the expression uses, e.g. 2 for `1 + 2 * 3 + 4`
+    pub fn distinct_operators(&self) -> usize {
+        self.operator_counts
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .count()
+    }
+}
+
+/// Measure an expression. The parenthesis are counted with the same rules as `to_text`.
+pub fn expression_metrics(expr: &EvaluatedExpr) -> ExpressionMetrics {
+    let mut operator_counts: Vec<_> = OperationKind::ALL.iter().map(|&op| (op, 0)).collect();
+    let mut largest_value = expr.evaluate();
+    let mut parentheses = 0;
+    let mut nesting = 0;
+
+    // Each node is paired with its parent's operation and whether it's the left child (if it
+    // has a parent), and how many parenthesis it's inside of
+    type Parent = Option<(OperationKind, bool)>;
+    let mut stack: Vec<(&EvaluatedExpr, Parent, usize)> = vec![(expr, None, 0)];
+    while let Some((node, parent, mut depth)) = stack.pop() {
+        largest_value = largest_value.max(node.evaluate());
+
+        if let Expression::Op(op) = &**node {
+            if let Some((parent_op, is_left)) = parent {
+                if is_operator_greater_than(op.kind, parent_op) || !is_left {
+                    parentheses += 1;
+                    depth += 1;
+                    nesting = nesting.max(depth);
+                }
+            }
+
+            if let Some((_, count)) = operator_counts.iter_mut().find(|(k, _)| *k == op.kind) {
+                *count += 1;
+            }
+
+            stack.push((&op.left, Some((op.kind, true)), depth));
+            stack.push((&op.right, Some((op.kind, false)), depth));
+        }
+    }
+
+    ExpressionMetrics {
+        depth: expr.depth(),
+        operator_counts,
+        largest_value,
+        parentheses,
+        nesting,
+    }
+}
+
+/// Count how many of the solutions use each compiled in operation at least once, in the order
+/// of `OperationKind::ALL`, e.g. to show that 80% of the solutions need multiplication
+pub fn operation_usage(solutions: &[Solution]) -> Vec<(OperationKind, usize)> {
+    let mut usage: Vec<_> = OperationKind::ALL.iter().map(|&op| (op, 0)).collect();
+
+    for solution in solutions {
+        let metrics = expression_metrics(&solution.expression);
+        for ((_, used), (_, count)) in usage.iter_mut().zip(metrics.operator_counts) {
+            if count > 0 {
+                *used += 1;
+            }
+        }
+    }
+
+    usage
+}
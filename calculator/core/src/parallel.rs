@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use log::debug;
+
+use crate::generate::ExpressionIter;
+use crate::maths::{Evaluate, EvaluatedExpr};
+use crate::solver::{Normalizer, Solution};
+
+/// How many expressions can wait for a worker before the generator blocks, which bounds the
+/// memory used when the workers fall behind
+const CHANNEL_BOUND: usize = 1024;
+
+/// What a worker found for an expression: its position in the generator's order, its
+/// fingerprint, and the solution if it's one
+type Normalized = (usize, u64, Option<Solution>);
+
+/// Normalize the expressions that a worker takes off the channel until it's closed
+fn work(
+    expressions: Arc<Mutex<Receiver<(usize, EvaluatedExpr)>>>,
+    normalizer: Normalizer,
+) -> Vec<Normalized> {
+    let mut normalized = Vec::new();
+    loop {
+        // The lock is only held while waiting for the next expression
+        let next = expressions.lock().unwrap().recv();
+        let (index, mut expr) = match next {
+            Ok(next) => next,
+            Err(_) => return normalized,
+        };
+
+        let canonical = normalizer.canonical(&mut expr);
+        let fingerprint = canonical.fingerprint();
+        let solution = if normalizer.within_depth(&canonical) {
+            normalizer.solution(canonical, expr)
+        } else {
+            None
+        };
+        normalized.push((index, fingerprint, solution));
+    }
+}
+
+/// Find the solutions in the generator's expressions, generating them on this thread while
+/// worker threads canonicalize and shuffle them, which dominates solving on puzzles with lots of
+/// solutions. The workers normalize duplicates too, as they can't know which copy the
+/// generator made first, and the copy that was made first is kept so the solutions are the
+/// same as `SolutionIter`'s, in the same order.
+pub(crate) fn find_solutions(
+    expressions: ExpressionIter,
+    target: i32,
+    normalizer: Normalizer,
+) -> Vec<Solution> {
+    let threads = thread::available_parallelism()
+        .map(|threads| threads.get().saturating_sub(1))
+        .unwrap_or(1)
+        .max(1);
+    let (sender, receiver) = sync_channel(CHANNEL_BOUND);
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let receiver = Arc::clone(&receiver);
+            let normalizer = normalizer.clone();
+            thread::spawn(move || work(receiver, normalizer))
+        })
+        .collect();
+
+    let mut checked = 0;
+    for (index, expr) in expressions.enumerate() {
+        if expr.evaluate() == target {
+            checked += 1;
+            sender.send((index, expr)).unwrap();
+        }
+    }
+    drop(sender);
+
+    let mut normalized: Vec<Normalized> = workers
+        .into_iter()
+        .flat_map(|worker| worker.join().unwrap())
+        .collect();
+    normalized.sort_unstable_by_key(|(index, _, _)| *index);
+
+    let mut seen = HashSet::new();
+    let solutions: Vec<Solution> = normalized
+        .into_iter()
+        .filter(|(_, fingerprint, _)| seen.insert(*fingerprint))
+        .filter_map(|(_, _, solution)| solution)
+        .collect();
+    debug!(
+        "Found {} expressions that make the target on {} threads, {} of them distinct",
+        checked,
+        threads,
+        seen.len()
+    );
+    solutions
+}
@@ -0,0 +1,230 @@
+use std::ops::Range;
+
+use crate::custom_rules::Template;
+use crate::error::CalcError;
+use crate::maths::{Evaluate, EvaluatedExpr, Expression, OperationKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Num(i32),
+    /// A letter that stands for any expression, which is only allowed in templates
+    Var(char),
+    Op(OperationKind),
+    Open,
+    Close,
+}
+
+fn parse_error(message: impl Into<String>, span: Range<usize>) -> CalcError {
+    CalcError::ParseError {
+        message: message.into(),
+        span,
+    }
+}
+
+/// Split the text into tokens along with their byte ranges, skipping whitespace. A `-` directly
+/// in front of a number is part of the number when an operator couldn't go there, e.g. `-3 + 5`
+/// or `5 - (-3)`. Lowercase letters are variables if `variables` is set.
+fn tokenize(text: &str, variables: bool) -> Result<Vec<(Token, Range<usize>)>, CalcError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let end = start + c.len_utf8();
+
+        let is_sign = c == '-'
+            && matches!(chars.peek(), Some((_, next)) if next.is_ascii_digit())
+            && !matches!(tokens.last(), Some((Token::Num(_) | Token::Close, _)));
+
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if variables && c.is_ascii_lowercase() => Token::Var(c),
+            c if c.is_ascii_digit() || is_sign => {
+                let mut end = end;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+
+                let num = text[start..end]
+                    .parse()
+                    .map_err(|_| parse_error("Number is too large", start..end))?;
+                tokens.push((Token::Num(num), start..end));
+                continue;
+            }
+            c => match OperationKind::from_symbol(c) {
+                Some(kind) => Token::Op(kind),
+                None => return Err(parse_error(format!("Unexpected '{}'", c), start..end)),
+            },
+        };
+
+        tokens.push((token, start..end));
+    }
+
+    Ok(tokens)
+}
+
+/// Pop the top two expressions off the output and combine them with the operation
+fn apply(
+    output: &mut Vec<(EvaluatedExpr, Range<usize>)>,
+    kind: OperationKind,
+) -> Result<(), CalcError> {
+    // The parser only pushes operations after an operand, so there are always two here
+    let (right, right_span) = output.pop().expect("Missing right operand");
+    let (left, left_span) = output.pop().expect("Missing left operand");
+    let span = left_span.start..right_span.end;
+
+    let (left_val, right_val) = (left.evaluate(), right.evaluate());
+    // Dividing the smallest number by -1 overflows, which is reported below instead
+    if kind == OperationKind::Divide && (right_val == 0 || left_val.wrapping_rem(right_val) != 0) {
+        return Err(parse_error(
+            format!("{} / {} isn't a whole number", left_val, right_val),
+            span,
+        ));
+    }
+    #[cfg(feature = "power")]
+    if kind == OperationKind::Power && right_val < 0 {
+        return Err(parse_error(
+            format!("{} ^ {} has a negative exponent", left_val, right_val),
+            span,
+        ));
+    }
+    let expr = Expression::new_op_unpruned(left, right, kind).ok_or(CalcError::Overflow)?;
+    output.push((expr, span));
+    Ok(())
+}
+
+/// Parse an expression like `(3 + 7) * 2 - 8 / 4`, the same format that solutions are written in.
+/// Operators with the same precedence are applied from left to right, and every intermediate
+/// value has to be a whole number.
+///
+/// Parsing uses the shunting yard algorithm with explicit stacks rather than recursion, so
+/// deeply nested parenthesis can't overflow the stack.
+pub fn parse_expression(text: &str) -> Result<EvaluatedExpr, CalcError> {
+    let mut output: Vec<(EvaluatedExpr, Range<usize>)> = Vec::new();
+    // The pending operations, where None is an open parenthesis
+    let mut pending: Vec<(Option<OperationKind>, Range<usize>)> = Vec::new();
+    let mut expect_operand = true;
+
+    for (token, span) in tokenize(text, false)? {
+        match token {
+            Token::Var(_) => unreachable!("Variables are only tokenized in templates"),
+            Token::Num(_) | Token::Open if !expect_operand => {
+                return Err(parse_error("Expected an operator", span));
+            }
+            Token::Op(_) | Token::Close if expect_operand => {
+                return Err(parse_error("Expected a number", span));
+            }
+            Token::Num(num) => {
+                output.push((Expression::new_num(num), span));
+                expect_operand = false;
+            }
+            Token::Open => pending.push((None, span)),
+            Token::Op(kind) => {
+                while let Some(&(Some(top), _)) = pending.last() {
+                    if top.precedence() < kind.precedence() {
+                        break;
+                    }
+                    pending.pop();
+                    apply(&mut output, top)?;
+                }
+                pending.push((Some(kind), span));
+                expect_operand = true;
+            }
+            Token::Close => loop {
+                match pending.pop() {
+                    Some((Some(kind), _)) => apply(&mut output, kind)?,
+                    Some((None, _)) => break,
+                    None => return Err(parse_error("Unmatched ')'", span)),
+                }
+            },
+        }
+    }
+
+    if expect_operand {
+        return Err(parse_error("Expected a number", text.len()..text.len()));
+    }
+
+    while let Some((kind, span)) = pending.pop() {
+        match kind {
+            Some(kind) => apply(&mut output, kind)?,
+            None => return Err(parse_error("Unclosed '('", span)),
+        }
+    }
+
+    // The operand checks above guarantee that everything was combined into one expression
+    Ok(output.pop().expect("Missing expression").0)
+}
+
+/// Parse a template for a custom rewrite rule, which is an expression where lowercase letters
+/// stand for any sub-expression, e.g. `a * a` or `(a - b) + 0`. It's parsed the same way as
+/// `parse_expression`, except nothing is evaluated.
+pub(crate) fn parse_template(text: &str) -> Result<Template, CalcError> {
+    let mut output: Vec<Template> = Vec::new();
+    // The pending operations, where None is an open parenthesis
+    let mut pending: Vec<(Option<OperationKind>, Range<usize>)> = Vec::new();
+    let mut expect_operand = true;
+    let apply = |output: &mut Vec<Template>, kind| {
+        let right = output.pop().expect("Missing right operand");
+        let left = output.pop().expect("Missing left operand");
+        output.push(Template::Op(Box::new(left), kind, Box::new(right)));
+    };
+
+    for (token, span) in tokenize(text, true)? {
+        match token {
+            Token::Num(_) | Token::Var(_) | Token::Open if !expect_operand => {
+                return Err(parse_error("Expected an operator", span));
+            }
+            Token::Op(_) | Token::Close if expect_operand => {
+                return Err(parse_error("Expected a number or variable", span));
+            }
+            Token::Num(num) => {
+                output.push(Template::Num(num));
+                expect_operand = false;
+            }
+            Token::Var(name) => {
+                output.push(Template::Var(name));
+                expect_operand = false;
+            }
+            Token::Open => pending.push((None, span)),
+            Token::Op(kind) => {
+                while let Some(&(Some(top), _)) = pending.last() {
+                    if top.precedence() < kind.precedence() {
+                        break;
+                    }
+                    pending.pop();
+                    apply(&mut output, top);
+                }
+                pending.push((Some(kind), span));
+                expect_operand = true;
+            }
+            Token::Close => loop {
+                match pending.pop() {
+                    Some((Some(kind), _)) => apply(&mut output, kind),
+                    Some((None, _)) => break,
+                    None => return Err(parse_error("Unmatched ')'", span)),
+                }
+            },
+        }
+    }
+
+    if expect_operand {
+        return Err(parse_error(
+            "Expected a number or variable",
+            text.len()..text.len(),
+        ));
+    }
+
+    while let Some((kind, span)) = pending.pop() {
+        match kind {
+            Some(kind) => apply(&mut output, kind),
+            None => return Err(parse_error("Unclosed '('", span)),
+        }
+    }
+
+    Ok(output.pop().expect("Missing template"))
+}
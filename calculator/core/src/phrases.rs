@@ -0,0 +1,87 @@
+use crate::maths::OperationKind;
+
+/// The wording used by the explanation and spoken renderers, so they can be localized
+/// instead of always being in english.
+///
+/// The explanation templates (`add` to `number`) are used by `explain`. The operation templates
+/// can use `{left}`, `{right}` and `{result}`, the step templates wrap an operation template as
+/// `{step}`, and `number` is used (with `{result}`) when there are no operations.
+///
+/// The spoken words (`plus` to `number_words`) are used by `Expression::to_spoken_text_with`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phrases {
+    pub add: String,
+    pub subtract: String,
+    pub multiply: String,
+    pub divide: String,
+    pub power: String,
+    /// The first step (also used when there's only one step)
+    pub first: String,
+    /// Every step between the first and last step
+    pub then: String,
+    /// The last step
+    pub last: String,
+    pub number: String,
+
+    pub plus: String,
+    pub minus: String,
+    pub times: String,
+    pub divided_by: String,
+    pub to_the_power_of: String,
+    pub open_bracket: String,
+    pub close_bracket: String,
+    /// Whether numbers are spelled out in english words, otherwise they're left as digits
+    /// (which screen readers read out in their own language)
+    pub number_words: bool,
+}
+
+impl Default for Phrases {
+    fn default() -> Self {
+        Phrases {
+            add: "add {left} and {right} to get {result}".to_string(),
+            subtract: "subtract {right} from {left} to get {result}".to_string(),
+            multiply: "multiply {left} and {right} to get {result}".to_string(),
+            divide: "divide {left} by {right} to get {result}".to_string(),
+            power: "raise {left} to the power of {right} to get {result}".to_string(),
+            first: "First {step}".to_string(),
+            then: "Then {step}".to_string(),
+            last: "Finally {step}".to_string(),
+            number: "The answer is just {result}".to_string(),
+
+            plus: "plus".to_string(),
+            minus: "minus".to_string(),
+            times: "times".to_string(),
+            divided_by: "divided by".to_string(),
+            to_the_power_of: "to the power of".to_string(),
+            open_bracket: "open bracket".to_string(),
+            close_bracket: "close bracket".to_string(),
+            number_words: true,
+        }
+    }
+}
+
+impl Phrases {
+    /// The explanation template for an operation
+    pub(crate) fn explanation(&self, kind: OperationKind) -> &str {
+        match kind {
+            OperationKind::Add => &self.add,
+            OperationKind::Subtract => &self.subtract,
+            OperationKind::Multiply => &self.multiply,
+            OperationKind::Divide => &self.divide,
+            #[cfg(feature = "power")]
+            OperationKind::Power => &self.power,
+        }
+    }
+
+    /// The spoken word for an operation
+    pub(crate) fn spoken(&self, kind: OperationKind) -> &str {
+        match kind {
+            OperationKind::Add => &self.plus,
+            OperationKind::Subtract => &self.minus,
+            OperationKind::Multiply => &self.times,
+            OperationKind::Divide => &self.divided_by,
+            #[cfg(feature = "power")]
+            OperationKind::Power => &self.to_the_power_of,
+        }
+    }
+}
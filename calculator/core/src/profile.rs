@@ -0,0 +1,71 @@
+use crate::maths::{ComplexityModel, OperationKind};
+
+/// A preset of ranking and pruning options for an audience, which replaces the operations and
+/// complexity model of a `SolverConfig` and caps how big the intermediate values can get
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Young players: only `+`, `-` and `*`, and every value along the way stays within 100
+    Grade3,
+    /// Everyday players: no powers, and every value along the way stays within 1000
+    Casual,
+    /// Every operation with no cap, ranked only by the operations and parenthesis used
+    Expert,
+}
+
+impl Profile {
+    /// Every profile
+    pub const ALL: &'static [Profile] = &[Profile::Grade3, Profile::Casual, Profile::Expert];
+
+    /// The name of the profile, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Grade3 => "grade-3",
+            Profile::Casual => "casual",
+            Profile::Expert => "expert",
+        }
+    }
+
+    /// Get the profile with a name, the reverse of `name`
+    pub fn from_name(name: &str) -> Option<Profile> {
+        Profile::ALL
+            .iter()
+            .copied()
+            .find(|profile| profile.name() == name)
+    }
+
+    /// The operations that solutions are allowed to use
+    pub fn operations(self) -> Vec<OperationKind> {
+        match self {
+            Profile::Grade3 => vec![
+                OperationKind::Add,
+                OperationKind::Subtract,
+                OperationKind::Multiply,
+            ],
+            Profile::Casual => vec![
+                OperationKind::Add,
+                OperationKind::Subtract,
+                OperationKind::Multiply,
+                OperationKind::Divide,
+            ],
+            Profile::Expert => OperationKind::ALL.to_vec(),
+        }
+    }
+
+    /// The complexity metric that solutions are ranked by
+    pub fn complexity_model(self) -> ComplexityModel {
+        match self {
+            Profile::Grade3 | Profile::Casual => ComplexityModel::V2,
+            Profile::Expert => ComplexityModel::V1,
+        }
+    }
+
+    /// The biggest value (either way from zero) that any operation in a solution can have,
+    /// including the final one, or None for no cap
+    pub fn max_intermediate(self) -> Option<u32> {
+        match self {
+            Profile::Grade3 => Some(100),
+            Profile::Casual => Some(1000),
+            Profile::Expert => None,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::maths::Expression;
+
+/// Work out which input slot each leaf of an expression came from, in the order that the
+/// leaves appear in the expression's text. Copies of the same number are interchangeable, so
+/// repeated numbers are assigned to their slots in order from left to right.
+/// Returns None if the leaves don't use exactly the numbers in the inputs.
+pub fn leaf_slots(expr: &Expression, inputs: &[i32]) -> Option<Vec<usize>> {
+    let mut used = vec![false; inputs.len()];
+    let mut slots = Vec::new();
+
+    for leaf in expr.leaves() {
+        let slot = (0..inputs.len()).find(|&i| !used[i] && inputs[i] == leaf)?;
+        used[slot] = true;
+        slots.push(slot);
+    }
+
+    if used.iter().all(|&u| u) {
+        Some(slots)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,34 @@
+use log::debug;
+
+use crate::rng::Rng;
+use crate::{is_solvable, CalcError, SolverConfig};
+
+/// How many random puzzles to try before giving up on finding a solvable one
+const MAX_ATTEMPTS: usize = 1000;
+
+/// Generate a random solvable puzzle with `count` single digit numbers (also kept within the
+/// config's input value limits), using the config's target and operations.
+/// The same seed always produces the same puzzle.
+/// Fails with `CalcError::Unsolvable` if no solvable puzzle was found (e.g. the target can't be
+/// reached at all), or `CalcError::InvalidInput` if `count` is outside of the input limits.
+pub fn generate_puzzle(
+    seed: u64,
+    count: usize,
+    config: &SolverConfig,
+) -> Result<Vec<i32>, CalcError> {
+    let mut rng = Rng::new(seed);
+    for attempt in 1..=MAX_ATTEMPTS {
+        let puzzle: Vec<i32> = (0..count)
+            .map(|_| rng.range_i32(config.limits.min_value, config.limits.max_value.min(9)))
+            .collect();
+
+        // Any error means the count itself is invalid, so there's no point trying again
+        if is_solvable(&puzzle, config)? {
+            debug!("Generated {:?} after {} attempts", puzzle, attempt);
+            return Ok(puzzle);
+        }
+    }
+
+    debug!("No solvable puzzle found in {} attempts", MAX_ATTEMPTS);
+    Err(CalcError::Unsolvable)
+}
@@ -0,0 +1,76 @@
+//! Python bindings for the solver, built as the `make_ten_core` python module
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::maths::OperationKind;
+use crate::{CalcError, SolverConfig};
+
+fn make_config(target: i32, ops: Option<&str>) -> PyResult<SolverConfig> {
+    let mut config = SolverConfig {
+        target,
+        ..Default::default()
+    };
+
+    if let Some(ops) = ops {
+        config.operations = ops
+            .chars()
+            .map(|c| {
+                OperationKind::from_symbol(c)
+                    .ok_or_else(|| PyValueError::new_err(format!("Unknown operator: {}", c)))
+            })
+            .collect::<PyResult<_>>()?;
+    }
+
+    Ok(config)
+}
+
+/// Solve a puzzle, returning a list of `(text, complexity)` tuples sorted simplest first
+#[pyfunction]
+#[pyo3(signature = (inputs, target = 10, ops = None))]
+fn solve(inputs: Vec<i32>, target: i32, ops: Option<&str>) -> PyResult<Vec<(String, u32)>> {
+    let config = make_config(target, ops)?;
+    let solutions =
+        crate::solve(&inputs, &config).map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+    Ok(solutions
+        .map(|solution| (solution.text, solution.complexity))
+        .collect())
+}
+
+/// Rate a puzzle from 0 (trivial) to 100 (very hard), or None if it's unsolvable
+#[pyfunction]
+#[pyo3(signature = (inputs, target = 10, ops = None))]
+fn rate_difficulty(inputs: Vec<i32>, target: i32, ops: Option<&str>) -> PyResult<Option<u32>> {
+    let config = make_config(target, ops)?;
+    match crate::rate_difficulty(&inputs, &config) {
+        Ok(rating) => Ok(Some(rating)),
+        Err(CalcError::Unsolvable) => Ok(None),
+        Err(err) => Err(PyValueError::new_err(err.to_string())),
+    }
+}
+
+/// Generate a random solvable puzzle from a seed, or None if one couldn't be found
+#[pyfunction]
+#[pyo3(signature = (seed, count = 4, target = 10, ops = None))]
+fn generate_puzzle(
+    seed: u64,
+    count: usize,
+    target: i32,
+    ops: Option<&str>,
+) -> PyResult<Option<Vec<i32>>> {
+    let config = make_config(target, ops)?;
+    match crate::generate_puzzle(seed, count, &config) {
+        Ok(puzzle) => Ok(Some(puzzle)),
+        Err(CalcError::Unsolvable) => Ok(None),
+        Err(err) => Err(PyValueError::new_err(err.to_string())),
+    }
+}
+
+#[pymodule]
+fn make_ten_core(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(rate_difficulty, m)?)?;
+    m.add_function(wrap_pyfunction!(generate_puzzle, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use crate::maths::{canonical::canonicalize, Evaluate, EvaluatedExpr};
+use crate::shuffle::fully_shuffle_expr;
+use crate::{CalcError, SolverConfig};
+
+/// A value that can be made from the inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReachableValue {
+    /// The value
+    pub value: i32,
+    /// The simplest expression that makes the value, shuffled into its display form
+    pub expression: EvaluatedExpr,
+    /// The expression as text
+    pub text: String,
+}
+
+/// Find every value that can be made from the inputs with the config's operations (the target
+/// is ignored), smallest first, along with the simplest expression that makes each of them.
+/// Every expression is generated, so this takes as long as the slowest possible solve.
+pub fn reachable_values(
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<Vec<ReachableValue>, CalcError> {
+    let config = &config.resolved();
+    config.validate(inputs)?;
+
+    // The simplest expression for each value, along with its complexity
+    let mut simplest: BTreeMap<i32, (u32, EvaluatedExpr)> = BTreeMap::new();
+
+    for expr in config.expressions(inputs, None) {
+        let complexity = expr.complexity_with(config.complexity_model);
+        match simplest.get(&expr.value()) {
+            Some((best, _)) if *best <= complexity => {}
+            _ => {
+                simplest.insert(expr.value(), (complexity, expr));
+            }
+        }
+    }
+
+    Ok(simplest
+        .into_values()
+        .map(|(_, expr)| {
+            // Display the expression the same way as a solution would be
+            let mut expression = canonicalize(&expr).to_expr().unwrap_or(expr);
+            fully_shuffle_expr(&mut expression);
+
+            ReachableValue {
+                value: expression.evaluate(),
+                text: expression.to_text(),
+                expression,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,175 @@
+use crate::generate::SplitStrategy;
+use crate::maths::OperationKind;
+use crate::{solve, SolverConfig, SolverMode};
+
+/// Puzzles with every solution that `solve` returns for them, in order, see `known_answers.txt`
+const KNOWN_ANSWERS: &str = include_str!("known_answers.txt");
+
+/// A puzzle in the corpus along with its expected solutions
+struct KnownAnswer {
+    inputs: Vec<i32>,
+    config: SolverConfig,
+    /// None if it uses an operation that isn't compiled in
+    solutions: Option<Vec<String>>,
+}
+
+/// Parse a puzzle's header, e.g. `2 3 5 -> 1 +-*/^ allPartitions`, and its solutions
+fn parse_known_answer<'a>(header: &str, solutions: impl Iterator<Item = &'a str>) -> KnownAnswer {
+    let (inputs, rest) = header
+        .split_once("->")
+        .expect("every puzzle in the corpus has a target");
+    let inputs = inputs
+        .split_whitespace()
+        .map(|n| n.parse().expect("the corpus inputs are numbers"))
+        .collect();
+
+    let mut rest = rest.split_whitespace();
+    let mut config = SolverConfig {
+        target: rest
+            .next()
+            .and_then(|target| target.parse().ok())
+            .expect("the corpus targets are numbers"),
+        ..SolverConfig::default()
+    };
+    let operations: Option<Vec<OperationKind>> = rest
+        .next()
+        .expect("every puzzle in the corpus has its operations")
+        .chars()
+        .map(OperationKind::from_symbol)
+        .collect();
+
+    for option in rest {
+        if let Some(strategy) = SplitStrategy::from_name(option) {
+            config.split_strategy = strategy;
+        } else if let Some(mode) = SolverMode::from_name(option) {
+            config.mode = mode;
+        } else {
+            panic!("Unknown option in the corpus: {}", option);
+        }
+    }
+
+    let solutions = operations.map(|operations| {
+        config.operations = operations;
+        solutions.map(str::to_string).collect()
+    });
+    KnownAnswer {
+        inputs,
+        config,
+        solutions,
+    }
+}
+
+/// Every puzzle in the corpus. Puzzles are separated by blank lines, and lines starting with
+/// `#` are comments.
+fn known_answers() -> Vec<KnownAnswer> {
+    let lines: Vec<&str> = KNOWN_ANSWERS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+
+    lines
+        .split(|line| line.is_empty())
+        .filter(|puzzle| !puzzle.is_empty())
+        .map(|puzzle| parse_known_answer(puzzle[0], puzzle[1..].iter().copied()))
+        .collect()
+}
+
+/// A puzzle from the corpus whose solutions have changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegressionDiff {
+    pub inputs: Vec<i32>,
+    pub target: i32,
+    /// The expected solutions that weren't found, as text
+    pub missing: Vec<String>,
+    /// The solutions that were found but weren't expected, as text
+    pub unexpected: Vec<String>,
+    /// Whether the solutions were all the expected ones but in a different order
+    pub reordered: bool,
+}
+
+/// The result of `run_regression_suite`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegressionReport {
+    /// How many puzzles were solved and compared
+    pub puzzles_checked: usize,
+    /// How many puzzles were skipped because they use an operation that isn't compiled in
+    pub puzzles_skipped: usize,
+    /// Every puzzle whose solutions have changed, in the order of the corpus
+    pub diffs: Vec<RegressionDiff>,
+}
+
+impl RegressionReport {
+    /// Whether every puzzle still has exactly the expected solutions in the expected order
+    pub fn passed(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+/// Solve every puzzle in the corpus that ships with the crate and compare the solutions with
+/// the known answers. The solutions are compared by their text in the order that `solve`
+/// returns them, so changes to the pruning (which solutions are found), dedup (which are
+/// distinct), shuffle (how they're displayed) and ranking (their order) all show up as diffs
+/// rather than silently changing the results.
+///
+/// When a change to the results is intended, the corpus (`known_answers.txt`) has to be
+/// updated with the new solutions, e.g. from the CLI's output for each puzzle.
+pub fn run_regression_suite() -> RegressionReport {
+    let mut puzzles_checked = 0;
+    let mut puzzles_skipped = 0;
+    let mut diffs = Vec::new();
+
+    for known in known_answers() {
+        let expected = match known.solutions {
+            Some(solutions) => solutions,
+            None => {
+                puzzles_skipped += 1;
+                continue;
+            }
+        };
+
+        puzzles_checked += 1;
+        let found: Vec<String> = solve(&known.inputs, &known.config)
+            .expect("the corpus puzzles are within the default limits")
+            .map(|solution| solution.text)
+            .collect();
+        if found == expected {
+            continue;
+        }
+
+        let missing: Vec<String> = expected
+            .iter()
+            .filter(|text| !found.contains(text))
+            .cloned()
+            .collect();
+        let unexpected: Vec<String> = found
+            .iter()
+            .filter(|text| !expected.contains(text))
+            .cloned()
+            .collect();
+        diffs.push(RegressionDiff {
+            inputs: known.inputs,
+            target: known.config.target,
+            reordered: missing.is_empty() && unexpected.is_empty(),
+            missing,
+            unexpected,
+        });
+    }
+
+    RegressionReport {
+        puzzles_checked,
+        puzzles_skipped,
+        diffs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_still_has_the_known_answers() {
+        let report = run_regression_suite();
+        assert!(report.passed(), "{:?}", report.diffs);
+    }
+}
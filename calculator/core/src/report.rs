@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use crate::maths::{canonical::canonicalize, Evaluate, EvaluatedExpr, OperationKind};
+use crate::shuffle::fully_shuffle_expr;
+use crate::value_sets::closest_values;
+use crate::{CalcError, SolverConfig};
+
+/// How many of the closest values to keep in an `UnsolvableReport`
+const CLOSEST_VALUES: usize = 3;
+
+/// A value that an expression reached instead of the target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearMiss {
+    /// The value that was reached
+    pub value: i32,
+    /// An example expression that reaches the value, shuffled into its display form
+    pub expression: EvaluatedExpr,
+    /// The example expression as text
+    pub text: String,
+}
+
+/// Why a puzzle has no solutions, so there's more to show than "no solutions"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsolvableReport {
+    /// How many operations on the values that each part of the inputs can make were tried,
+    /// which is far fewer than the number of expressions as each value is only tried once
+    pub operations_tried: usize,
+    /// The closest distinct values to the target that were reached, closest first
+    pub closest: Vec<NearMiss>,
+    /// Every non-empty subset of the allowed operations, smallest first. The full search
+    /// covers all of them, so none of them can reach the target either.
+    pub exhausted_operations: Vec<Vec<OperationKind>>,
+}
+
+/// Every non-empty subset of the operations, smallest first
+fn operation_subsets(operations: &[OperationKind]) -> Vec<Vec<OperationKind>> {
+    let mut subsets: Vec<Vec<OperationKind>> = (1..(1u32 << operations.len()))
+        .map(|mask| {
+            operations
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, &op)| op)
+                .collect()
+        })
+        .collect();
+
+    subsets.sort_by_key(|subset| subset.len());
+    subsets
+}
+
+/// Find the distinct expressions whose value is within `delta` of the target without being
+/// exactly the target, e.g. for hints like "you can make 9 and 11, what gets you to 10?".
+/// They're sorted by how close they are, then from simplest to most complex, and there are at
+/// most `max_results` of them.
+pub fn near_misses(
+    inputs: &[i32],
+    config: &SolverConfig,
+    delta: u32,
+) -> Result<Vec<NearMiss>, CalcError> {
+    let config = &config.resolved();
+    config.validate(inputs)?;
+
+    let distance = |value: i32| (value as i64 - config.target as i64).unsigned_abs();
+
+    let mut seen = HashSet::new();
+    let mut misses = Vec::new();
+    for expr in config.expressions(inputs, None) {
+        let value_distance = distance(expr.value());
+        if value_distance == 0 || value_distance > delta as u64 {
+            continue;
+        }
+
+        let canonical = canonicalize(&expr);
+        if seen.contains(&canonical) {
+            continue;
+        }
+
+        // Display the expression the same way as a solution would be
+        let mut expression = canonical.to_expr().unwrap_or(expr);
+        fully_shuffle_expr(&mut expression);
+        seen.insert(canonical.clone());
+        misses.push((
+            value_distance,
+            expression.complexity_with(config.complexity_model),
+            canonical,
+            expression,
+        ));
+    }
+
+    misses.sort_by(|a, b| (a.0, a.1, &a.2).cmp(&(b.0, b.1, &b.2)));
+
+    Ok(misses
+        .into_iter()
+        .take(config.max_results.unwrap_or(usize::MAX))
+        .map(|(_, _, _, expression)| NearMiss {
+            value: expression.evaluate(),
+            text: expression.to_text(),
+            expression,
+        })
+        .collect())
+}
+
+/// Check why the inputs have no solutions, returning None if the puzzle has a solution. The
+/// value sets (see `quick_solution`) are worked out rather than generating every expression,
+/// so the report is ready about as fast as the puzzle is ruled out.
+pub fn unsolvable_report(
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<Option<UnsolvableReport>, CalcError> {
+    let config = &config.resolved();
+    config.validate(inputs)?;
+
+    let (closest, operations_tried) = closest_values(inputs, config, CLOSEST_VALUES);
+    if closest.iter().any(|expr| expr.value() == config.target) {
+        return Ok(None);
+    }
+
+    let closest = closest
+        .into_iter()
+        .map(|expr| {
+            // Display the example the same way as a solution would be
+            let mut expression = canonicalize(&expr).to_expr().unwrap_or(expr);
+            fully_shuffle_expr(&mut expression);
+
+            NearMiss {
+                value: expression.evaluate(),
+                text: expression.to_text(),
+                expression,
+            }
+        })
+        .collect();
+
+    Ok(Some(UnsolvableReport {
+        operations_tried,
+        closest,
+        exhausted_operations: operation_subsets(&config.operations),
+    }))
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::parse::parse_expression;
+use crate::{solve, CalcError, SolverConfig};
+
+/// A property that every solution from the solver should have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Invariant {
+    /// The solution evaluates to the target without overflowing or dividing unevenly
+    ReachesTarget,
+    /// The solution uses each input exactly once, and nothing else
+    UsesInputs,
+    /// The solution's text parses back into an expression with the same value and canonical
+    /// form, see `Solution::round_trips`
+    Reparses,
+    /// No other solution has the same canonical form
+    Distinct,
+}
+
+impl Invariant {
+    /// The name of the invariant, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            Invariant::ReachesTarget => "reachesTarget",
+            Invariant::UsesInputs => "usesInputs",
+            Invariant::Reparses => "reparses",
+            Invariant::Distinct => "distinct",
+        }
+    }
+}
+
+/// A solution that broke one of the invariants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The invariant that was broken
+    pub invariant: Invariant,
+    /// The solution's text
+    pub text: String,
+    /// What went wrong, e.g. "evaluates to 9"
+    pub detail: String,
+}
+
+/// The result of `self_test`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// How many solutions were checked
+    pub solutions_checked: usize,
+    /// Every broken invariant, in the order of the solutions
+    pub violations: Vec<Violation>,
+}
+
+impl SelfTestReport {
+    /// Whether every solution kept every invariant
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Solve the inputs and check every solution against each `Invariant`, reporting the ones
+/// that break instead of panicking so the report can be shown in the browser console as well
+/// as asserted on.
+pub fn self_test(inputs: &[i32], config: &SolverConfig) -> Result<SelfTestReport, CalcError> {
+    let mut sorted_inputs = inputs.to_vec();
+    sorted_inputs.sort_unstable();
+
+    let mut solutions_checked = 0;
+    let mut violations = Vec::new();
+    // The text of the first solution with each canonical form
+    let mut seen = HashMap::new();
+
+    for solution in solve(inputs, config)? {
+        solutions_checked += 1;
+        let mut violation = |invariant: Invariant, detail: String| {
+            violations.push(Violation {
+                invariant,
+                text: solution.text.clone(),
+                detail,
+            });
+        };
+
+        match solution.expression.exact_value() {
+            Some(value) if value == config.target => {}
+            Some(value) => violation(Invariant::ReachesTarget, format!("evaluates to {}", value)),
+            None => violation(Invariant::ReachesTarget, "doesn't evaluate exactly".into()),
+        }
+
+        let mut leaves = solution.expression.leaves();
+        leaves.sort_unstable();
+        if leaves != sorted_inputs {
+            violation(Invariant::UsesInputs, format!("uses {:?}", leaves));
+        }
+
+        if !solution.round_trips() {
+            let detail = match parse_expression(&solution.text) {
+                Ok(parsed) if parsed.value() != solution.expression.value() => {
+                    format!("parses to a value of {}", parsed.value())
+                }
+                Ok(_) => "parses to a different canonical form".into(),
+                Err(err) => err.to_string(),
+            };
+            violation(Invariant::Reparses, detail);
+        }
+
+        match seen.get(&solution.canonical) {
+            Some(text) => violation(Invariant::Distinct, format!("is equivalent to {}", text)),
+            None => {
+                seen.insert(solution.canonical.clone(), solution.text.clone());
+            }
+        }
+    }
+
+    Ok(SelfTestReport {
+        solutions_checked,
+        violations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::InputLimits;
+
+    #[test]
+    fn edge_case_puzzles_keep_the_invariants() {
+        let with_target = |target| SolverConfig {
+            target,
+            ..SolverConfig::default()
+        };
+        let mut puzzles = vec![
+            // Duplicate inputs
+            (vec![1, 1, 9, 9], with_target(20)),
+            // Zeros
+            (vec![0, 0, 5, 5], with_target(10)),
+            // A negative input
+            (
+                vec![-2, 3, 4, 5],
+                SolverConfig {
+                    limits: InputLimits {
+                        min_value: -10,
+                        ..InputLimits::default()
+                    },
+                    ..with_target(10)
+                },
+            ),
+        ];
+        // A power tower, which goes through `2 ^ 40` if it's regrouped
+        if cfg!(feature = "power") {
+            puzzles.push((vec![2, 0, 40], with_target(1)));
+        }
+
+        for (inputs, config) in puzzles {
+            let report = self_test(&inputs, &config).unwrap();
+            assert!(report.solutions_checked > 0, "{:?} has no solutions", inputs);
+            assert!(report.passed(), "{:?}: {:?}", inputs, report.violations);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::ops::DerefMut;
+
+use crate::custom_rules::CustomRule;
+use crate::maths::{
+    canonical::canonicalize, nary::NaryExpr, operation::checked_apply, EvaluatedExpr, Expression,
+    Operation, OperationKind,
+};
+use crate::{parse_expression, CalcError};
+
+/// Which child of an operation to step into
+#[derive(Clone, Copy)]
+enum Branch {
+    Left,
+    Right,
+}
+
+/// Get the path to every operation in the expression, ordered so that children always come
+/// before their parents. This uses an explicit stack rather than recursion.
+fn post_order_paths(expression: &EvaluatedExpr) -> Vec<Vec<Branch>> {
+    let mut paths = Vec::new();
+    let mut stack = vec![(Vec::new(), false)];
+
+    while let Some((path, children_visited)) = stack.pop() {
+        if let Expression::Op(_) = &**node_at_path(expression, &path) {
+            if children_visited {
+                paths.push(path);
+            } else {
+                let mut left = path.clone();
+                left.push(Branch::Left);
+                let mut right = path.clone();
+                right.push(Branch::Right);
+
+                stack.push((path, true));
+                stack.push((right, false));
+                stack.push((left, false));
+            }
+        }
+    }
+
+    paths
+}
+
+fn node_at_path<'a>(mut expression: &'a EvaluatedExpr, path: &[Branch]) -> &'a EvaluatedExpr {
+    for branch in path {
+        if let Expression::Op(op) = &**expression {
+            expression = match branch {
+                Branch::Left => &op.left,
+                Branch::Right => &op.right,
+            };
+        }
+    }
+    expression
+}
+
+fn node_at_path_mut<'a>(
+    mut expression: &'a mut EvaluatedExpr,
+    path: &[Branch],
+) -> &'a mut EvaluatedExpr {
+    for branch in path {
+        let current = expression;
+        expression = match current.deref_mut() {
+            Expression::Op(op) => match branch {
+                Branch::Left => &mut op.left,
+                Branch::Right => &mut op.right,
+            },
+            Expression::Num(_) => unreachable!("Paths only step through operations"),
+        };
+    }
+    expression
+}
+
+/// A function that simplifies the expression based on criteria. This helps eliminate solutions
+/// that are too similar to each other, for example a + b is the same as b + a.
+/// This function runs a single permutation of the shuffle over every operation (children before
+/// parents), and returns a true if anything was changed.
+fn shuffle_expr_once(expression: &mut EvaluatedExpr, rules: &[RewriteRule]) -> bool {
+    let mut changed = false;
+
+    for path in post_order_paths(expression) {
+        changed |= shuffle_node(node_at_path_mut(expression, &path), rules);
+    }
+
+    changed
+}
+
+/// Which child of an operation a rewrite rule needs to be an operation itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildPattern {
+    /// Either child can be a number or an operation
+    Any,
+    /// The left child has to be an operation, e.g. `(a - x) + y`
+    LeftOp,
+    /// The right child has to be an operation, e.g. `y + (a - x)`
+    RightOp,
+}
+
+/// The shape of the operations that a rewrite rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RulePattern {
+    /// The kinds of operation that the rule applies to, or every kind if it's empty
+    pub kinds: &'static [OperationKind],
+    pub child: ChildPattern,
+}
+
+impl RulePattern {
+    /// Check if the operation has the pattern's shape
+    pub fn matches(&self, op: &Operation) -> bool {
+        let is_op = |expr: &EvaluatedExpr| matches!(&**expr, Expression::Op(_));
+        (self.kinds.is_empty() || self.kinds.contains(&op.kind))
+            && match self.child {
+                ChildPattern::Any => true,
+                ChildPattern::LeftOp => is_op(&op.left),
+                ChildPattern::RightOp => is_op(&op.right),
+            }
+    }
+}
+
+/// A rewrite of an operation that moves it towards its display form. The rule applies if the
+/// operation matches the `pattern` and the `condition` holds, and the `action` then rewrites
+/// it in place without changing its value. Rules assume that the operation's children are
+/// already shuffled, and a rule's condition and action can assume that its pattern matched.
+#[derive(Debug, Clone, Copy)]
+pub struct RewriteRule {
+    /// The name of the rule, as used in a `Rewrite`
+    pub name: &'static str,
+    pub pattern: RulePattern,
+    pub condition: fn(&Operation) -> bool,
+    pub action: fn(&mut Operation),
+}
+
+/// Rules are compared by their name and pattern, as functions can't be compared reliably
+impl PartialEq for RewriteRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.pattern == other.pattern
+    }
+}
+
+impl Eq for RewriteRule {}
+
+impl RewriteRule {
+    /// Apply the rule to the operation if it matches, and return whether it did. The
+    /// expression that the operation is in has to be re-evaluated afterwards to update its
+    /// cached complexity, see `EvaluatedExpr::re_evaluate`.
+    pub fn apply(&self, op: &mut Operation) -> bool {
+        if self.pattern.matches(op) && (self.condition)(op) {
+            (self.action)(op);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The left child of an operation that matched `ChildPattern::LeftOp`
+fn left_op(op: &Operation) -> &Operation {
+    match &*op.left {
+        Expression::Op(left_op) => left_op,
+        Expression::Num(_) => unreachable!("The pattern only matches a left operation"),
+    }
+}
+
+/// The rules that `fully_shuffle_expr` applies after sorting the chains of additions/
+/// subtractions and multiplications/divisions (see `NaryExpr::sort`), in the order they're
+/// tried on each operation. Each one is tried on the result of the ones before it.
+///
+/// Sorting the flattened chains already swaps operands, hoists inverse operations to the end
+/// of their chain and unwraps chains on the right of a subtraction or division, so the rules
+/// are only for the operations that aren't part of a chain, e.g. powers.
+pub const SHUFFLE_RULES: &[RewriteRule] = &[
+    // Compare the right element of the internal expression with the external right element
+    // Basically, compare x and y in ((a ^ x) ^ y) and swap if needed. Sorted sums and products
+    // already have their terms in this order. The new inner operation (a ^ y) has to fit, e.g.
+    // `(2 ^ 0) ^ 40` stays as it is rather than going through `2 ^ 40`.
+    RewriteRule {
+        name: "sortChain",
+        pattern: RulePattern {
+            kinds: &[],
+            child: ChildPattern::LeftOp,
+        },
+        condition: |op| {
+            let left_op = left_op(op);
+            left_op.kind == op.kind
+                && left_op.right < op.right
+                && checked_apply(op.kind, left_op.left.value(), op.right.value()).is_some()
+        },
+        action: swap_right_operands,
+    },
+];
+
+/// Swap the right operands of an operation and its left child, i.e. x and y in
+/// ((a + x) + y)
+fn swap_right_operands(op: &mut Operation) {
+    if let Expression::Op(left_op) = op.left.deref_mut() {
+        std::mem::swap(&mut left_op.right, &mut op.right);
+    }
+    op.re_evaluate();
+}
+
+/// Apply the rules to a single operation, assuming its children are already shuffled
+fn shuffle_node(expression: &mut EvaluatedExpr, rules: &[RewriteRule]) -> bool {
+    let op = if let Expression::Op(op) = expression.deref_mut() {
+        op
+    } else {
+        return false;
+    };
+
+    // Every rule is tried in turn, each on the result of the ones before it
+    let mut changed = false;
+    for rule in rules {
+        changed |= rule.apply(op);
+    }
+    changed
+}
+
+/// Sort the terms of every chain into their display order (see `NaryExpr::sort`), and return
+/// whether anything changed. The expression is left unsorted if the sorted form would overflow.
+fn sort_chains(expression: &mut EvaluatedExpr) -> bool {
+    let mut nary = NaryExpr::from_expr(expression);
+    nary.sort();
+    let sorted = match nary.to_expr() {
+        Some(sorted) => sorted,
+        None => return false,
+    };
+
+    let changed = sorted != *expression;
+    *expression = sorted;
+    changed
+}
+
+/// Shuffle an expression until fully shuffled
+pub fn fully_shuffle_expr(expression: &mut EvaluatedExpr) {
+    shuffle_with_rules(expression, SHUFFLE_RULES);
+}
+
+/// The most passes over an expression, in case its rules keep undoing each other (or the
+/// sorting of the chains), e.g. `a + b` to `b + a`. The built in rules never need more than a
+/// few.
+const MAX_PASSES: usize = 32;
+
+/// Same as `fully_shuffle_expr`, except the custom rules are applied to each operation after
+/// the built in ones. With no custom rules, this is exactly `fully_shuffle_expr`.
+pub(crate) fn shuffle_with_custom_rules(expression: &mut EvaluatedExpr, custom: &[CustomRule]) {
+    if custom.is_empty() {
+        return fully_shuffle_expr(expression);
+    }
+
+    for _ in 0..MAX_PASSES {
+        let mut changed = sort_chains(expression);
+        changed |= shuffle_expr_once(expression, SHUFFLE_RULES);
+        // Rewriting a node only changes its own subtree, so the paths to its parents still
+        // lead to operations
+        for path in post_order_paths(expression) {
+            let node = node_at_path_mut(expression, &path);
+            for rule in custom {
+                changed |= rule.apply(node);
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+    refresh(expression);
+}
+
+/// Same as `fully_shuffle_expr`, except with only the given rules, e.g. `SHUFFLE_RULES`
+/// without one of them to see what it does, or with a new rule to try out. The chains are
+/// always sorted, as that isn't a rule.
+pub fn shuffle_with_rules(expression: &mut EvaluatedExpr, rules: &[RewriteRule]) {
+    for _ in 0..MAX_PASSES {
+        let sorted = sort_chains(expression);
+        let shuffled = shuffle_expr_once(expression, rules);
+
+        if !sorted && !shuffled {
+            break;
+        }
+    }
+    refresh(expression);
+}
+
+/// Rules rewrite operations in place, which leaves the cached values and complexity of the
+/// expressions above them out of date, so they're recomputed once the shuffle is done
+fn refresh(expression: &mut EvaluatedExpr) {
+    expression.re_evaluate();
+}
+
+/// A rewrite that `fully_shuffle_expr` applied, see `normalization_trace`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    /// The name of the rule, e.g. `"sortTerms"` for sorting the terms of each chain, as in
+    /// `2 + 8 - 3 + 7` into `8 + 7 + 2 - 3`, or a rule from `SHUFFLE_RULES`
+    pub rule: &'static str,
+    /// The whole expression before the rewrite, as text
+    pub before: String,
+    /// The whole expression after the rewrite, as text
+    pub after: String,
+}
+
+/// Same as `fully_shuffle_expr`, except it records each rewrite that it applies
+fn fully_shuffle_expr_traced(expression: &mut EvaluatedExpr) -> Vec<Rewrite> {
+    let mut trace = Vec::new();
+    let mut text = expression.to_text();
+
+    for _ in 0..MAX_PASSES {
+        let mut shuffled = false;
+        if sort_chains(expression) {
+            let after = expression.to_text();
+            trace.push(Rewrite {
+                rule: "sortTerms",
+                before: std::mem::replace(&mut text, after.clone()),
+                after,
+            });
+            shuffled = true;
+        }
+        for path in post_order_paths(expression) {
+            for rule in SHUFFLE_RULES {
+                let applied = match node_at_path_mut(expression, &path).deref_mut() {
+                    Expression::Op(op) => rule.apply(op),
+                    Expression::Num(_) => false,
+                };
+                if applied {
+                    let after = expression.to_text();
+                    trace.push(Rewrite {
+                        rule: rule.name,
+                        before: std::mem::replace(&mut text, after.clone()),
+                        after,
+                    });
+                    shuffled = true;
+                }
+            }
+        }
+
+        if !shuffled {
+            break;
+        }
+    }
+    refresh(expression);
+
+    trace
+}
+
+/// Turn an expression into its display form the same way the solver does, and return every
+/// rewrite along the way with the rule's name and the whole expression before and after it,
+/// e.g. for seeing exactly how a solution got its form. Like the solver, the expression is
+/// first rebuilt from its canonical form (the `"canonicalForm"` rewrite, which is left out if
+/// it doesn't change the text) and then shuffled. The solver can still fall back to the
+/// generated expression if the shuffled one breaks a cap, which isn't traced.
+/// Fails if the expression can't be parsed.
+pub fn normalization_trace(expr: &str) -> Result<Vec<Rewrite>, CalcError> {
+    let parsed = parse_expression(expr)?;
+    let mut expression = canonicalize(&parsed)
+        .to_expr()
+        .unwrap_or_else(|| parsed.clone());
+
+    let mut trace = Vec::new();
+    let (before, after) = (parsed.to_text(), expression.to_text());
+    if before != after {
+        trace.push(Rewrite {
+            rule: "canonicalForm",
+            before,
+            after,
+        });
+    }
+    trace.extend(fully_shuffle_expr_traced(&mut expression));
+    Ok(trace)
+}
@@ -0,0 +1,990 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use log::{debug, trace};
+
+use crate::custom_rules::CustomRule;
+use crate::error::CalcError;
+use crate::generate::{Cursor, ExpressionIter, SplitStrategy};
+use crate::maths::{
+    canonical::{canonicalize, fnv_write, CanonicalExpr, FNV_OFFSET},
+    Beam, ComplexityModel, Depth, Evaluate, EvaluatedExpr, OperationKind, Pruning,
+};
+use crate::parse::parse_expression;
+use crate::profile::Profile;
+use crate::rng::Rng;
+use crate::shuffle::shuffle_with_custom_rules;
+use crate::techniques::{techniques, Technique};
+use crate::telemetry::{Candidate, CandidateOutcome, RejectionCounts, RejectionReason, Telemetry};
+use crate::validate::InputLimits;
+use crate::value_sets::reaches_target;
+
+/// Whether a solve favours finding every distinct solution or finding them quickly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SolverMode {
+    /// Leave out the solutions that a simpler solution stands in for, even though they're in
+    /// a different equivalence class, e.g. `3 ^ (2 - 1) + 7` for `3 * (2 - 1) + 7` or
+    /// `(0 - 5) / (4 - 9)` for `(5 - 0) / (9 - 4)`. This is the only mode that supports beam
+    /// search (see `SolverConfig::beam_width`).
+    Fast,
+    /// Find every distinct solution that the split strategy allows (unless they're capped),
+    /// which `audit_completeness` checks. It's slower, and `beam_width` is ignored.
+    Exhaustive,
+}
+
+// `#[default]` on enum variants isn't stable on the pinned toolchain
+#[allow(clippy::derivable_impls)]
+impl Default for SolverMode {
+    fn default() -> Self {
+        SolverMode::Fast
+    }
+}
+
+impl SolverMode {
+    /// Every mode
+    pub const ALL: &'static [SolverMode] = &[SolverMode::Fast, SolverMode::Exhaustive];
+
+    /// The name of the mode, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            SolverMode::Fast => "fast",
+            SolverMode::Exhaustive => "exhaustive",
+        }
+    }
+
+    /// Get the mode with a name, the reverse of `name`
+    pub fn from_name(name: &str) -> Option<SolverMode> {
+        SolverMode::ALL
+            .iter()
+            .copied()
+            .find(|mode| mode.name() == name)
+    }
+}
+
+/// The options for a solve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverConfig {
+    /// The number that every solution has to evaluate to
+    pub target: i32,
+    /// The operations that solutions are allowed to use
+    pub operations: Vec<OperationKind>,
+    /// The bounds that the inputs have to be within
+    pub limits: InputLimits,
+    /// The most solutions to return, or None to return all of them
+    pub max_results: Option<usize>,
+    /// If set, solutions with the same complexity are shuffled with this seed instead of being
+    /// ordered by their canonical form. The order is still the same for everyone with the same
+    /// seed, e.g. so a daily puzzle can feature a different solution each day.
+    pub shuffle_seed: Option<u64>,
+    /// Only return the solutions that use the fewest operations. Every solution uses all of
+    /// the inputs at the moment, so they all use the same number of operations, but this will
+    /// matter for modes where solutions can use fewer numbers.
+    pub fewest_operations: bool,
+    /// The complexity metric that solutions are ranked by
+    pub complexity_model: ComplexityModel,
+    /// Rank balanced solutions like `(a + b) * (c - d)` ahead of chains like `a * b + c - d`,
+    /// as younger players find two pairs easier to follow. Solutions are ordered by their
+    /// `Operation::imbalance` first and then by complexity.
+    pub prefer_balanced: bool,
+    /// A preset for an audience, which replaces `operations` and `complexity_model` and caps
+    /// the intermediate values, see `Profile`
+    pub profile: Option<Profile>,
+    /// The children's mode, which rejects solutions with an exponent above 2 or any value along
+    /// the way above `kid_safe_threshold` (either way from zero)
+    pub kid_safe: bool,
+    /// The biggest value that a kid safe solution can go through, see `kid_safe`
+    pub kid_safe_threshold: u32,
+    /// Skip every expression that goes through a value bigger than this (either way from
+    /// zero), including the final value. This guards against huge values (e.g. from powers)
+    /// and cuts down the search a lot for bigger puzzles, but it can lose solutions.
+    pub max_intermediate: Option<u32>,
+    /// Same as `max_intermediate`, except the cap is this many times the target (or this many
+    /// for a target of 0), so it scales with big Countdown style targets
+    pub relative_intermediate_cap: Option<u32>,
+    /// Only return the solutions whose canonical form is at most this deep, where a chain of
+    /// additions/subtractions or multiplications/divisions counts as one level (see
+    /// `CanonicalExpr::depth`). E.g. 2 only allows flat solutions like `a + b + c - d`.
+    pub max_depth: Option<usize>,
+    /// Only return the solutions whose canonical form uses at most this many different
+    /// operations (see `CanonicalExpr::distinct_operators`), e.g. 1 for a "purist" mode where
+    /// `2 * 5 * 1 * 1` counts but `2 * 5 + 1 - 1` doesn't
+    pub max_operator_kinds: Option<usize>,
+    /// Beam search mode, for puzzles with up to `MAX_BEAM_INPUTS` numbers where searching every
+    /// expression is infeasible. For each contiguous run of the inputs, the generator only keeps
+    /// the expressions for the this many values closest to the target (see `Beam`), so it can
+    /// miss solutions, see `is_exhaustive`. It's ignored in `SolverMode::Exhaustive`.
+    pub beam_width: Option<usize>,
+    /// How the inputs can be divided between the operands of each operation, i.e. whether
+    /// they have to stay in order, see `SplitStrategy`. Using them in any order only supports
+    /// up to `MAX_PARTITION_INPUTS` numbers.
+    pub split_strategy: SplitStrategy,
+    /// Whether to find every distinct solution or to find them quickly, see `SolverMode`. Each
+    /// solution reports the mode that found it.
+    pub mode: SolverMode,
+    /// Extra normalization rules that are applied on top of the built in `SHUFFLE_RULES`,
+    /// e.g. a `TemplateRule` from `a * a` to `a ^ 2` for a variant that has both. They're
+    /// applied before solutions are deduplicated too, so solutions that a rule rewrites into
+    /// the same form count as one. A rule that would change an expression's value is skipped.
+    pub rewrite_rules: Vec<CustomRule>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            target: 10,
+            operations: OperationKind::ALL.to_vec(),
+            limits: InputLimits::default(),
+            max_results: None,
+            shuffle_seed: None,
+            fewest_operations: false,
+            complexity_model: ComplexityModel::default(),
+            prefer_balanced: false,
+            profile: None,
+            kid_safe: false,
+            kid_safe_threshold: 100,
+            max_intermediate: None,
+            relative_intermediate_cap: None,
+            max_depth: None,
+            max_operator_kinds: None,
+            beam_width: None,
+            split_strategy: SplitStrategy::default(),
+            mode: SolverMode::default(),
+            rewrite_rules: Vec::new(),
+        }
+    }
+}
+
+impl SolverConfig {
+    /// The config with the profile's operations and complexity model in place of its own, if
+    /// there's a profile
+    pub fn resolved(&self) -> SolverConfig {
+        match self.profile {
+            Some(profile) => SolverConfig {
+                operations: profile.operations(),
+                complexity_model: profile.complexity_model(),
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// A 64-bit hash of every option, which is stable in the same way as
+    /// `CanonicalExpr::fingerprint`. Equal configs always have the same fingerprint.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        let write_option = |hash: &mut u64, value: Option<u64>| match value {
+            Some(value) => {
+                fnv_write(hash, &[1]);
+                fnv_write(hash, &value.to_le_bytes());
+            }
+            None => fnv_write(hash, &[0]),
+        };
+
+        fnv_write(&mut hash, &self.target.to_le_bytes());
+        fnv_write(&mut hash, &(self.operations.len() as u32).to_le_bytes());
+        for op in &self.operations {
+            fnv_write(&mut hash, &(op.symbol() as u32).to_le_bytes());
+        }
+        for value in [self.limits.min_inputs, self.limits.max_inputs] {
+            fnv_write(&mut hash, &(value as u64).to_le_bytes());
+        }
+        for value in [self.limits.min_value, self.limits.max_value] {
+            fnv_write(&mut hash, &value.to_le_bytes());
+        }
+        write_option(&mut hash, self.max_results.map(|max| max as u64));
+        write_option(&mut hash, self.shuffle_seed);
+        fnv_write(
+            &mut hash,
+            &[
+                self.fewest_operations as u8,
+                match self.complexity_model {
+                    ComplexityModel::V1 => 1,
+                    ComplexityModel::V2 => 2,
+                },
+                self.prefer_balanced as u8,
+                self.kid_safe as u8,
+            ],
+        );
+        let profile = self.profile.map_or("", Profile::name);
+        fnv_write(&mut hash, &(profile.len() as u32).to_le_bytes());
+        fnv_write(&mut hash, profile.as_bytes());
+        fnv_write(&mut hash, &self.kid_safe_threshold.to_le_bytes());
+        write_option(&mut hash, self.max_intermediate.map(u64::from));
+        write_option(&mut hash, self.relative_intermediate_cap.map(u64::from));
+        write_option(&mut hash, self.max_depth.map(|max| max as u64));
+        write_option(&mut hash, self.max_operator_kinds.map(|max| max as u64));
+        // Only written when they're set, so the other configs keep their fingerprints. The
+        // width is tagged, so that a width of 0 can't look like the options after it.
+        if let Some(width) = self.beam_width {
+            fnv_write(&mut hash, b"beam_width");
+            fnv_write(&mut hash, &(width as u64).to_le_bytes());
+        }
+        if self.split_strategy != SplitStrategy::Contiguous {
+            let name = self.split_strategy.name();
+            fnv_write(&mut hash, &(name.len() as u32).to_le_bytes());
+            fnv_write(&mut hash, name.as_bytes());
+        }
+        if self.mode != SolverMode::Fast {
+            let name = self.mode.name();
+            fnv_write(&mut hash, &(name.len() as u32).to_le_bytes());
+            fnv_write(&mut hash, name.as_bytes());
+        }
+        if !self.rewrite_rules.is_empty() {
+            fnv_write(&mut hash, &(self.rewrite_rules.len() as u32).to_le_bytes());
+            for rule in &self.rewrite_rules {
+                // Native rules can only be told apart by their name
+                let texts = match rule {
+                    CustomRule::Native(rule) => [rule.name, "", ""],
+                    CustomRule::Template(rule) => [rule.name(), rule.from(), rule.to()],
+                };
+                for text in texts {
+                    fnv_write(&mut hash, &(text.len() as u32).to_le_bytes());
+                    fnv_write(&mut hash, text.as_bytes());
+                }
+            }
+        }
+        hash
+    }
+
+    /// Whether solves find every solution, which they don't in beam search mode (see
+    /// `beam_width`), so the results can be flagged as possibly incomplete
+    pub fn is_exhaustive(&self) -> bool {
+        self.beam().is_none()
+    }
+
+    /// The beam for beam search mode, which only `SolverMode::Fast` supports
+    pub(crate) fn beam(&self) -> Option<Beam> {
+        match self.mode {
+            SolverMode::Fast => self.beam_width.map(|width| Beam {
+                width,
+                target: self.target,
+            }),
+            SolverMode::Exhaustive => None,
+        }
+    }
+
+    /// The pruning rules for the puzzle, with the tightest of the caps from `max_intermediate`,
+    /// `relative_intermediate_cap`, the profile and `kid_safe`. The cap is never below the
+    /// biggest input, as the inputs are part of every solution, so e.g. a puzzle with 250 in it
+    /// can still be solved with the kid safe threshold of 100. The stand ins are kept in
+    /// `SolverMode::Exhaustive`.
+    pub(crate) fn pruning(&self, inputs: &[i32], target: Option<i32>) -> Pruning {
+        let relative_max = self
+            .relative_intermediate_cap
+            .map(|factor| factor.saturating_mul(self.target.unsigned_abs().max(1)));
+        let kid_safe_max = if self.kid_safe {
+            Some(self.kid_safe_threshold)
+        } else {
+            None
+        };
+        let caps = [
+            self.max_intermediate,
+            relative_max,
+            self.profile.and_then(Profile::max_intermediate),
+            kid_safe_max,
+        ];
+
+        let biggest_input = inputs.iter().map(|n| n.unsigned_abs()).max().unwrap_or(0);
+
+        let exhaustive = self.mode == SolverMode::Exhaustive;
+        Pruning {
+            negative_results: Pruning::for_puzzle(inputs, target).negative_results && !exhaustive,
+            stand_ins: !exhaustive,
+            max_intermediate: caps
+                .iter()
+                .flatten()
+                .min()
+                .map(|&cap| cap.max(biggest_input)),
+            max_exponent: if self.kid_safe { Some(2) } else { None },
+            beam: self.beam(),
+        }
+    }
+
+    /// How solutions to the inputs are turned into their display form and filtered by shape
+    pub(crate) fn normalizer(&self, inputs: &[i32]) -> Normalizer {
+        Normalizer {
+            complexity_model: self.complexity_model,
+            pruning: self.pruning(inputs, Some(self.target)),
+            max_depth: self.max_depth,
+            max_operator_kinds: self.max_operator_kinds,
+            mode: self.mode,
+            rewrite_rules: self.rewrite_rules.as_slice().into(),
+        }
+    }
+
+    /// Generate the expressions for the inputs with the (resolved) config's operations and
+    /// pruning rules
+    pub(crate) fn expressions(&self, inputs: &[i32], target: Option<i32>) -> ExpressionIter {
+        let pruning = self.pruning(inputs, target);
+        ExpressionIter::new(
+            inputs,
+            &self.operations,
+            target,
+            pruning,
+            self.split_strategy,
+        )
+    }
+}
+
+/// A single distinct solution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// The solution, shuffled into its display form
+    pub expression: EvaluatedExpr,
+    /// The canonical form of the solution, which is the same for all equivalent solutions
+    pub canonical: CanonicalExpr,
+    /// The solution as text
+    pub text: String,
+    /// The complexity of the solution with the config's `complexity_model`
+    pub complexity: u32,
+    /// A stable hash of the canonical form, see `CanonicalExpr::fingerprint`
+    pub fingerprint: u64,
+    /// The techniques that the solution relies on, see `Technique`
+    pub techniques: Vec<Technique>,
+    /// The mode of the solve that found the solution, e.g. so a list of solutions can be
+    /// labelled as complete, see `SolverMode`
+    pub mode: SolverMode,
+}
+
+impl Solution {
+    fn new(
+        canonical: CanonicalExpr,
+        expression: EvaluatedExpr,
+        model: ComplexityModel,
+        mode: SolverMode,
+    ) -> Solution {
+        Solution {
+            text: expression.to_text(),
+            complexity: expression.complexity_with(model),
+            fingerprint: canonical.fingerprint(),
+            techniques: techniques(&expression),
+            expression,
+            canonical,
+            mode,
+        }
+    }
+
+    /// Create a solution from an expression that's already in its display form, e.g. one
+    /// parsed back from a solution's `text`. It has the default mode, as it wasn't found by a
+    /// solve.
+    pub fn from_expression(expression: EvaluatedExpr, model: ComplexityModel) -> Solution {
+        Solution::new(
+            canonicalize(&expression),
+            expression,
+            model,
+            SolverMode::default(),
+        )
+    }
+
+    /// Whether the text parses back into an expression with the same value and the same
+    /// canonical form, which catches display bugs like missing parenthesis
+    pub fn round_trips(&self) -> bool {
+        match parse_expression(&self.text) {
+            Ok(parsed) => {
+                parsed.value() == self.expression.value() && canonicalize(&parsed) == self.canonical
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// The key that the bindings cache a puzzle's solutions under, from the inputs and the config's
+/// `fingerprint`. The inputs are kept in their given order rather than sorted, as the generator
+/// only combines neighbouring inputs, so reordering the inputs can change the solutions.
+pub fn cache_key(inputs: &[i32], config: &SolverConfig) -> u64 {
+    let mut hash = FNV_OFFSET;
+    fnv_write(&mut hash, &(inputs.len() as u32).to_le_bytes());
+    for input in inputs {
+        fnv_write(&mut hash, &input.to_le_bytes());
+    }
+    fnv_write(&mut hash, &config.fingerprint().to_le_bytes());
+    hash
+}
+
+/// Find all the distinct solutions for the inputs, shuffled into their display form and sorted.
+///
+/// The solutions are ordered by complexity, simplest first. Solutions with the same complexity
+/// are ordered by their canonical form, which doesn't depend on the order that the generator
+/// happens to produce expressions in, so the output order is stable between versions. If
+/// `shuffle_seed` is set, solutions with the same complexity are shuffled with the seed instead.
+///
+/// With the `parallel` feature, the solutions are normalized on worker threads while the
+/// expressions are generated, which gives the same solutions in the same order.
+///
+/// Unsolvable puzzles are ruled out by the value sets (see `quick_solution`) before any
+/// expressions are generated, which is a lot faster than generating all of them.
+pub fn solve(
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<impl Iterator<Item = Solution>, CalcError> {
+    let resolved = config.resolved();
+    resolved.validate(inputs)?;
+    if !reaches_target(inputs, &resolved) {
+        debug!("{:?} can't reach {}", inputs, resolved.target);
+        return Ok(Vec::new().into_iter());
+    }
+
+    let solutions = solve_lazy(
+        inputs,
+        &SolverConfig {
+            max_results: None,
+            ..config.clone()
+        },
+    )?;
+
+    #[cfg(feature = "parallel")]
+    let solutions = solutions.collect_parallel();
+    #[cfg(not(feature = "parallel"))]
+    let solutions = solutions.collect();
+
+    Ok(rank_solutions(solutions, config).into_iter())
+}
+
+/// Same as `solve`, except it also counts why each candidate expression was rejected (see
+/// `SolutionIter::count_rejections`), for tuning the pruning rules. This is a debugging aid:
+/// it's slower than `solve`, as it doesn't rule out unsolvable puzzles up front or normalize
+/// on worker threads, but the solutions are the same.
+pub fn solve_with_rejections(
+    inputs: &[i32],
+    config: &SolverConfig,
+) -> Result<(Vec<Solution>, RejectionCounts), CalcError> {
+    let mut solutions = solve_lazy(
+        inputs,
+        &SolverConfig {
+            max_results: None,
+            ..config.clone()
+        },
+    )?;
+    solutions.count_rejections();
+    let found = solutions.by_ref().collect();
+    let rejections = solutions.rejections().copied().unwrap_or_default();
+
+    Ok((rank_solutions(found, config), rejections))
+}
+
+/// Put every solution for a puzzle into the order that `solve` returns them in (see `solve`),
+/// then apply `fewest_operations` and `max_results`. This is for solutions found some other
+/// way, e.g. a few at a time with `SolutionIter::step`.
+pub fn rank_solutions(solutions: Vec<Solution>, config: &SolverConfig) -> Vec<Solution> {
+    // The imbalance only counts when balanced solutions are preferred
+    let rank = |solution: &Solution| {
+        let imbalance = if config.prefer_balanced {
+            solution.expression.imbalance()
+        } else {
+            0
+        };
+        (imbalance, solution.complexity)
+    };
+
+    // Sort by imbalance and complexity, then by canonical form to make the order deterministic
+    let mut sorted: Vec<Solution> = solutions
+        .into_iter()
+        .sorted_by(|a, b| {
+            rank(a)
+                .cmp(&rank(b))
+                .then_with(|| a.canonical.cmp(&b.canonical))
+        })
+        .collect();
+
+    // Shuffle each run of equally ranked solutions. They're sorted first so that the
+    // shuffle only depends on the seed, and not on the order they were generated in.
+    if let Some(seed) = config.shuffle_seed {
+        let mut rng = Rng::new(seed);
+        let mut start = 0;
+        while start < sorted.len() {
+            let rank_of_run = rank(&sorted[start]);
+            let len = sorted[start..]
+                .iter()
+                .take_while(|solution| rank(solution) == rank_of_run)
+                .count();
+            rng.shuffle(&mut sorted[start..start + len]);
+            start += len;
+        }
+    }
+
+    if config.fewest_operations {
+        let fewest = sorted
+            .iter()
+            .map(|solution| solution.expression.operation_count())
+            .min();
+        sorted.retain(|solution| Some(solution.expression.operation_count()) == fewest);
+    }
+
+    sorted.truncate(config.max_results.unwrap_or(usize::MAX));
+    sorted
+}
+
+/// A lazy iterator over the distinct solutions for some inputs, created with `solve_lazy`.
+/// It owns all of its state, so it can be kept alive and pulled from between calls.
+pub struct SolutionIter {
+    expressions: ExpressionIter,
+    target: i32,
+    /// The fingerprints of the solutions found so far. Only the hashes are kept rather than the
+    /// canonical forms, so the memory stays small while the generator streams expressions in.
+    seen: HashSet<u64>,
+    remaining: usize,
+    checked: usize,
+    normalizer: Normalizer,
+    telemetry: Option<Telemetry>,
+    /// Why the candidates were rejected, once `count_rejections` is called
+    rejections: Option<RejectionCounts>,
+}
+
+/// Turns the expressions that make the target into solutions, with the parts of the config
+/// that it needs. It doesn't keep any state, so it can be shared between threads.
+#[derive(Debug, Clone)]
+pub(crate) struct Normalizer {
+    complexity_model: ComplexityModel,
+    pruning: Pruning,
+    max_depth: Option<usize>,
+    max_operator_kinds: Option<usize>,
+    mode: SolverMode,
+    rewrite_rules: Arc<[CustomRule]>,
+}
+
+impl Normalizer {
+    /// The canonical form that a generated expression is deduplicated by. With custom rewrite
+    /// rules, they're applied to the expression first, as they can change its canonical form.
+    pub fn canonical(&self, generated: &mut EvaluatedExpr) -> CanonicalExpr {
+        if !self.rewrite_rules.is_empty() {
+            shuffle_with_custom_rules(generated, &self.rewrite_rules);
+        }
+        canonicalize(generated)
+    }
+
+    /// Check if solutions with the canonical form are shallow enough for `max_depth`
+    pub fn within_depth(&self, canonical: &CanonicalExpr) -> bool {
+        match self.max_depth {
+            Some(max_depth) => canonical.depth() <= max_depth,
+            None => true,
+        }
+    }
+
+    /// Turn an expression into a solution in its display form, or None if it breaks
+    /// `max_operator_kinds`. The canonical form is rebuilt into an expression, so that every
+    /// solution in the same equivalence class gets displayed the same way regardless of which
+    /// one was generated first.
+    pub fn solution(&self, canonical: CanonicalExpr, generated: EvaluatedExpr) -> Option<Solution> {
+        // The operators are counted on the canonical form rather than the display form, which
+        // can fall back to the generated expression, so a whole equivalence class passes or not
+        if let Some(max_kinds) = self.max_operator_kinds {
+            if canonical.distinct_operators() > max_kinds {
+                return None;
+            }
+        }
+
+        let mut expr = canonical.to_expr().unwrap_or_else(|| generated.clone());
+
+        // Shuffle the expression into its nicest looking form
+        shuffle_with_custom_rules(&mut expr, &self.rewrite_rules);
+
+        // Regrouping can go through bigger values than the generated expression did, so
+        // keep the generated one if the display form breaks the profile or kid safe caps.
+        // Swapping operands with equal values can also land in a different equivalence class,
+        // e.g. `5 * 6 / (9 - 4)` into `(9 - 4) * 6 / 5`, and then the text wouldn't parse back
+        // into the solution that was deduplicated.
+        if !self.pruning.within_caps(&expr) || canonicalize(&expr) != canonical {
+            expr = generated;
+        }
+
+        let solution = Solution::new(canonical, expr, self.complexity_model, self.mode);
+        debug_assert!(
+            solution.round_trips(),
+            "{} doesn't parse back into the same solution",
+            solution.text
+        );
+        Some(solution)
+    }
+}
+
+impl SolutionIter {
+    /// Do a bounded amount of work, trying at most `budget` operations (see
+    /// `ExpressionIter::next_within`), and return the solutions that were found. Calling this
+    /// until `is_finished` finds the same solutions as the iterator, without any one call
+    /// taking long, e.g. so a UI thread can interleave solving with rendering.
+    pub fn step(&mut self, budget: usize) -> Vec<Solution> {
+        let mut budget = budget;
+        let mut found = Vec::new();
+        while self.remaining > 0 {
+            match self.next_expression(&mut budget) {
+                Some(expr) => found.extend(self.check(expr)),
+                None => break,
+            }
+        }
+        found
+    }
+
+    /// Call `callback` with a random `fraction` (from 0 to 1) of the candidate expressions that
+    /// are tried from now on, with their value and what happened to them, e.g. for a developer
+    /// tool that visualizes the search. Only the top operation of each candidate is tried
+    /// separately, so expressions that are pruned part way through building the generator's
+    /// tables aren't included.
+    pub fn observe(&mut self, fraction: f64, callback: impl FnMut(Candidate) + 'static) {
+        self.telemetry = Some(Telemetry::new(fraction, Box::new(callback)));
+    }
+
+    /// Count why each candidate expression is rejected from now on, for tuning the pruning
+    /// rules, see `rejections`. The same caveat as `observe` applies, that the candidates
+    /// pruned while building the generator's tables aren't counted.
+    pub fn count_rejections(&mut self) {
+        self.rejections.get_or_insert_with(RejectionCounts::default);
+    }
+
+    /// How many candidates were rejected for each reason since `count_rejections` was called,
+    /// or None if it wasn't
+    pub fn rejections(&self) -> Option<&RejectionCounts> {
+        self.rejections.as_ref()
+    }
+
+    /// Get the next expression from the generator, recording the rejected ones if observed
+    fn next_expression(&mut self, budget: &mut usize) -> Option<EvaluatedExpr> {
+        if self.telemetry.is_none() && self.rejections.is_none() {
+            return self.expressions.next_within(budget);
+        }
+
+        let (telemetry, rejections) = (&mut self.telemetry, &mut self.rejections);
+        self.expressions.next_observed(budget, |value, reason| {
+            if let Some(telemetry) = telemetry {
+                telemetry.record(value, reason.outcome());
+            }
+            if let Some(rejections) = rejections {
+                rejections.record(reason);
+            }
+        })
+    }
+
+    /// Where the generator is up to, which can be saved along with the solutions found so far
+    /// to pick up from later with `resume_lazy`
+    pub fn cursor(&self) -> Cursor {
+        self.expressions.cursor()
+    }
+
+    /// How many of the generated expressions have been checked so far, which can be saved along
+    /// with the cursor for `resume_lazy`
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    /// Check if every solution has been found (or `max_results` of them)
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0 || self.expressions.is_finished()
+    }
+
+    /// Turn a generated expression into a solution, or None if it doesn't make the target or
+    /// it's equivalent to a solution that was already found
+    fn check(&mut self, solution: EvaluatedExpr) -> Option<Solution> {
+        let value = solution.value();
+        let checked = self.check_outcome(solution);
+        if let Some(telemetry) = &mut self.telemetry {
+            let outcome = match &checked {
+                Ok(_) => CandidateOutcome::Accepted,
+                Err(reason) => reason.outcome(),
+            };
+            telemetry.record(Some(value), outcome);
+        }
+        if let (Some(rejections), Err(reason)) = (&mut self.rejections, &checked) {
+            rejections.record(*reason);
+        }
+        checked.ok()
+    }
+
+    /// Same as `check`, except with why the expression was rejected
+    fn check_outcome(&mut self, mut solution: EvaluatedExpr) -> Result<Solution, RejectionReason> {
+        self.checked += 1;
+        if solution.evaluate() != self.target {
+            return Err(RejectionReason::MissedTarget);
+        }
+
+        // Skip any duplicates based on their canonical form
+        let canonical = self.normalizer.canonical(&mut solution);
+        let fingerprint = canonical.fingerprint();
+        if self.seen.contains(&fingerprint) {
+            return Err(RejectionReason::Duplicate);
+        }
+        if !self.normalizer.within_depth(&canonical) {
+            return Err(RejectionReason::TooDeep);
+        }
+        self.seen.insert(fingerprint);
+
+        let solution = self
+            .normalizer
+            .solution(canonical, solution)
+            .ok_or(RejectionReason::TooManyOperators)?;
+        self.remaining -= 1;
+        trace!("Found solution {}", solution.text);
+        Ok(solution)
+    }
+
+    /// Find every remaining solution, normalizing them on worker threads, see
+    /// `parallel::find_solutions`. The solutions are the same as the iterator's, in the same
+    /// order.
+    #[cfg(feature = "parallel")]
+    fn collect_parallel(self) -> Vec<Solution> {
+        crate::parallel::find_solutions(self.expressions, self.target, self.normalizer)
+    }
+}
+
+impl Iterator for SolutionIter {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Solution> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let mut budget = usize::MAX;
+        while let Some(expr) = self.next_expression(&mut budget) {
+            if let Some(solution) = self.check(expr) {
+                return Some(solution);
+            }
+        }
+
+        debug!(
+            "Found {} expressions that make the target, {} of them distinct",
+            self.checked,
+            self.seen.len()
+        );
+        if let Some(rejections) = &self.rejections {
+            let counts: Vec<_> = rejections
+                .iter()
+                .filter(|(_, count)| *count > 0)
+                .map(|(reason, count)| format!("{} {}", count, reason.name()))
+                .collect();
+            debug!("Rejected candidates: {}", counts.join(", "));
+        }
+        None
+    }
+}
+
+/// Same as `solve`, except the solutions are found one at a time as the iterator is pulled,
+/// in the order that the generator produces them, which is roughly simplest first (see
+/// `ExpressionIter`) rather than sorted by complexity.
+/// `shuffle_seed` and `fewest_operations` need every solution up front, so they're ignored.
+pub fn solve_lazy(inputs: &[i32], config: &SolverConfig) -> Result<SolutionIter, CalcError> {
+    let config = &config.resolved();
+    config.validate(inputs)?;
+    debug!(
+        "Solving {:?} for {} with {:?}",
+        inputs, config.target, config.operations
+    );
+
+    Ok(SolutionIter {
+        expressions: config.expressions(inputs, Some(config.target)),
+        target: config.target,
+        seen: HashSet::new(),
+        remaining: config.max_results.unwrap_or(usize::MAX),
+        checked: 0,
+        normalizer: config.normalizer(inputs),
+        telemetry: None,
+        rejections: None,
+    })
+}
+
+/// Same as `solve_lazy`, except it picks up from a `SolutionIter::cursor` of an earlier solve of
+/// the same puzzle with the same config, where `found` are the solutions that it had already
+/// found and `checked` is its `SolutionIter::checked`. Those solutions aren't found again, which
+/// goes by their `fingerprint`, so it has to be the one from the earlier solve rather than
+/// worked out again from the text. The generator's tables aren't saved, as they're built again
+/// from the inputs. Fails with `CalcError::InvalidCursor` if the cursor doesn't fit the puzzle.
+pub fn resume_lazy(
+    inputs: &[i32],
+    config: &SolverConfig,
+    cursor: Cursor,
+    checked: usize,
+    found: &[Solution],
+) -> Result<SolutionIter, CalcError> {
+    let mut iter = solve_lazy(inputs, config)?;
+    iter.expressions.seek(cursor)?;
+    iter.seen = found.iter().map(|solution| solution.fingerprint).collect();
+    iter.checked = checked;
+    iter.remaining = iter.remaining.saturating_sub(found.len());
+    Ok(iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maths::ExpressionEquals;
+
+    /// Check that the puzzle has a solution equivalent to `expected`
+    fn assert_solves(inputs: &[i32], config: &SolverConfig, expected: &str) {
+        let expected_expr = parse_expression(expected).unwrap();
+        let solutions: Vec<_> = solve(inputs, config).unwrap().collect();
+        assert!(
+            solutions
+                .iter()
+                .any(|solution| solution.expression.expr_equals(&expected_expr)),
+            "{:?} with target {} has no solution like {}, only {:?}",
+            inputs,
+            config.target,
+            expected,
+            solutions.iter().map(|s| &s.text).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fingerprints_tell_unset_options_from_zero() {
+        let unset = SolverConfig::default().fingerprint();
+        let zeros = [
+            SolverConfig {
+                max_results: Some(0),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                max_intermediate: Some(0),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                relative_intermediate_cap: Some(0),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                max_depth: Some(0),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                max_operator_kinds: Some(0),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                beam_width: Some(0),
+                ..SolverConfig::default()
+            },
+        ];
+        for config in zeros {
+            assert_ne!(config.fingerprint(), unset, "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn resuming_checks_the_cursor() {
+        let inputs = [1, 2, 3, 4];
+        let config = SolverConfig::default();
+        let all: Vec<_> = solve_lazy(&inputs, &config)
+            .unwrap()
+            .map(|solution| solution.text)
+            .collect();
+
+        let mut iter = solve_lazy(&inputs, &config).unwrap();
+        let found = SolutionIter::step(&mut iter, 10);
+        assert!(!iter.is_finished());
+        let (cursor, checked) = (iter.cursor(), iter.checked());
+        let resumed = resume_lazy(&inputs, &config, cursor, checked, &found).unwrap();
+        assert_eq!(resumed.checked(), checked);
+        let texts: Vec<_> = found
+            .into_iter()
+            .chain(resumed)
+            .map(|solution| solution.text)
+            .collect();
+        assert_eq!(texts, all);
+
+        let foreign = [
+            Cursor {
+                split: 1000,
+                ..cursor
+            },
+            Cursor {
+                left: 1000,
+                ..cursor
+            },
+            Cursor {
+                right: 1000,
+                ..cursor
+            },
+            Cursor {
+                operation: 1000,
+                ..cursor
+            },
+            Cursor {
+                left: 1,
+                ..Cursor::default()
+            },
+        ];
+        for cursor in foreign {
+            assert_eq!(
+                resume_lazy(&inputs, &config, cursor, 0, &[]).err(),
+                Some(CalcError::InvalidCursor),
+                "{:?}",
+                cursor
+            );
+        }
+    }
+
+    #[test]
+    fn multi_digit_inputs() {
+        let config = SolverConfig {
+            target: 1600,
+            ..SolverConfig::default()
+        };
+        assert_solves(&[25, 75, 3, 7], &config, "25 + 75 * 3 * 7");
+
+        let config = SolverConfig {
+            target: 876,
+            ..SolverConfig::default()
+        };
+        assert_solves(
+            &[100, 50, 25, 10, 5, 1],
+            &config,
+            "(100 + 50 + 25) * (10 - 5) + 1",
+        );
+    }
+
+    #[test]
+    fn tight_caps_never_rule_out_the_inputs() {
+        let tight_configs = [
+            SolverConfig {
+                max_intermediate: Some(1),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                relative_intermediate_cap: Some(1),
+                ..SolverConfig::default()
+            },
+            SolverConfig {
+                kid_safe: true,
+                kid_safe_threshold: 0,
+                ..SolverConfig::default()
+            },
+        ];
+
+        // Every value along the way is at most the biggest input
+        let puzzles: [(&[i32], i32, &str); 2] = [
+            (&[25, 75, 3, 7], 7, "25 - 75 / 3 + 7"),
+            (&[100, 50, 25, 10, 5, 1], 9, "100 - 50 - 25 - 10 - 5 - 1"),
+        ];
+        for (inputs, target, expected) in puzzles {
+            let biggest_input = inputs.iter().map(|n| n.unsigned_abs()).max().unwrap();
+            for config in &tight_configs {
+                let config = SolverConfig {
+                    target,
+                    ..config.clone()
+                };
+                let pruning = config.pruning(inputs, Some(target));
+                assert_eq!(pruning.max_intermediate, Some(biggest_input));
+                assert_solves(inputs, &config, expected);
+            }
+        }
+    }
+
+    /// Rebuilding a chain puts the multiplied terms first, which can overflow even though
+    /// the generated expression never did, e.g. the `46341 * 46341` in `46341 / (139023 / 46341)`
+    #[cfg(feature = "power")]
+    #[test]
+    fn overflowing_regroupings_keep_the_generated_expression() {
+        let config = SolverConfig {
+            target: 8,
+            limits: InputLimits {
+                max_value: i32::MAX,
+                ..InputLimits::default()
+            },
+            ..SolverConfig::default()
+        };
+        for solution in solve(&[2, 46341, 715829427, 46341], &config).unwrap() {
+            assert_eq!(solution.expression.exact_value(), Some(8), "{}", solution.text);
+        }
+
+        let trace = crate::shuffle::normalization_trace("(-1) ^ (46341 / (139023 / 46341))");
+        assert!(trace.is_ok());
+    }
+}
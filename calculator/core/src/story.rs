@@ -0,0 +1,261 @@
+use crate::maths::{EvaluatedExpr, Expression, OperationKind};
+
+/// The people in a story that each keep a pile of apples, in the order they're introduced.
+/// A new person is only needed when both operands of an operation are plain numbers.
+const PEOPLE: [&str; 6] = [
+    "you",
+    "your friend",
+    "your teacher",
+    "your brother",
+    "your sister",
+    "your neighbour",
+];
+
+/// A person in the story, who is "you" or someone else (who takes the third person verbs)
+#[derive(Debug, Clone, Copy)]
+struct Person(usize);
+
+impl Person {
+    fn name(self) -> &'static str {
+        PEOPLE[self.0 % PEOPLE.len()]
+    }
+
+    /// The name at the start of a sentence
+    fn subject(self) -> String {
+        let name = self.name();
+        name[..1].to_uppercase() + &name[1..]
+    }
+
+    /// Pick the verb form for the person, e.g. `verb("have", "has")`
+    fn verb(self, you: &'static str, other: &'static str) -> &'static str {
+        if self.name() == PEOPLE[0] {
+            you
+        } else {
+            other
+        }
+    }
+}
+
+/// An operand in the story, which is either a number from the expression or the pile of
+/// apples that someone has after the earlier sentences
+#[derive(Debug, Clone, Copy)]
+enum Amount {
+    Number(i32),
+    Pile(Person),
+}
+
+/// A count of something, e.g. "1 apple" or "3 apples"
+fn count(n: i32, singular: &str, plural: &str) -> String {
+    if n == 1 {
+        format!("1 {}", singular)
+    } else {
+        format!("{} {}", n, plural)
+    }
+}
+
+fn apples(n: i32) -> String {
+    count(n, "apple", "apples")
+}
+
+/// Tell the story of an expression one operation at a time (left operands first), adding a
+/// sentence for each operation to `sentences` and returning what the expression amounts to
+fn tell(expr: &EvaluatedExpr, people: &mut usize, sentences: &mut Vec<String>) -> Amount {
+    let op = match &**expr {
+        Expression::Num(num) => return Amount::Number(*num),
+        Expression::Op(op) => op,
+    };
+
+    let left = tell(&op.left, people, sentences);
+    let right = tell(&op.right, people, sentences);
+
+    let (person, sentence) = match (left, right) {
+        (Amount::Number(a), Amount::Number(b)) => {
+            let p = Person(*people);
+            *people += 1;
+            let s = p.subject();
+            let sentence = match op.kind {
+                OperationKind::Add => format!(
+                    "{} {} {} and {} {} more.",
+                    s,
+                    p.verb("have", "has"),
+                    apples(a),
+                    p.verb("pick", "picks"),
+                    b
+                ),
+                OperationKind::Subtract => format!(
+                    "{} {} {} and {} {}.",
+                    s,
+                    p.verb("have", "has"),
+                    apples(a),
+                    p.verb("eat", "eats"),
+                    b
+                ),
+                OperationKind::Multiply => format!(
+                    "{} {} {} of {}.",
+                    s,
+                    p.verb("have", "has"),
+                    count(a, "bag", "bags"),
+                    apples(b)
+                ),
+                OperationKind::Divide => format!(
+                    "{} {} {} equally between {} and {} one share.",
+                    s,
+                    p.verb("share", "shares"),
+                    apples(a),
+                    count(b, "friend", "friends"),
+                    p.verb("keep", "keeps")
+                ),
+                #[cfg(feature = "power")]
+                OperationKind::Power => format!(
+                    "{} {} with 1 apple, and {} in a row {} {} each apple for {}.",
+                    s,
+                    p.verb("start", "starts"),
+                    count(b, "time", "times"),
+                    p.name(),
+                    p.verb("swap", "swaps"),
+                    apples(a)
+                ),
+            };
+            (p, sentence)
+        }
+        (Amount::Pile(p), Amount::Number(n)) => {
+            let s = p.subject();
+            let sentence = match op.kind {
+                OperationKind::Add => format!("{} {} {} more.", s, p.verb("get", "gets"), n),
+                OperationKind::Subtract => format!("{} {} {}.", s, p.verb("eat", "eats"), n),
+                OperationKind::Multiply => format!(
+                    "{} {} each apple for {}.",
+                    s,
+                    p.verb("swap", "swaps"),
+                    apples(n)
+                ),
+                OperationKind::Divide => format!(
+                    "{} {} them equally between {} and {} one share.",
+                    s,
+                    p.verb("share", "shares"),
+                    count(n, "friend", "friends"),
+                    p.verb("keep", "keeps")
+                ),
+                #[cfg(feature = "power")]
+                OperationKind::Power => format!(
+                    "{} {} them aside and {} with 1 apple, then {} in a row {} {} each apple \
+                     for as many as {} put aside.",
+                    s,
+                    p.verb("put", "puts"),
+                    p.verb("start", "starts"),
+                    count(n, "time", "times"),
+                    p.name(),
+                    p.verb("swap", "swaps"),
+                    p.verb("you", "they")
+                ),
+            };
+            (p, sentence)
+        }
+        (Amount::Number(n), Amount::Pile(p)) => {
+            let s = p.subject();
+            let sentence = match op.kind {
+                OperationKind::Add => format!("{} {} {} more.", s, p.verb("get", "gets"), n),
+                OperationKind::Subtract => format!(
+                    "{} {} them out of a box of {}, and {} what's left in the box.",
+                    s,
+                    p.verb("take", "takes"),
+                    apples(n),
+                    p.verb("keep", "keeps")
+                ),
+                OperationKind::Multiply => format!(
+                    "{} {} each apple for {}.",
+                    s,
+                    p.verb("swap", "swaps"),
+                    apples(n)
+                ),
+                OperationKind::Divide => format!(
+                    "{} {} a box of {} into piles that size, and {} one apple for each pile.",
+                    s,
+                    p.verb("split", "splits"),
+                    apples(n),
+                    p.verb("keep", "keeps")
+                ),
+                #[cfg(feature = "power")]
+                OperationKind::Power => format!(
+                    "{} {} them aside and {} with 1 apple, then once for each apple {} put \
+                     aside, {} {} each apple for {}.",
+                    s,
+                    p.verb("put", "puts"),
+                    p.verb("start", "starts"),
+                    p.verb("you", "they"),
+                    p.name(),
+                    p.verb("swap", "swaps"),
+                    apples(n)
+                ),
+            };
+            (p, sentence)
+        }
+        // The right pile is always introduced after the left one, so it's never "you"
+        (Amount::Pile(p), Amount::Pile(q)) => {
+            let s = p.subject();
+            let sentence = match op.kind {
+                OperationKind::Add => {
+                    format!("{} gives all of theirs to {}.", q.subject(), p.name())
+                }
+                OperationKind::Subtract => format!(
+                    "{} {} away as many as {} has.",
+                    s,
+                    p.verb("give", "gives"),
+                    q.name()
+                ),
+                OperationKind::Multiply => format!(
+                    "{} {} each apple for as many as {} has.",
+                    s,
+                    p.verb("swap", "swaps"),
+                    q.name()
+                ),
+                OperationKind::Divide => format!(
+                    "{} {} them equally between as many friends as {} has apples, and {} one \
+                     share.",
+                    s,
+                    p.verb("share", "shares"),
+                    q.name(),
+                    p.verb("keep", "keeps")
+                ),
+                #[cfg(feature = "power")]
+                OperationKind::Power => format!(
+                    "{} {} them aside and {} with 1 apple, then once for each apple that {} \
+                     has, {} {} each apple for as many as {} put aside.",
+                    s,
+                    p.verb("put", "puts"),
+                    p.verb("start", "starts"),
+                    q.name(),
+                    p.name(),
+                    p.verb("swap", "swaps"),
+                    p.verb("you", "they")
+                ),
+            };
+            (p, sentence)
+        }
+    };
+
+    sentences.push(sentence);
+    Amount::Pile(person)
+}
+
+/// Turn an expression into a short word problem about apples for younger players, e.g.
+/// `3 * 4 - 2` becomes "You have 3 bags of 4 apples. You eat 2. How many apples do you have
+/// now?". Each operation gets a sentence from a template for its operator, with a new person
+/// for each part of the expression that's worked out separately, e.g. `(3 + 2) * (4 - 2)`.
+pub fn story_problem(expr: &EvaluatedExpr) -> String {
+    let mut sentences = Vec::new();
+    let person = match tell(expr, &mut 0, &mut sentences) {
+        Amount::Pile(person) => person,
+        Amount::Number(num) => {
+            sentences.push(format!("You have {}.", apples(num)));
+            Person(0)
+        }
+    };
+
+    sentences.push(format!(
+        "How many apples {} {} have now?",
+        person.verb("do", "does"),
+        person.name()
+    ));
+    sentences.join(" ")
+}
@@ -0,0 +1,78 @@
+use crate::maths::{Evaluate, EvaluatedExpr, Expression, OperationKind};
+
+/// Any intermediate value bigger than this (either way from zero) counts as large
+const LARGE_INTERMEDIATE: i32 = 100;
+
+/// A technique that a solution relies on, so solutions can be filtered by how they're solved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Technique {
+    /// Uses the power operator
+    Power,
+    /// Divides by something other than 1 or the dividend itself
+    Division,
+    /// Uses 0 or 1 as an identity or to absorb another value, e.g. `x + 0`, `x * 1`, `x * 0`
+    /// or `x ^ 0`
+    Identity,
+    /// Has an intermediate value bigger than 100
+    LargeIntermediate,
+}
+
+impl Technique {
+    /// The name of the technique, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::Power => "power",
+            Technique::Division => "division",
+            Technique::Identity => "identity",
+            Technique::LargeIntermediate => "largeIntermediate",
+        }
+    }
+}
+
+/// Whether one of the operands is the identity of the operation (or absorbs the other operand)
+fn uses_identity(kind: OperationKind, left: i32, right: i32) -> bool {
+    match kind {
+        OperationKind::Add => left == 0 || right == 0,
+        OperationKind::Subtract => right == 0,
+        OperationKind::Multiply => left == 0 || right == 0 || left == 1 || right == 1,
+        OperationKind::Divide => left == 0 || right == 1,
+        #[cfg(feature = "power")]
+        OperationKind::Power => left == 0 || left == 1 || right == 0 || right == 1,
+    }
+}
+
+/// Find the techniques that an expression relies on, sorted and without duplicates
+pub fn techniques(expr: &EvaluatedExpr) -> Vec<Technique> {
+    let mut techniques = Vec::new();
+
+    let mut stack = vec![(expr, true)];
+    while let Some((node, is_root)) = stack.pop() {
+        let op = match &**node {
+            Expression::Op(op) => op,
+            Expression::Num(_) => continue,
+        };
+
+        let (left, right) = (op.left.evaluate(), op.right.evaluate());
+
+        if uses_identity(op.kind, left, right) {
+            techniques.push(Technique::Identity);
+        }
+        if op.kind == OperationKind::Divide && right != 1 && left != right {
+            techniques.push(Technique::Division);
+        }
+        #[cfg(feature = "power")]
+        if op.kind == OperationKind::Power {
+            techniques.push(Technique::Power);
+        }
+        if !is_root && node.evaluate().abs() > LARGE_INTERMEDIATE {
+            techniques.push(Technique::LargeIntermediate);
+        }
+
+        stack.push((&op.left, false));
+        stack.push((&op.right, false));
+    }
+
+    techniques.sort();
+    techniques.dedup();
+    techniques
+}
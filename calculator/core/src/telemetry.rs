@@ -0,0 +1,206 @@
+use crate::rng::Rng;
+
+/// What happened to a candidate expression during a solve, see `SolutionIter::observe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandidateOutcome {
+    /// An operation overflowed, divided unevenly or went past a cap on the values
+    Pruned,
+    /// It doesn't make the target
+    MissedTarget,
+    /// It's equivalent to a solution that was already found
+    Duplicate,
+    /// Its canonical form is deeper than `max_depth`
+    TooDeep,
+    /// It uses more kinds of operators than `max_operator_kinds`
+    TooManyOperators,
+    /// It's a new solution
+    Accepted,
+}
+
+impl CandidateOutcome {
+    /// Every outcome
+    pub const ALL: &'static [CandidateOutcome] = &[
+        CandidateOutcome::Pruned,
+        CandidateOutcome::MissedTarget,
+        CandidateOutcome::Duplicate,
+        CandidateOutcome::TooDeep,
+        CandidateOutcome::TooManyOperators,
+        CandidateOutcome::Accepted,
+    ];
+
+    /// The name of the outcome, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            CandidateOutcome::Pruned => "pruned",
+            CandidateOutcome::MissedTarget => "missedTarget",
+            CandidateOutcome::Duplicate => "duplicate",
+            CandidateOutcome::TooDeep => "tooDeep",
+            CandidateOutcome::TooManyOperators => "tooManyOperators",
+            CandidateOutcome::Accepted => "accepted",
+        }
+    }
+
+    /// Get the outcome with a name, the reverse of `name`
+    pub fn from_name(name: &str) -> Option<CandidateOutcome> {
+        CandidateOutcome::ALL
+            .iter()
+            .copied()
+            .find(|outcome| outcome.name() == name)
+    }
+}
+
+/// Why a candidate expression was rejected during a solve, see `solve_with_rejections`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RejectionReason {
+    /// A division with a remainder, or by zero
+    NonExactDivision,
+    /// A subtraction with a negative result, which the reversed subtraction stands in for
+    NegativeSubtraction,
+    /// A simpler operation with the same numbers stands in for it, e.g. `a * 1` for `a / 1`
+    StandIn,
+    /// A power with a negative exponent
+    NegativeExponent,
+    /// A power with an exponent above the kid safe cap
+    ExponentCap,
+    /// A value past `max_intermediate` (or the cap from a profile or `kid_safe`)
+    IntermediateCap,
+    /// An operation overflowed
+    Overflow,
+    /// It doesn't make the target
+    MissedTarget,
+    /// It's equivalent to a solution that was already found
+    Duplicate,
+    /// Its canonical form is deeper than `max_depth`
+    TooDeep,
+    /// It uses more kinds of operators than `max_operator_kinds`
+    TooManyOperators,
+}
+
+impl RejectionReason {
+    /// Every reason, in the order of `RejectionCounts`
+    pub const ALL: &'static [RejectionReason] = &[
+        RejectionReason::NonExactDivision,
+        RejectionReason::NegativeSubtraction,
+        RejectionReason::StandIn,
+        RejectionReason::NegativeExponent,
+        RejectionReason::ExponentCap,
+        RejectionReason::IntermediateCap,
+        RejectionReason::Overflow,
+        RejectionReason::MissedTarget,
+        RejectionReason::Duplicate,
+        RejectionReason::TooDeep,
+        RejectionReason::TooManyOperators,
+    ];
+
+    /// The name of the reason, as used by the bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            RejectionReason::NonExactDivision => "nonExactDivision",
+            RejectionReason::NegativeSubtraction => "negativeSubtraction",
+            RejectionReason::StandIn => "standIn",
+            RejectionReason::NegativeExponent => "negativeExponent",
+            RejectionReason::ExponentCap => "exponentCap",
+            RejectionReason::IntermediateCap => "intermediateCap",
+            RejectionReason::Overflow => "overflow",
+            RejectionReason::MissedTarget => "missedTarget",
+            RejectionReason::Duplicate => "duplicate",
+            RejectionReason::TooDeep => "tooDeep",
+            RejectionReason::TooManyOperators => "tooManyOperators",
+        }
+    }
+
+    /// Get the reason with a name, the reverse of `name`
+    pub fn from_name(name: &str) -> Option<RejectionReason> {
+        RejectionReason::ALL
+            .iter()
+            .copied()
+            .find(|reason| reason.name() == name)
+    }
+
+    /// The outcome that `SolutionIter::observe` reports for a candidate rejected for this
+    /// reason, where every reason from before the candidate had a value counts as pruned
+    pub fn outcome(self) -> CandidateOutcome {
+        match self {
+            RejectionReason::MissedTarget => CandidateOutcome::MissedTarget,
+            RejectionReason::Duplicate => CandidateOutcome::Duplicate,
+            RejectionReason::TooDeep => CandidateOutcome::TooDeep,
+            RejectionReason::TooManyOperators => CandidateOutcome::TooManyOperators,
+            _ => CandidateOutcome::Pruned,
+        }
+    }
+}
+
+/// How many candidates were rejected for each `RejectionReason`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RejectionCounts([u64; RejectionReason::ALL.len()]);
+
+impl RejectionCounts {
+    /// How many candidates were rejected for the reason
+    pub fn get(&self, reason: RejectionReason) -> u64 {
+        self.0[reason as usize]
+    }
+
+    /// How many candidates were rejected for any reason
+    pub fn total(&self) -> u64 {
+        self.0.iter().sum()
+    }
+
+    /// Every reason along with its count, in the order of `RejectionReason::ALL`
+    pub fn iter(&self) -> impl Iterator<Item = (RejectionReason, u64)> + '_ {
+        RejectionReason::ALL
+            .iter()
+            .map(move |&reason| (reason, self.get(reason)))
+    }
+
+    /// Add another solve's counts to these, e.g. to keep a running total
+    pub fn add(&mut self, other: &RejectionCounts) {
+        for (count, other) in self.0.iter_mut().zip(other.0) {
+            *count += other;
+        }
+    }
+
+    pub(crate) fn record(&mut self, reason: RejectionReason) {
+        self.0[reason as usize] += 1;
+    }
+}
+
+/// A candidate expression that was sampled during a solve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    /// The value of the expression, or None if it was pruned before it had one
+    pub value: Option<i32>,
+    pub outcome: CandidateOutcome,
+}
+
+/// Passes a random sample of the candidates to a callback. The sample is seeded, so the same
+/// solve samples the same candidates every time.
+pub(crate) struct Telemetry {
+    /// Candidates are sampled when a random u64 is below this, or always if it's None
+    threshold: Option<u64>,
+    rng: Rng,
+    callback: Box<dyn FnMut(Candidate)>,
+}
+
+impl Telemetry {
+    pub fn new(fraction: f64, callback: Box<dyn FnMut(Candidate)>) -> Telemetry {
+        Telemetry {
+            threshold: if fraction >= 1.0 {
+                None
+            } else {
+                Some((fraction.max(0.0) * u64::MAX as f64) as u64)
+            },
+            rng: Rng::new(0),
+            callback,
+        }
+    }
+
+    pub fn record(&mut self, value: Option<i32>, outcome: CandidateOutcome) {
+        let sampled = match self.threshold {
+            Some(threshold) => self.rng.next_u64() < threshold,
+            None => true,
+        };
+        if sampled {
+            (self.callback)(Candidate { value, outcome });
+        }
+    }
+}
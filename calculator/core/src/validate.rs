@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::generate::{MAX_BEAM_INPUTS, MAX_PARTITION_INPUTS, MAX_SUPPORTED_INPUTS};
+use crate::{SolverConfig, SplitStrategy};
+
+/// The bounds that an input array has to be within before it is handed to the generator.
+/// The number of expressions grows extremely quickly with the number of inputs, so without
+/// a limit a long input would just spin forever. `max_inputs` can't be raised past
+/// `MAX_SUPPORTED_INPUTS` (or `MAX_BEAM_INPUTS` in beam search mode and `MAX_PARTITION_INPUTS`
+/// with `SplitStrategy::AllPartitions`, see `SolverConfig::validate`), anything above that is
+/// treated as that value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputLimits {
+    pub min_inputs: usize,
+    pub max_inputs: usize,
+    /// The smallest value an input can have, which can be negative. Negative numbers are written
+    /// with parenthesis where the minus sign would be ambiguous, e.g. `5 - (-3)` or `(-3) ^ 2`.
+    pub min_value: i32,
+    pub max_value: i32,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        InputLimits {
+            min_inputs: 1,
+            max_inputs: 6,
+            min_value: 0,
+            max_value: 1000,
+        }
+    }
+}
+
+/// The reasons an input array can be rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    TooFewInputs {
+        min: usize,
+        found: usize,
+    },
+    TooManyInputs {
+        max: usize,
+        found: usize,
+    },
+    ValueOutOfRange {
+        index: usize,
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::TooFewInputs { min, found } => {
+                write!(f, "Expected at least {} numbers, got {}", min, found)
+            }
+            InputError::TooManyInputs { max, found } => {
+                write!(f, "Expected at most {} numbers, got {}", max, found)
+            }
+            InputError::ValueOutOfRange {
+                index,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "Number {} at position {} is outside of the range {} to {}",
+                value, index, min, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Check that the inputs are within the limits
+pub fn validate_inputs(inputs: &[i32], limits: &InputLimits) -> Result<(), InputError> {
+    validate_inputs_up_to(inputs, limits, MAX_SUPPORTED_INPUTS)
+}
+
+/// Same as `validate_inputs`, except `max_inputs` can be raised up to `supported`
+fn validate_inputs_up_to(
+    inputs: &[i32],
+    limits: &InputLimits,
+    supported: usize,
+) -> Result<(), InputError> {
+    if inputs.len() < limits.min_inputs {
+        return Err(InputError::TooFewInputs {
+            min: limits.min_inputs,
+            found: inputs.len(),
+        });
+    }
+
+    let max_inputs = limits.max_inputs.min(supported);
+    if inputs.len() > max_inputs {
+        return Err(InputError::TooManyInputs {
+            max: max_inputs,
+            found: inputs.len(),
+        });
+    }
+
+    for (index, &value) in inputs.iter().enumerate() {
+        if value < limits.min_value || value > limits.max_value {
+            return Err(InputError::ValueOutOfRange {
+                index,
+                value,
+                min: limits.min_value,
+                max: limits.max_value,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+impl SolverConfig {
+    /// Check that the inputs are within the config's limits, where `max_inputs` can be raised
+    /// up to `MAX_BEAM_INPUTS` in beam search mode, and only up to `MAX_PARTITION_INPUTS` with
+    /// `SplitStrategy::AllPartitions` otherwise
+    pub fn validate(&self, inputs: &[i32]) -> Result<(), InputError> {
+        let supported = match (self.beam(), self.split_strategy) {
+            (Some(_), _) => MAX_BEAM_INPUTS,
+            (None, SplitStrategy::AllPartitions) => MAX_PARTITION_INPUTS,
+            (None, SplitStrategy::Contiguous) => MAX_SUPPORTED_INPUTS,
+        };
+        validate_inputs_up_to(inputs, &self.limits, supported)
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use crate::generate::is_orientation_dependent;
+use crate::maths::expression::ComplexityParts;
+use crate::maths::{Beam, EvaluatedExpr, Expression, OperationKind, Pruning};
+use crate::{solve_lazy, CalcError, Solution, SolverConfig, SolverMode};
+
+/// How the cheapest expression for a value is made
+#[derive(Debug, Clone, Copy)]
+struct Source {
+    kind: OperationKind,
+    /// The parts of the inputs on the left and right of the split, see `SplitStrategy`
+    split: (u32, u32),
+    /// The values of the left and right side of the split
+    left: i32,
+    right: i32,
+    /// Whether the right side is the first operand
+    reversed: bool,
+}
+
+/// The cheapest expression for a value. Only how it's made is kept rather than the expression
+/// itself, which is only built for the solution at the end.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    complexity: u32,
+    parts: ComplexityParts,
+    /// None for a number
+    source: Option<Source>,
+}
+
+/// The values that a run of the inputs can make, smallest first, with their cheapest
+/// expressions. They're kept in order so that ties between equally cheap expressions always
+/// go the same way.
+type ValueSet = Vec<(i32, Entry)>;
+
+fn find(set: &[(i32, Entry)], value: i32) -> Option<&Entry> {
+    set.binary_search_by_key(&value, |&(value, _)| value)
+        .ok()
+        .map(|index| &set[index].1)
+}
+
+/// Combine the value sets on either side of a split with every operator in both orientations
+/// (where the orientation matters), keeping the cheapest way to make each value in `output`.
+/// Only the values that `keep` accepts are kept. Returns how many operations were tried.
+fn combine_sets(
+    left: &[(i32, Entry)],
+    right: &[(i32, Entry)],
+    split: (u32, u32),
+    config: &SolverConfig,
+    pruning: Pruning,
+    keep: impl Fn(i32) -> bool,
+    output: &mut HashMap<i32, Entry>,
+) -> usize {
+    let mut tried = 0;
+    for &(left_val, left_entry) in left {
+        for &(right_val, right_entry) in right {
+            for &kind in &config.operations {
+                let both = is_orientation_dependent(kind) && left_val != right_val;
+                for reversed in [false, true] {
+                    if reversed && !both {
+                        break;
+                    }
+                    let (first, second) = if reversed {
+                        ((right_val, right_entry), (left_val, left_entry))
+                    } else {
+                        ((left_val, left_entry), (right_val, right_entry))
+                    };
+
+                    tried += 1;
+                    let value = match Expression::pruned_value(first.0, second.0, kind, pruning) {
+                        Some(value) if keep(value) => value,
+                        _ => continue,
+                    };
+                    let parts = ComplexityParts::op(first.1.parts, second.1.parts, kind, value);
+                    let complexity = parts.complexity(config.complexity_model);
+
+                    if matches!(output.get(&value), Some(best) if best.complexity <= complexity) {
+                        continue;
+                    }
+                    let source = Source {
+                        kind,
+                        split,
+                        left: left_val,
+                        right: right_val,
+                        reversed,
+                    };
+                    output.insert(
+                        value,
+                        Entry {
+                            complexity,
+                            parts,
+                            source: Some(source),
+                        },
+                    );
+                }
+            }
+        }
+    }
+    tried
+}
+
+/// Sort the values that were made into a value set, narrowing them down to the beam's values
+/// if there is one, the same ones as the generator keeps (see `Beam`)
+fn into_value_set(values: HashMap<i32, Entry>, beam: Option<Beam>) -> ValueSet {
+    let mut set: ValueSet = values.into_iter().collect();
+    if let Some(beam) = beam {
+        set.sort_unstable_by_key(|&(value, _)| {
+            ((value as i64 - beam.target as i64).unsigned_abs(), value)
+        });
+        set.truncate(beam.width);
+    }
+    set.sort_unstable_by_key(|&(value, _)| value);
+    set
+}
+
+/// The value sets for each part of the inputs (see `SplitStrategy`), except for all of them
+struct ValueTable {
+    /// The value set for each part, indexed by the part's bitmask
+    parts: Vec<ValueSet>,
+    /// The ways to split all of the inputs between the operands of the top operation
+    splits: Vec<(u32, u32)>,
+    /// The number for a single input, which doesn't have any splits
+    single: Option<i32>,
+}
+
+impl ValueTable {
+    /// Build the table bottom up (smallest parts first), like `ExpressionTable`
+    fn build(inputs: &[i32], config: &SolverConfig, pruning: Pruning) -> ValueTable {
+        let strategy = config.split_strategy;
+        let mut parts: Vec<ValueSet> = vec![Vec::new(); 1 << inputs.len()];
+
+        for part in strategy.parts(inputs.len()) {
+            parts[part as usize] = if part.count_ones() == 1 {
+                let n = inputs[part.trailing_zeros() as usize];
+                vec![(n, ValueTable::num(n, config))]
+            } else {
+                let mut values = HashMap::new();
+                for split in strategy.splits(part) {
+                    let left = &parts[split.0 as usize];
+                    let right = &parts[split.1 as usize];
+                    combine_sets(left, right, split, config, pruning, |_| true, &mut values);
+                }
+                into_value_set(values, pruning.beam)
+            };
+        }
+
+        ValueTable {
+            parts,
+            splits: match inputs.len() {
+                0 | 1 => Vec::new(),
+                len => strategy.splits((1 << len) - 1),
+            },
+            single: match inputs {
+                [n] => Some(*n),
+                _ => None,
+            },
+        }
+    }
+
+    fn num(n: i32, config: &SolverConfig) -> Entry {
+        let parts = ComplexityParts::num(n);
+        Entry {
+            complexity: parts.complexity(config.complexity_model),
+            parts,
+            source: None,
+        }
+    }
+
+    fn get(&self, part: u32) -> &[(i32, Entry)] {
+        &self.parts[part as usize]
+    }
+
+    /// Work out the values that all of the inputs can make, keeping the ones that `keep`
+    /// accepts, along with how many operations were tried
+    fn full_run(
+        &self,
+        config: &SolverConfig,
+        pruning: Pruning,
+        keep: impl Fn(i32) -> bool,
+    ) -> (HashMap<i32, Entry>, usize) {
+        if let Some(n) = self.single {
+            let values = Some((n, ValueTable::num(n, config)));
+            return (
+                values
+                    .filter(|&(value, _)| keep(value))
+                    .into_iter()
+                    .collect(),
+                0,
+            );
+        }
+
+        let mut values = HashMap::new();
+        let mut tried = 0;
+        for &split in &self.splits {
+            let (left, right) = (self.get(split.0), self.get(split.1));
+            tried += combine_sets(left, right, split, config, pruning, &keep, &mut values);
+        }
+        (values, tried)
+    }
+
+    /// Build the expression for an entry in the table, or for all of the inputs
+    fn expression(&self, value: i32, entry: &Entry) -> EvaluatedExpr {
+        let source = match entry.source {
+            Some(source) => source,
+            None => return Expression::new_num(value),
+        };
+
+        let operand = |part, value| {
+            let entry = find(self.get(part), value).expect("operands are in the table");
+            self.expression(value, entry)
+        };
+        let left = operand(source.split.0, source.left);
+        let right = operand(source.split.1, source.right);
+        let (first, second) = if source.reversed {
+            (right, left)
+        } else {
+            (left, right)
+        };
+
+        Expression::new_op_unpruned(first, second, source.kind)
+            .expect("the value was worked out from the same operation")
+    }
+}
+
+/// Find the cheapest expression that makes the target with the classic Countdown dynamic
+/// programming approach. Each part of the inputs only keeps the cheapest way to make
+/// each value it can make, as expressions with the same value always combine into the same
+/// values, so this reaches every value that the generator does while only building the
+/// solution's expression.
+fn cheapest_solution(inputs: &[i32], config: &SolverConfig) -> Option<EvaluatedExpr> {
+    let pruning = config.pruning(inputs, Some(config.target));
+    let table = ValueTable::build(inputs, config, pruning);
+    let (solutions, _) = table.full_run(config, pruning, |value| value == config.target);
+
+    let entry = solutions.get(&config.target)?;
+    let expr = table.expression(config.target, entry);
+    debug_assert_eq!(
+        expr.complexity_with(config.complexity_model),
+        entry.complexity,
+        "the complexity of {} was worked out wrong",
+        expr.to_text()
+    );
+    Some(expr)
+}
+
+/// Find one good solution for the inputs, which is usually the simplest one, without
+/// generating every expression like `solve` does. Each part of the inputs only keeps
+/// the cheapest expression for each value it can make, which is a lot faster for bigger
+/// puzzles. The solution is in its display form like the ones from `solve`. If it breaks
+/// `max_depth` or `max_operator_kinds` then the first solution from `solve_lazy` is used
+/// instead, which can take as long as a full solve. `max_results`, `shuffle_seed` and
+/// `prefer_balanced` are ignored, and the solution is reported as found by `SolverMode::Fast`
+/// whatever the config's mode is.
+/// Fails with `CalcError::Unsolvable` if there are no solutions.
+pub fn quick_solution(inputs: &[i32], config: &SolverConfig) -> Result<Solution, CalcError> {
+    let config = &config.resolved();
+    config.validate(inputs)?;
+
+    let mut generated = cheapest_solution(inputs, config).ok_or(CalcError::Unsolvable)?;
+    let normalizer = config.normalizer(inputs);
+    let canonical = normalizer.canonical(&mut generated);
+    let solution = if normalizer.within_depth(&canonical) {
+        normalizer.solution(canonical, generated)
+    } else {
+        None
+    };
+
+    let solution = match solution {
+        Some(solution) => solution,
+        None => solve_lazy(inputs, config)?
+            .next()
+            .ok_or(CalcError::Unsolvable)?,
+    };
+    Ok(Solution {
+        mode: SolverMode::Fast,
+        ..solution
+    })
+}
+
+/// Check if the inputs have any solutions, see `quick_solution`
+pub fn is_solvable(inputs: &[i32], config: &SolverConfig) -> Result<bool, CalcError> {
+    match quick_solution(inputs, config) {
+        Ok(_) => Ok(true),
+        Err(CalcError::Unsolvable) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Check if any expression for the inputs makes the target, without generating them, so that
+/// unsolvable puzzles can be ruled out before a full solve. The config should be resolved.
+pub(crate) fn reaches_target(inputs: &[i32], config: &SolverConfig) -> bool {
+    cheapest_solution(inputs, config).is_some()
+}
+
+/// Find the `count` distinct values closest to the target (including the target itself) that
+/// the inputs can make, along with the cheapest expression for each, closest first. Also
+/// returns how many operations were tried. The config should be resolved.
+pub(crate) fn closest_values(
+    inputs: &[i32],
+    config: &SolverConfig,
+    count: usize,
+) -> (Vec<EvaluatedExpr>, usize) {
+    let pruning = config.pruning(inputs, Some(config.target));
+    let table = ValueTable::build(inputs, config, pruning);
+    let (values, tried) = table.full_run(config, pruning, |_| true);
+
+    let mut values: ValueSet = values.into_iter().collect();
+    let distance = |value: i32| (value as i64 - config.target as i64).unsigned_abs();
+    values.sort_unstable_by_key(|&(value, _)| (distance(value), value));
+    let closest = values
+        .iter()
+        .take(count)
+        .map(|(value, entry)| table.expression(*value, entry))
+        .collect();
+    (closest, tried)
+}
@@ -0,0 +1,157 @@
+//! Native Node.js bindings for the make ten solver, built with napi-rs (e.g. `napi build --release`).
+//! These let a Node backend call the solver without the startup cost of the wasm module.
+
+use make_ten_core::{
+    cache_key, featured_solution, generate_puzzle, group_equivalent, hash_canonical,
+    maths::OperationKind, rate_difficulty, CalcError, SolverConfig,
+};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+#[napi(object)]
+pub struct Solution {
+    pub text: String,
+    pub complexity: u32,
+    /// A stable hash of the solution's canonical form, as 16 hex digits
+    pub fingerprint: String,
+    /// The techniques that the solution relies on, e.g. "division" or "largeIntermediate"
+    pub techniques: Vec<String>,
+    /// The mode of the solve that found the solution, "fast" or "exhaustive"
+    pub mode: String,
+}
+
+impl From<make_ten_core::Solution> for Solution {
+    fn from(solution: make_ten_core::Solution) -> Self {
+        Solution {
+            text: solution.text,
+            complexity: solution.complexity,
+            fingerprint: format!("{:016x}", solution.fingerprint),
+            techniques: solution
+                .techniques
+                .iter()
+                .map(|technique| technique.name().to_string())
+                .collect(),
+            mode: solution.mode.name().to_string(),
+        }
+    }
+}
+
+/// Convert a solver error into a js error, with the error's kind as the `code`
+fn calc_error_to_js(err: CalcError) -> Error {
+    Error::new(Status::InvalidArg, format!("{}: {}", err.kind(), err))
+}
+
+fn make_config(target: Option<i32>, ops: Option<String>) -> Result<SolverConfig> {
+    let mut config = SolverConfig::default();
+
+    if let Some(target) = target {
+        config.target = target;
+    }
+
+    if let Some(ops) = ops {
+        config.operations = ops
+            .chars()
+            .map(|c| {
+                OperationKind::from_symbol(c)
+                    .ok_or_else(|| Error::from_reason(format!("Unknown operator: {}", c)))
+            })
+            .collect::<Result<_>>()?;
+    }
+
+    Ok(config)
+}
+
+/// Solve a puzzle, returning the solutions sorted simplest first
+#[napi]
+pub fn solve(inputs: Vec<i32>, target: Option<i32>, ops: Option<String>) -> Result<Vec<Solution>> {
+    let config = make_config(target, ops)?;
+    let solutions = make_ten_core::solve(&inputs, &config).map_err(calc_error_to_js)?;
+
+    Ok(solutions.map(Solution::from).collect())
+}
+
+/// Pick a solution to feature for the daily puzzle, leaning towards the medium complexity
+/// ones, or null if it's unsolvable. The same seed always picks the same solution.
+#[napi(js_name = "featuredSolution")]
+pub fn pick_featured_solution(
+    inputs: Vec<i32>,
+    seed: u32,
+    target: Option<i32>,
+    ops: Option<String>,
+) -> Result<Option<Solution>> {
+    let config = make_config(target, ops)?;
+    match featured_solution(&inputs, &config, seed as u64) {
+        Ok(solution) => Ok(Some(solution.into())),
+        Err(CalcError::Unsolvable) => Ok(None),
+        Err(err) => Err(calc_error_to_js(err)),
+    }
+}
+
+/// Group expressions that are equivalent (e.g. `1 + 2 * 3` and `3 * 2 + 1`), to merge the
+/// answers that different players submitted. Returns the groups in the order of their first
+/// expression, with each group's expressions in their original order.
+#[napi]
+pub fn dedup(expressions: Vec<String>) -> Result<Vec<Vec<String>>> {
+    let groups = group_equivalent(&expressions).map_err(calc_error_to_js)?;
+
+    Ok(groups
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|index| expressions[index].clone())
+                .collect()
+        })
+        .collect())
+}
+
+/// Hash an expression by its canonical form, as 16 hex digits, so equivalent expressions have
+/// the same hash. It matches a solution's fingerprint and is stable between minor versions.
+#[napi(js_name = "hashCanonical")]
+pub fn hash_canonical_of(expr: String) -> Result<String> {
+    let expr = make_ten_core::parse_expression(&expr).map_err(calc_error_to_js)?;
+    Ok(format!("{:016x}", hash_canonical(&expr)))
+}
+
+/// The key that the wasm `Solver` caches a puzzle's solutions under, as 16 hex digits, so a
+/// server cache can share keys with it. The inputs' order is part of the key.
+#[napi(js_name = "cacheKey")]
+pub fn puzzle_cache_key(
+    inputs: Vec<i32>,
+    target: Option<i32>,
+    ops: Option<String>,
+) -> Result<String> {
+    let config = make_config(target, ops)?;
+    Ok(format!("{:016x}", cache_key(&inputs, &config)))
+}
+
+/// Rate a puzzle from 0 (trivial) to 100 (very hard), or null if it's unsolvable
+#[napi(js_name = "rateDifficulty")]
+pub fn rate_puzzle_difficulty(
+    inputs: Vec<i32>,
+    target: Option<i32>,
+    ops: Option<String>,
+) -> Result<Option<u32>> {
+    let config = make_config(target, ops)?;
+    match rate_difficulty(&inputs, &config) {
+        Ok(rating) => Ok(Some(rating)),
+        Err(CalcError::Unsolvable) => Ok(None),
+        Err(err) => Err(calc_error_to_js(err)),
+    }
+}
+
+/// Generate a random solvable puzzle from a seed, or null if one couldn't be found
+#[napi(js_name = "generatePuzzle")]
+pub fn generate_seeded_puzzle(
+    seed: u32,
+    count: u32,
+    target: Option<i32>,
+    ops: Option<String>,
+) -> Result<Option<Vec<i32>>> {
+    let config = make_config(target, ops)?;
+    match generate_puzzle(seed as u64, count as usize, &config) {
+        Ok(puzzle) => Ok(Some(puzzle)),
+        Err(CalcError::Unsolvable) => Ok(None),
+        Err(err) => Err(calc_error_to_js(err)),
+    }
+}
@@ -0,0 +1,530 @@
+use make_ten_core::{
+    cache_key,
+    maths::{canonical::CanonicalExpr, ComplexityModel, OperationKind},
+    parse_expression, CustomRule, InputLimits, Profile, Solution, SolverConfig, SolverMode,
+    SplitStrategy, TemplateRule,
+};
+
+/// The start of an exported cache, followed by the format version
+const MAGIC: &[u8; 4] = b"MT10";
+/// Only bumped when the format of a released version changes
+const FORMAT_VERSION: u32 = 1;
+
+/// How deeply a canonical form can be nested when it's read, which is far more than any
+/// solution needs
+const MAX_CANONICAL_DEPTH: usize = 64;
+
+/// How many puzzles the cache remembers by default
+const DEFAULT_CAPACITY: usize = 32;
+
+/// A least recently used cache of the solutions for recent puzzles.
+///
+/// Entries are keyed by `cache_key`, which is the same key that js can get for its own caches.
+/// There are only a few entries, so they're kept in a list from least to most recently used
+/// rather than in a map.
+#[derive(Debug)]
+pub struct SolutionCache {
+    capacity: usize,
+    entries: Vec<Entry>,
+}
+
+/// A cached puzzle, which keeps its inputs and config for exporting
+#[derive(Debug)]
+struct Entry {
+    key: u64,
+    inputs: Vec<i32>,
+    config: SolverConfig,
+    solutions: Vec<Solution>,
+}
+
+impl Default for SolutionCache {
+    fn default() -> Self {
+        SolutionCache {
+            capacity: DEFAULT_CAPACITY,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl SolutionCache {
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change how many puzzles are remembered (at least 1), returning how many were evicted
+    pub fn set_capacity(&mut self, capacity: usize) -> usize {
+        self.capacity = capacity.max(1);
+        self.evict()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// If the puzzle is cached, mark it as the most recently used and return true
+    pub fn touch(&mut self, inputs: &[i32], config: &SolverConfig) -> bool {
+        let key = cache_key(inputs, config);
+        let position = self.entries.iter().position(|entry| entry.key == key);
+
+        match position {
+            Some(position) => {
+                let entry = self.entries.remove(position);
+                self.entries.push(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a puzzle as the most recently used, returning how many old puzzles were evicted
+    pub fn insert(
+        &mut self,
+        inputs: Vec<i32>,
+        config: SolverConfig,
+        solutions: Vec<Solution>,
+    ) -> usize {
+        self.entries.push(Entry {
+            key: cache_key(&inputs, &config),
+            inputs,
+            config,
+            solutions,
+        });
+        self.evict()
+    }
+
+    /// The solutions of the most recently used puzzle
+    pub fn most_recent(&self) -> &[Solution] {
+        self.entries.last().map_or(&[], |entry| &entry.solutions)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Remove the least recently used puzzles until the cache is within its capacity
+    fn evict(&mut self) -> usize {
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+        excess
+    }
+}
+
+/// Reads the little endian values of an exported cache (or other exported state), failing if
+/// the data runs out
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err("The data ended early".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn len(&mut self) -> Result<usize, String> {
+        Ok(self.u32()? as usize)
+    }
+
+    /// Read a string written with `write_str`
+    pub fn str(&mut self) -> Result<&'a str, String> {
+        let len = self.len()?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| "A string wasn't valid UTF-8".to_string())
+    }
+
+    /// Read an optional number written with `write_option_len`
+    pub fn option_len(&mut self) -> Result<Option<usize>, String> {
+        Ok(match self.take(1)?[0] {
+            0 => None,
+            _ => Some(self.len()?),
+        })
+    }
+
+    /// Read inputs written with `write_inputs`
+    pub fn inputs(&mut self) -> Result<Vec<i32>, String> {
+        (0..self.len()?).map(|_| self.i32()).collect()
+    }
+
+    /// Read a config written with `write_config`
+    pub fn config(&mut self) -> Result<SolverConfig, String> {
+        let target = self.i32()?;
+        let operations = (0..self.len()?)
+            .map(|_| {
+                let symbol = self.take(1)?[0] as char;
+                OperationKind::from_symbol(symbol)
+                    .ok_or_else(|| format!("Unknown operator: {}", symbol))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let limits = InputLimits {
+            min_inputs: self.len()?,
+            max_inputs: self.len()?,
+            min_value: self.i32()?,
+            max_value: self.i32()?,
+        };
+        let max_results = self.option_len()?;
+        let shuffle_seed = match self.take(1)?[0] {
+            0 => None,
+            _ => Some(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+        };
+        let fewest_operations = self.take(1)?[0] != 0;
+        let complexity_model = match self.take(1)?[0] {
+            1 => ComplexityModel::V1,
+            2 => ComplexityModel::V2,
+            model => return Err(format!("Unknown complexity model: {}", model)),
+        };
+        let prefer_balanced = self.take(1)?[0] != 0;
+        let profile = match self.take(1)?[0] {
+            0 => None,
+            1 => Some(Profile::Grade3),
+            2 => Some(Profile::Casual),
+            3 => Some(Profile::Expert),
+            profile => return Err(format!("Unknown profile: {}", profile)),
+        };
+        let kid_safe = self.take(1)?[0] != 0;
+        let kid_safe_threshold = self.u32()?;
+        let max_intermediate = self.option_len()?.map(|max| max as u32);
+        let relative_intermediate_cap = self.option_len()?.map(|factor| factor as u32);
+        let max_depth = self.option_len()?;
+        let max_operator_kinds = self.option_len()?;
+        let beam_width = self.option_len()?;
+        let split_strategy = match self.take(1)?[0] {
+            0 => SplitStrategy::Contiguous,
+            1 => SplitStrategy::AllPartitions,
+            strategy => return Err(format!("Unknown split strategy: {}", strategy)),
+        };
+        let mode = match self.take(1)?[0] {
+            0 => SolverMode::Fast,
+            1 => SolverMode::Exhaustive,
+            mode => return Err(format!("Unknown solver mode: {}", mode)),
+        };
+        let rewrite_rules = (0..self.len()?)
+            .map(|_| {
+                let (name, from, to) = (self.str()?, self.str()?, self.str()?);
+                TemplateRule::parse(name, from, to)
+                    .map(CustomRule::Template)
+                    .map_err(|_| format!("Invalid rewrite rule: {}", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SolverConfig {
+            target,
+            operations,
+            limits,
+            max_results,
+            shuffle_seed,
+            fewest_operations,
+            complexity_model,
+            prefer_balanced,
+            profile,
+            kid_safe,
+            kid_safe_threshold,
+            max_intermediate,
+            relative_intermediate_cap,
+            max_depth,
+            max_operator_kinds,
+            beam_width,
+            split_strategy,
+            mode,
+            rewrite_rules,
+        })
+    }
+
+    /// Read a canonical form written with `write_canonical`
+    pub fn canonical(&mut self) -> Result<CanonicalExpr, String> {
+        self.canonical_within(MAX_CANONICAL_DEPTH)
+    }
+
+    /// Read a canonical form that's nested at most `depth` deep, so that bad data can't
+    /// overflow the stack
+    fn canonical_within(&mut self, depth: usize) -> Result<CanonicalExpr, String> {
+        let depth = depth
+            .checked_sub(1)
+            .ok_or_else(|| "A canonical form is nested too deeply".to_string())?;
+        let terms = |reader: &mut Self| {
+            (0..reader.len()?)
+                .map(|_| reader.canonical_within(depth))
+                .collect::<Result<Vec<_>, String>>()
+        };
+
+        Ok(match self.take(1)?[0] {
+            0 => CanonicalExpr::Num(self.i32()?),
+            1 => CanonicalExpr::Sum {
+                added: terms(self)?,
+                subtracted: terms(self)?,
+            },
+            2 => CanonicalExpr::Product {
+                multiplied: terms(self)?,
+                divided: terms(self)?,
+            },
+            #[cfg(feature = "power")]
+            3 => CanonicalExpr::Power(
+                Box::new(self.canonical_within(depth)?),
+                Box::new(self.canonical_within(depth)?),
+            ),
+            tag => return Err(format!("Unknown canonical form: {}", tag)),
+        })
+    }
+
+    /// Read solutions written with `write_solutions`, which were found with the config
+    pub fn solutions(&mut self, config: &SolverConfig) -> Result<Vec<Solution>, String> {
+        // A profile replaces the config's complexity model. The solutions were found in the
+        // config's mode, which isn't in their text.
+        let complexity_model = config.resolved().complexity_model;
+
+        (0..self.len()?)
+            .map(|_| {
+                let len = self.len()?;
+                let text = std::str::from_utf8(self.take(len)?)
+                    .map_err(|_| "A solution isn't valid text".to_string())?;
+                let expression = parse_expression(text).map_err(|err| err.to_string())?;
+                Ok(Solution {
+                    mode: config.mode,
+                    ..Solution::from_expression(expression, complexity_model)
+                })
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(crate) fn write_i32(out: &mut Vec<u8>, value: i32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(crate) fn write_str(out: &mut Vec<u8>, text: &str) {
+    write_u32(out, text.len() as u32);
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Write an optional number as a byte for whether it's set followed by the number, so that
+/// `Some(0)` isn't read back as `None`
+pub(crate) fn write_option_len(out: &mut Vec<u8>, value: Option<usize>) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            write_u32(out, value as u32);
+        }
+        None => out.push(0),
+    }
+}
+
+pub(crate) fn write_inputs(out: &mut Vec<u8>, inputs: &[i32]) {
+    write_u32(out, inputs.len() as u32);
+    for &input in inputs {
+        write_i32(out, input);
+    }
+}
+
+pub(crate) fn write_config(out: &mut Vec<u8>, config: &SolverConfig) {
+    write_i32(out, config.target);
+    write_u32(out, config.operations.len() as u32);
+    for op in &config.operations {
+        out.push(op.symbol() as u8);
+    }
+    write_u32(out, config.limits.min_inputs as u32);
+    write_u32(out, config.limits.max_inputs as u32);
+    write_i32(out, config.limits.min_value);
+    write_i32(out, config.limits.max_value);
+    write_option_len(out, config.max_results);
+    match config.shuffle_seed {
+        Some(seed) => {
+            out.push(1);
+            out.extend_from_slice(&seed.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+    out.push(config.fewest_operations as u8);
+    out.push(match config.complexity_model {
+        ComplexityModel::V1 => 1,
+        ComplexityModel::V2 => 2,
+    });
+    out.push(config.prefer_balanced as u8);
+    out.push(match config.profile {
+        None => 0,
+        Some(Profile::Grade3) => 1,
+        Some(Profile::Casual) => 2,
+        Some(Profile::Expert) => 3,
+    });
+    out.push(config.kid_safe as u8);
+    write_u32(out, config.kid_safe_threshold);
+    write_option_len(out, config.max_intermediate.map(|max| max as usize));
+    write_option_len(
+        out,
+        config
+            .relative_intermediate_cap
+            .map(|factor| factor as usize),
+    );
+    write_option_len(out, config.max_depth);
+    write_option_len(out, config.max_operator_kinds);
+    write_option_len(out, config.beam_width);
+    out.push(match config.split_strategy {
+        SplitStrategy::Contiguous => 0,
+        SplitStrategy::AllPartitions => 1,
+    });
+    out.push(match config.mode {
+        SolverMode::Fast => 0,
+        SolverMode::Exhaustive => 1,
+    });
+    // Only the template rules can be written, but js can't add any other kind
+    let templates: Vec<_> = config
+        .rewrite_rules
+        .iter()
+        .filter_map(|rule| match rule {
+            CustomRule::Template(rule) => Some(rule),
+            CustomRule::Native(_) => None,
+        })
+        .collect();
+    write_u32(out, templates.len() as u32);
+    for rule in templates {
+        write_str(out, rule.name());
+        write_str(out, rule.from());
+        write_str(out, rule.to());
+    }
+}
+
+/// Write a canonical form as a tag for its variant followed by its terms
+pub(crate) fn write_canonical(out: &mut Vec<u8>, canonical: &CanonicalExpr) {
+    let write_terms = |out: &mut Vec<u8>, terms: &[CanonicalExpr]| {
+        write_u32(out, terms.len() as u32);
+        for term in terms {
+            write_canonical(out, term);
+        }
+    };
+
+    match canonical {
+        CanonicalExpr::Num(num) => {
+            out.push(0);
+            write_i32(out, *num);
+        }
+        CanonicalExpr::Sum { added, subtracted } => {
+            out.push(1);
+            write_terms(out, added);
+            write_terms(out, subtracted);
+        }
+        CanonicalExpr::Product {
+            multiplied,
+            divided,
+        } => {
+            out.push(2);
+            write_terms(out, multiplied);
+            write_terms(out, divided);
+        }
+        #[cfg(feature = "power")]
+        CanonicalExpr::Power(base, exponent) => {
+            out.push(3);
+            write_canonical(out, base);
+            write_canonical(out, exponent);
+        }
+    }
+}
+
+/// Write the solutions as their text, and everything else about them is worked out again when
+/// they're read
+pub(crate) fn write_solutions(out: &mut Vec<u8>, solutions: &[Solution]) {
+    write_u32(out, solutions.len() as u32);
+    for solution in solutions {
+        write_u32(out, solution.text.len() as u32);
+        out.extend_from_slice(solution.text.as_bytes());
+    }
+}
+
+impl SolutionCache {
+    /// Export the cached puzzles (from least to most recently used) as bytes, so they can be
+    /// stored and imported again later. Solutions are stored as their text, and everything
+    /// else about them is worked out again on import.
+    pub fn export(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        write_u32(&mut out, FORMAT_VERSION);
+        write_u32(&mut out, self.entries.len() as u32);
+
+        for entry in &self.entries {
+            write_inputs(&mut out, &entry.inputs);
+            write_config(&mut out, &entry.config);
+            write_solutions(&mut out, &entry.solutions);
+        }
+
+        out
+    }
+
+    /// Import puzzles exported with `export`, as less recently used than the puzzles that are
+    /// already cached. Puzzles that are already cached are skipped. Returns how many puzzles
+    /// were imported, or an error if the data isn't a valid export (nothing is imported then).
+    pub fn import(&mut self, bytes: &[u8]) -> Result<usize, String> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4)? != MAGIC {
+            return Err("The data isn't an exported cache".to_string());
+        }
+        let version = reader.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported cache format version {}", version));
+        }
+
+        let mut imported = Vec::new();
+        for _ in 0..reader.len()? {
+            let inputs = reader.inputs()?;
+            let config = reader.config()?;
+            let solutions = reader.solutions(&config)?;
+
+            imported.push(Entry {
+                key: cache_key(&inputs, &config),
+                inputs,
+                config,
+                solutions,
+            });
+        }
+
+        imported.retain(|entry| !self.entries.iter().any(|e| e.key == entry.key));
+        let count = imported.len();
+
+        // The imported puzzles go before the existing ones, so they're evicted first
+        imported.append(&mut self.entries);
+        self.entries = imported;
+        self.evict();
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs_round_trip() {
+        let unset = SolverConfig::default();
+        let zeros = SolverConfig {
+            max_results: Some(0),
+            shuffle_seed: Some(0),
+            max_intermediate: Some(0),
+            relative_intermediate_cap: Some(0),
+            max_depth: Some(0),
+            max_operator_kinds: Some(0),
+            beam_width: Some(0),
+            ..SolverConfig::default()
+        };
+        for config in [unset, zeros] {
+            let mut out = Vec::new();
+            write_config(&mut out, &config);
+            assert_eq!(Reader::new(&out).config(), Ok(config));
+        }
+    }
+}
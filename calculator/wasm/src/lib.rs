@@ -0,0 +1,1930 @@
+//! The js bindings for the make ten solver, built with wasm-pack into `calculator/pkg`
+
+use make_ten_core::{
+    audit_completeness, capabilities, cluster_solutions, compare_configs, difficulty_breakdown,
+    export_solution_set, expression_metrics, expression_nodes, featured_solution, group_equivalent,
+    is_solvable, leaf_slots,
+    maths::{Complexity, ComplexityModel, Expression, OperationKind},
+    near_misses, nearest_solution, node_spans, operation_usage, parse_expression, quick_solution,
+    rank_solutions, reachable_values, resume_lazy, self_test, solutions_to_csv, solvability_grid,
+    solve, solve_lazy, solve_with_rejections, swap_children, swap_operator, BenchmarkPreset,
+    CalcError, Cursor, CustomRule, Profile, RejectionCounts, Solution, SolutionIter, SolverConfig,
+    SolverMode, SplitStrategy, TemplateRule,
+};
+#[cfg(feature = "spoken")]
+use make_ten_core::{story_problem, Phrases};
+
+use std::cell::{Cell, Ref, RefCell};
+
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "benchmark")]
+mod alloc;
+mod cache;
+mod logging;
+mod panic;
+mod transfer;
+
+use cache::SolutionCache;
+use panic::PanicContext;
+
+pub use logging::set_log_level;
+
+/// The bounds that an input array has to be within, see `make_ten_core::InputLimits`
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputLimits(make_ten_core::InputLimits);
+
+#[wasm_bindgen]
+impl InputLimits {
+    /// Create the default limits, which can then be adjusted from js
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> InputLimits {
+        InputLimits::default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_inputs(&self) -> usize {
+        self.0.min_inputs
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_min_inputs(&mut self, value: usize) {
+        self.0.min_inputs = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_inputs(&self) -> usize {
+        self.0.max_inputs
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_inputs(&mut self, value: usize) {
+        self.0.max_inputs = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_value(&self) -> i32 {
+        self.0.min_value
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_min_value(&mut self, value: i32) {
+        self.0.min_value = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_value(&self) -> i32 {
+        self.0.max_value
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_value(&mut self, value: i32) {
+        self.0.max_value = value;
+    }
+}
+
+/// Convert a byte range into a js object of `{ start, end }`
+fn span_to_js(span: &std::ops::Range<usize>) -> js_sys::Object {
+    let js_span = js_sys::Object::new();
+    js_sys::Reflect::set(&js_span, &"start".into(), &(span.start as u32).into()).unwrap();
+    js_sys::Reflect::set(&js_span, &"end".into(), &(span.end as u32).into()).unwrap();
+    js_span
+}
+
+/// Convert a solution into a js object with its `text`, `complexity`, `fingerprint` (as hex),
+/// the names of its `techniques` and the `mode` that found it
+fn solution_to_js(solution: &Solution) -> js_sys::Object {
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"text".into(), &solution.text.as_str().into()).unwrap();
+    js_sys::Reflect::set(&obj, &"complexity".into(), &solution.complexity.into()).unwrap();
+    let fingerprint = format!("{:016x}", solution.fingerprint);
+    js_sys::Reflect::set(&obj, &"fingerprint".into(), &fingerprint.into()).unwrap();
+    let techniques: js_sys::Array = solution
+        .techniques
+        .iter()
+        .map(|technique| JsValue::from(technique.name()))
+        .collect();
+    js_sys::Reflect::set(&obj, &"techniques".into(), &techniques).unwrap();
+    js_sys::Reflect::set(&obj, &"mode".into(), &solution.mode.name().into()).unwrap();
+    obj
+}
+
+/// Convert a solver error into a js `Error` object named `CalcError`, with the variant name
+/// (e.g. `"InvalidInput"`) as its `kind`, and a `span` of `{ start, end }` for parse errors
+fn calc_error_to_js(err: CalcError) -> JsValue {
+    let js_err = js_sys::Error::new(&err.to_string());
+    js_err.set_name("CalcError");
+    js_sys::Reflect::set(&js_err, &"kind".into(), &err.kind().into()).unwrap();
+
+    if let CalcError::ParseError { span, .. } = &err {
+        js_sys::Reflect::set(&js_err, &"span".into(), &span_to_js(span)).unwrap();
+    }
+
+    js_err.into()
+}
+
+/// The counters returned by `Solver.stats()`
+#[derive(Debug, Clone, Copy, Default)]
+struct SolverStats {
+    solves: u32,
+    cache_hits: u32,
+    cache_misses: u32,
+    cache_evictions: u32,
+    solutions: u32,
+    /// Why the candidates were rejected, counted in debug mode
+    rejections: RejectionCounts,
+}
+
+/// Get what the loaded wasm build supports, as an object with the compiled in `operators`
+/// (an array of symbols), the `numericBackend`, whether it supports `parallel` solving, the
+/// compiled in `renderers` (e.g. `["text", "spoken"]`) and the solver `version`
+#[wasm_bindgen]
+pub fn get_capabilities() -> js_sys::Object {
+    let capabilities = capabilities();
+
+    let operators: js_sys::Array = capabilities
+        .operations
+        .iter()
+        .map(|op| JsValue::from(op.symbol().to_string()))
+        .collect();
+
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"operators".into(), &operators).unwrap();
+    js_sys::Reflect::set(
+        &obj,
+        &"numericBackend".into(),
+        &capabilities.numeric_backend.into(),
+    )
+    .unwrap();
+    js_sys::Reflect::set(&obj, &"parallel".into(), &capabilities.parallel.into()).unwrap();
+    let renderers: js_sys::Array = capabilities
+        .renderers
+        .iter()
+        .map(|&renderer| JsValue::from(renderer))
+        .collect();
+    js_sys::Reflect::set(&obj, &"renderers".into(), &renderers).unwrap();
+    js_sys::Reflect::set(&obj, &"version".into(), &capabilities.version.into()).unwrap();
+    obj
+}
+
+/// Parse an expression (in the same format as the solutions) and get its complexity, which is
+/// the same metric that the solutions are ranked by. Throws a `CalcError` with a `span` if the
+/// expression can't be parsed.
+#[wasm_bindgen]
+pub fn complexity_of(expr: &str) -> Result<u32, JsValue> {
+    let expr = parse_expression(expr).map_err(calc_error_to_js)?;
+    Ok(expr.get_complexity())
+}
+
+/// Parse an expression (in the same format as the solutions) and measure it, returning an
+/// object with its `depth`, the `operatorCounts` keyed by symbol, the `largestValue` of any
+/// intermediate result, and the number of `parentheses` pairs. Throws a `CalcError` with a
+/// `span` if the expression can't be parsed.
+#[wasm_bindgen]
+pub fn metrics_of(expr: &str) -> Result<js_sys::Object, JsValue> {
+    let metrics = expression_metrics(&parse_expression(expr).map_err(calc_error_to_js)?);
+
+    let operator_counts = js_sys::Object::new();
+    for (op, count) in metrics.operator_counts {
+        let symbol = JsValue::from(op.symbol().to_string());
+        js_sys::Reflect::set(&operator_counts, &symbol, &(count as u32).into()).unwrap();
+    }
+
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"depth".into(), &(metrics.depth as u32).into()).unwrap();
+    js_sys::Reflect::set(&obj, &"operatorCounts".into(), &operator_counts).unwrap();
+    js_sys::Reflect::set(&obj, &"largestValue".into(), &metrics.largest_value.into()).unwrap();
+    js_sys::Reflect::set(
+        &obj,
+        &"parentheses".into(),
+        &(metrics.parentheses as u32).into(),
+    )
+    .unwrap();
+    Ok(obj)
+}
+
+/// Read a phrase table from a js object, where any of the english phrases (see
+/// `make_ten_core::Phrases`, with camelCase names) can be replaced for localization
+#[cfg(feature = "spoken")]
+fn phrases_from_js(phrases: Option<js_sys::Object>) -> Result<Phrases, JsValue> {
+    let mut table = Phrases::default();
+    let phrases = match phrases {
+        Some(phrases) => phrases,
+        None => return Ok(table),
+    };
+
+    let fields = [
+        ("add", &mut table.add),
+        ("subtract", &mut table.subtract),
+        ("multiply", &mut table.multiply),
+        ("divide", &mut table.divide),
+        ("power", &mut table.power),
+        ("first", &mut table.first),
+        ("then", &mut table.then),
+        ("last", &mut table.last),
+        ("number", &mut table.number),
+        ("plus", &mut table.plus),
+        ("minus", &mut table.minus),
+        ("times", &mut table.times),
+        ("dividedBy", &mut table.divided_by),
+        ("toThePowerOf", &mut table.to_the_power_of),
+        ("openBracket", &mut table.open_bracket),
+        ("closeBracket", &mut table.close_bracket),
+    ];
+
+    for (name, field) in fields {
+        if let Some(phrase) = js_sys::Reflect::get(&phrases, &name.into())?.as_string() {
+            *field = phrase;
+        }
+    }
+
+    if let Some(number_words) = js_sys::Reflect::get(&phrases, &"numberWords".into())?.as_bool() {
+        table.number_words = number_words;
+    }
+
+    Ok(table)
+}
+
+/// Parse an expression (in the same format as the solutions) and explain how to work it out,
+/// returning an array with a sentence for each step. `phrases` can be an object that replaces
+/// any of the phrases, e.g. `{ add: "addiere {left} und {right} und erhalte {result}" }`.
+/// Throws a `CalcError` with a `span` if the expression can't be parsed.
+#[cfg(feature = "spoken")]
+#[wasm_bindgen]
+pub fn explain(expr: &str, phrases: Option<js_sys::Object>) -> Result<js_sys::Array, JsValue> {
+    let expr = parse_expression(expr).map_err(calc_error_to_js)?;
+    let phrases = phrases_from_js(phrases)?;
+
+    Ok(make_ten_core::explain(&expr, &phrases)
+        .into_iter()
+        .map(JsValue::from)
+        .collect())
+}
+
+/// Parse two expressions (in the same format as the solutions) and get the tree edit distance
+/// between them, which is the fewest numbers/operations that have to be inserted, deleted or
+/// changed to turn one into the other. Throws a `CalcError` with a `span` if either expression
+/// can't be parsed.
+#[wasm_bindgen]
+pub fn edit_distance(a: &str, b: &str) -> Result<u32, JsValue> {
+    let a = parse_expression(a).map_err(calc_error_to_js)?;
+    let b = parse_expression(b).map_err(calc_error_to_js)?;
+    Ok(make_ten_core::edit_distance(&a, &b) as u32)
+}
+
+/// Group an array of expressions (in the same format as the solutions) into the ones that are
+/// equivalent, e.g. `1 + 2 * 3` and `3 * 2 + 1`. Returns an array of groups where each group is
+/// an array of expressions, in the order that they first appear. Throws an `Error` if the array
+/// has anything other than strings, or a `CalcError` with a `span` if an expression can't be
+/// parsed.
+#[wasm_bindgen]
+pub fn dedup(expressions: js_sys::Array) -> Result<js_sys::Array, JsValue> {
+    let texts = expressions
+        .iter()
+        .map(|value| {
+            value
+                .as_string()
+                .ok_or_else(|| JsValue::from(js_sys::Error::new("Expected an array of strings")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let groups = group_equivalent(&texts).map_err(calc_error_to_js)?;
+
+    Ok(groups
+        .into_iter()
+        .map(|group| {
+            let group: js_sys::Array = group
+                .into_iter()
+                .map(|index| JsValue::from_str(&texts[index]))
+                .collect();
+            JsValue::from(group)
+        })
+        .collect())
+}
+
+/// Parse an expression (in the same format as the solutions) and hash its canonical form, as
+/// 16 hex digits. Equivalent expressions have the same hash, which matches the `fingerprint`
+/// of a solution, and the hash stays the same between runs and minor versions. Throws a
+/// `CalcError` with a `span` if the expression can't be parsed.
+#[wasm_bindgen]
+pub fn hash_canonical(expr: &str) -> Result<String, JsValue> {
+    let expr = parse_expression(expr).map_err(calc_error_to_js)?;
+    Ok(format!("{:016x}", make_ten_core::hash_canonical(&expr)))
+}
+
+/// Parse an expression and trace how it's turned into its display form, for the dev tools
+/// page. Returns an array of `{ rule, before, after }` objects for each rewrite in the order
+/// they were applied, where `before` and `after` are the whole expression's text, starting
+/// with the `"canonicalForm"` rebuild if it changes anything. Throws a `CalcError` with a
+/// `span` if the expression can't be parsed.
+#[wasm_bindgen]
+pub fn normalization_trace(expr: &str) -> Result<js_sys::Array, JsValue> {
+    let trace = make_ten_core::normalization_trace(expr).map_err(calc_error_to_js)?;
+    Ok(trace
+        .into_iter()
+        .map(|rewrite| {
+            let obj = js_sys::Object::new();
+            let set = |key: &str, value: &str| {
+                js_sys::Reflect::set(&obj, &key.into(), &value.into()).unwrap();
+            };
+            set("rule", rewrite.rule);
+            set("before", &rewrite.before);
+            set("after", &rewrite.after);
+            JsValue::from(obj)
+        })
+        .collect())
+}
+
+/// Times with `Date.now()` and allocations from the counting allocator, which is only
+/// installed with the `benchmark` feature
+struct JsProbe;
+
+impl make_ten_core::Probe for JsProbe {
+    fn now_ms(&self) -> f64 {
+        js_sys::Date::now()
+    }
+
+    #[cfg(feature = "benchmark")]
+    fn allocations(&self) -> Option<(u64, u64)> {
+        Some(alloc::allocation_counts())
+    }
+
+    #[cfg(not(feature = "benchmark"))]
+    fn allocations(&self) -> Option<(u64, u64)> {
+        None
+    }
+}
+
+/// Solve a fixed set of puzzles (the `preset`, which is `"quick"`, `"standard"` or `"large"`)
+/// and measure each stage of solving, to compare performance between builds and devices. This
+/// blocks until it's done, which can take a while for the larger presets. Returns an object
+/// with the number of `puzzles`, `expressions` and `solutions`, the `totalMs`, and the
+/// `stages` (an array of `{ stage, timeMs, allocations, allocatedBytes }` for `"generation"`,
+/// `"dedup"`, `"shuffle"` and `"render"`). The allocation counts are `null` unless the wasm
+/// crate is built with the `benchmark` feature. Throws an `Error` if the preset is unknown.
+#[wasm_bindgen]
+pub fn benchmark(preset: &str) -> Result<js_sys::Object, JsValue> {
+    let preset = BenchmarkPreset::from_name(preset)
+        .ok_or_else(|| js_sys::Error::new(&format!("Unknown benchmark preset: {}", preset)))?;
+    let report = make_ten_core::benchmark(preset, &JsProbe);
+    let counted = cfg!(feature = "benchmark");
+
+    let stages: js_sys::Array = report
+        .stages
+        .iter()
+        .map(|stats| {
+            let obj = js_sys::Object::new();
+            let set = |key: &str, value: &JsValue| {
+                js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+            };
+            set("stage", &stats.stage.name().into());
+            set("timeMs", &stats.time_ms.into());
+            if counted {
+                set("allocations", &(stats.allocations as f64).into());
+                set("allocatedBytes", &(stats.allocated_bytes as f64).into());
+            } else {
+                set("allocations", &JsValue::NULL);
+                set("allocatedBytes", &JsValue::NULL);
+            }
+            JsValue::from(obj)
+        })
+        .collect();
+
+    let obj = js_sys::Object::new();
+    let set = |key: &str, value: &JsValue| {
+        js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+    };
+    set("preset", &report.preset.name().into());
+    set("puzzles", &(report.puzzles as u32).into());
+    set("expressions", &(report.expressions as f64).into());
+    set("solutions", &(report.solutions as u32).into());
+    set("totalMs", &report.total_ms().into());
+    set("stages", &stages);
+    Ok(obj)
+}
+
+/// Solve the puzzles in the corpus of known answers that ships with the solver and compare
+/// their solutions (in order) with the expected ones, so a build whose results have changed
+/// can be caught, e.g. from the browser console with `run_regression_suite()`. Returns an
+/// object with whether it `passed`, the number of `puzzlesChecked` and `puzzlesSkipped` (for
+/// operations that aren't compiled in), and the `diffs` (an array of `{ inputs, target,
+/// missing, unexpected, reordered }` for each puzzle whose solutions changed).
+#[wasm_bindgen]
+pub fn run_regression_suite() -> js_sys::Object {
+    let report = make_ten_core::run_regression_suite();
+    let to_array = |texts: &[String]| -> js_sys::Array {
+        texts
+            .iter()
+            .map(|text| JsValue::from(text.as_str()))
+            .collect()
+    };
+
+    let diffs: js_sys::Array = report
+        .diffs
+        .iter()
+        .map(|diff| {
+            let obj = js_sys::Object::new();
+            let set = |key: &str, value: &JsValue| {
+                js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+            };
+            let inputs: js_sys::Array = diff.inputs.iter().map(|&n| JsValue::from(n)).collect();
+            set("inputs", &inputs);
+            set("target", &diff.target.into());
+            set("missing", &to_array(&diff.missing));
+            set("unexpected", &to_array(&diff.unexpected));
+            set("reordered", &diff.reordered.into());
+            JsValue::from(obj)
+        })
+        .collect();
+
+    let obj = js_sys::Object::new();
+    let set = |key: &str, value: &JsValue| {
+        js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+    };
+    set("passed", &report.passed().into());
+    set("puzzlesChecked", &(report.puzzles_checked as u32).into());
+    set("puzzlesSkipped", &(report.puzzles_skipped as u32).into());
+    set("diffs", &diffs);
+    obj
+}
+
+/// Compare two exports from `Solver.export_solution_set`, e.g. from the last release and the
+/// current build. Returns an object with whether the diff is `empty`, whether the exports
+/// have the `samePuzzle` and `sameConfig`, the text of the `added` and `removed` solutions,
+/// the solutions that `changed` (an array of `{ fingerprint, oldText, newText, oldComplexity,
+/// newComplexity }`), the number that are `unchanged`, and the diff as `text` for reviewing.
+/// Throws a `CalcError` with a `span` if either export can't be read.
+#[wasm_bindgen]
+pub fn diff_solution_sets(old: &str, new: &str) -> Result<js_sys::Object, JsValue> {
+    let diff = make_ten_core::diff_solution_sets(old, new).map_err(calc_error_to_js)?;
+    let to_array = |texts: &[String]| -> js_sys::Array {
+        texts
+            .iter()
+            .map(|text| JsValue::from(text.as_str()))
+            .collect()
+    };
+
+    let changed: js_sys::Array = diff
+        .changed
+        .iter()
+        .map(|changed| {
+            let obj = js_sys::Object::new();
+            let set = |key: &str, value: &JsValue| {
+                js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+            };
+            set(
+                "fingerprint",
+                &format!("{:016x}", changed.fingerprint).into(),
+            );
+            set("oldText", &changed.old_text.as_str().into());
+            set("newText", &changed.new_text.as_str().into());
+            set("oldComplexity", &changed.old_complexity.into());
+            set("newComplexity", &changed.new_complexity.into());
+            JsValue::from(obj)
+        })
+        .collect();
+
+    let obj = js_sys::Object::new();
+    let set = |key: &str, value: &JsValue| {
+        js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+    };
+    set("empty", &diff.is_empty().into());
+    set("samePuzzle", &diff.same_puzzle.into());
+    set("sameConfig", &diff.same_config.into());
+    set("added", &to_array(&diff.added));
+    set("removed", &to_array(&diff.removed));
+    set("changed", &changed);
+    set("unchanged", &(diff.unchanged as u32).into());
+    set("text", &diff.to_text().into());
+    Ok(obj)
+}
+
+/// Solve the inputs with every option passed as a versioned JSON config (a string), e.g.
+/// `run_with_json([3, 7, 2, 8], JSON.stringify({ version: 1, target: 10, filters: { kidSafe:
+/// true } }))`, see `make_ten_core::config_from_json` for the options. Options that this build
+/// doesn't know are ignored, so the frontend can pass the same config to older builds. Returns
+/// an array of objects with each solution's `text`, `complexity`, `fingerprint`, `techniques`
+/// and `mode`. Throws a `CalcError` if the config can't be read or the inputs are outside of
+/// its limits.
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub fn run_with_json(inputs: &[i32], config_json: &str) -> Result<js_sys::Array, JsValue> {
+    let config = make_ten_core::config_from_json(config_json).map_err(calc_error_to_js)?;
+    let _context = PanicContext::enter(inputs, &config);
+    Ok(solve(inputs, &config)
+        .map_err(calc_error_to_js)?
+        .map(|solution| JsValue::from(solution_to_js(&solution)))
+        .collect())
+}
+
+/// Decode the solutions from `Solver.solve_encoded`, returning an array of objects with each
+/// solution's `text` and `complexity`. Throws an `Error` if the bytes aren't a valid encoding.
+#[wasm_bindgen]
+pub fn decode_solutions(bytes: &[u8]) -> Result<js_sys::Array, JsValue> {
+    let solutions = transfer::decode(bytes)
+        .ok_or_else(|| js_sys::Error::new("The bytes aren't encoded solutions"))?;
+
+    Ok(solutions
+        .into_iter()
+        .map(|(text, complexity)| {
+            let obj = js_sys::Object::new();
+            js_sys::Reflect::set(&obj, &"text".into(), &text.into()).unwrap();
+            js_sys::Reflect::set(&obj, &"complexity".into(), &complexity.into()).unwrap();
+            JsValue::from(obj)
+        })
+        .collect())
+}
+
+/// Parse an expression (in the same format as the solutions) and read it out in words for
+/// screen readers, e.g. "open bracket three plus seven close bracket times one". `phrases` can
+/// be an object that replaces any of the phrases, e.g.
+/// `{ plus: "plus", times: "mal", openBracket: "Klammer auf", numberWords: false }`.
+/// Throws a `CalcError` with a `span` if the expression can't be parsed.
+#[cfg(feature = "spoken")]
+#[wasm_bindgen]
+pub fn to_spoken_text(expr: &str, phrases: Option<js_sys::Object>) -> Result<String, JsValue> {
+    let expr = parse_expression(expr).map_err(calc_error_to_js)?;
+    Ok(expr.to_spoken_text_with(&phrases_from_js(phrases)?))
+}
+
+/// Parse an expression (in the same format as the solutions) and turn it into a short word
+/// problem about apples for the primary school mode, e.g. "You have 3 bags of 4 apples. You
+/// eat 2. How many apples do you have now?". Throws a `CalcError` with a `span` if the
+/// expression can't be parsed.
+#[cfg(feature = "spoken")]
+#[wasm_bindgen]
+pub fn to_story_problem(expr: &str) -> Result<String, JsValue> {
+    let expr = parse_expression(expr).map_err(calc_error_to_js)?;
+    Ok(story_problem(&expr))
+}
+
+/// A solver (callable from js) that holds the solve configuration, along with a cache of
+/// the solutions for the most recent puzzles (and configurations), so switching back to a
+/// recent puzzle is instant.
+///
+/// The solutions are ordered by complexity, simplest first, with ties broken by a canonical
+/// order that stays the same between versions, unless a comparator is set with
+/// `set_comparator`.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct Solver {
+    config: SolverConfig,
+    /// The cache and stats are only borrowed for the solve itself, so that `solve_stream` can
+    /// take `&self` and its callback can still call `cancel()`
+    cache: RefCell<SolutionCache>,
+    cancelled: Cell<bool>,
+    debug: bool,
+    stats: RefCell<SolverStats>,
+    comparator: Option<js_sys::Function>,
+}
+
+#[wasm_bindgen]
+impl Solver {
+    /// Create a solver with the default configuration (target 10, all operations)
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn target(&self) -> i32 {
+        self.config.target
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_target(&mut self, value: i32) {
+        self.config.target = value;
+    }
+
+    /// The symbols of the allowed operations, e.g. `"+-*/^"`
+    #[wasm_bindgen(getter)]
+    pub fn operations(&self) -> String {
+        self.config
+            .operations
+            .iter()
+            .map(|op| op.symbol())
+            .collect()
+    }
+
+    /// Set the allowed operations from their symbols, throwing if any symbol is unknown
+    #[wasm_bindgen(setter)]
+    pub fn set_operations(&mut self, value: &str) -> Result<(), JsValue> {
+        self.config.operations = value
+            .chars()
+            .map(|c| {
+                OperationKind::from_symbol(c).ok_or_else(|| {
+                    JsValue::from(js_sys::Error::new(&format!("Unknown operator: {}", c)))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// The seed that equally complex solutions are shuffled with, or undefined to order them
+    /// by their canonical form
+    #[wasm_bindgen(getter)]
+    pub fn shuffle_seed(&self) -> Option<u32> {
+        self.config.shuffle_seed.map(|seed| seed as u32)
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_shuffle_seed(&mut self, value: Option<u32>) {
+        self.config.shuffle_seed = value.map(u64::from);
+    }
+
+    /// The complexity metric that solutions are ranked by, either `"v2"` (the default) which
+    /// also counts large intermediate values, or `"v1"` which only counts the operations and
+    /// parenthesis
+    #[wasm_bindgen(getter)]
+    pub fn complexity_model(&self) -> String {
+        match self.config.complexity_model {
+            ComplexityModel::V1 => "v1".to_string(),
+            ComplexityModel::V2 => "v2".to_string(),
+        }
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_complexity_model(&mut self, value: &str) -> Result<(), JsValue> {
+        self.config.complexity_model = match value {
+            "v1" => ComplexityModel::V1,
+            "v2" => ComplexityModel::V2,
+            _ => {
+                let message = format!("Unknown complexity model: {}", value);
+                return Err(js_sys::Error::new(&message).into());
+            }
+        };
+        Ok(())
+    }
+
+    /// The audience preset (`"grade-3"`, `"casual"` or `"expert"`) that replaces the
+    /// operations and complexity model and caps the intermediate values, or undefined for none
+    #[wasm_bindgen(getter)]
+    pub fn profile(&self) -> Option<String> {
+        self.config
+            .profile
+            .map(|profile| profile.name().to_string())
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_profile(&mut self, value: Option<String>) -> Result<(), JsValue> {
+        self.config.profile = match value {
+            Some(name) => Some(
+                Profile::from_name(&name)
+                    .ok_or_else(|| js_sys::Error::new(&format!("Unknown profile: {}", name)))?,
+            ),
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Whether to only return solutions that are suitable for children, with no exponent above
+    /// 2 and no value along the way above `kid_safe_threshold` (100 by default)
+    #[wasm_bindgen(getter)]
+    pub fn kid_safe(&self) -> bool {
+        self.config.kid_safe
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_kid_safe(&mut self, value: bool) {
+        self.config.kid_safe = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kid_safe_threshold(&self) -> u32 {
+        self.config.kid_safe_threshold
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_kid_safe_threshold(&mut self, value: u32) {
+        self.config.kid_safe_threshold = value;
+    }
+
+    /// The biggest value (either way from zero) that any part of a solution can have, or
+    /// undefined for no cap. A cap makes bigger puzzles much faster to solve, but it can lose
+    /// solutions that go through big values.
+    #[wasm_bindgen(getter)]
+    pub fn max_intermediate(&self) -> Option<u32> {
+        self.config.max_intermediate
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_intermediate(&mut self, value: Option<u32>) {
+        self.config.max_intermediate = value;
+    }
+
+    /// Same as `max_intermediate`, except the cap is this many times the target, which keeps
+    /// big targets workable while still skipping runaway powers. Undefined for no cap.
+    #[wasm_bindgen(getter)]
+    pub fn relative_intermediate_cap(&self) -> Option<u32> {
+        self.config.relative_intermediate_cap
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_relative_intermediate_cap(&mut self, value: Option<u32>) {
+        self.config.relative_intermediate_cap = value;
+    }
+
+    /// Only return the solutions that are at most this deep, where a chain like `a + b - c`
+    /// counts as one level, e.g. 2 for only flat solutions. Undefined for any depth.
+    #[wasm_bindgen(getter)]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.config.max_depth
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_depth(&mut self, value: Option<usize>) {
+        self.config.max_depth = value;
+    }
+
+    /// Only return the solutions that use at most this many different operators, e.g. 1 for
+    /// a purist mode with single operator solutions. Undefined for any number.
+    #[wasm_bindgen(getter)]
+    pub fn max_operator_kinds(&self) -> Option<usize> {
+        self.config.max_operator_kinds
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_operator_kinds(&mut self, value: Option<usize>) {
+        self.config.max_operator_kinds = value;
+    }
+
+    /// Beam search mode for puzzles with up to 12 numbers (the limits' `max_inputs` has to be
+    /// raised too), where only the expressions for this many values closest to the target are
+    /// kept for each part of the inputs. It can miss solutions, see `exhaustive`. Undefined to
+    /// search every expression.
+    #[wasm_bindgen(getter)]
+    pub fn beam_width(&self) -> Option<usize> {
+        self.config.beam_width
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_beam_width(&mut self, value: Option<usize>) {
+        self.config.beam_width = value;
+    }
+
+    /// How the numbers can be divided up between the operands of each operation: either
+    /// `"contiguous"` (the default) where they stay in order, or `"allPartitions"` where they
+    /// can be used in any order like in Countdown, which finds more solutions but searches a
+    /// lot more expressions
+    #[wasm_bindgen(getter)]
+    pub fn split_strategy(&self) -> String {
+        self.config.split_strategy.name().to_string()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_split_strategy(&mut self, value: &str) -> Result<(), JsValue> {
+        self.config.split_strategy = SplitStrategy::from_name(value).ok_or_else(|| {
+            let message = format!("Unknown split strategy: {}", value);
+            JsValue::from(js_sys::Error::new(&message))
+        })?;
+        Ok(())
+    }
+
+    /// Either `"fast"` (the default), which leaves out the solutions that a simpler solution
+    /// stands in for, e.g. `(0 - 5) / (4 - 9)` for `(5 - 0) / (9 - 4)`, or `"exhaustive"`,
+    /// which finds every distinct solution (see `audit_completeness`) but is slower and
+    /// ignores the `beamWidth`. Each solution has the `mode` that found it.
+    #[wasm_bindgen(getter)]
+    pub fn mode(&self) -> String {
+        self.config.mode.name().to_string()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_mode(&mut self, value: &str) -> Result<(), JsValue> {
+        self.config.mode = SolverMode::from_name(value).ok_or_else(|| {
+            let message = format!("Unknown solver mode: {}", value);
+            JsValue::from(js_sys::Error::new(&message))
+        })?;
+        Ok(())
+    }
+
+    /// Add a normalization rule that's applied to solutions on top of the built in
+    /// `SHUFFLE_RULES`, written as a pattern and its replacement where lowercase letters stand
+    /// for any sub-expression, e.g. `addRewriteRule("square", "a * a", "a ^ 2")`. A letter
+    /// that's in the pattern twice has to match the same sub-expression both times. Rules are
+    /// applied in the order they're added, and solutions that rules rewrite into the same form
+    /// are deduplicated. Throws a parse error if either side can't be parsed or the replacement
+    /// uses a letter that isn't in the pattern.
+    pub fn add_rewrite_rule(&mut self, name: &str, from: &str, to: &str) -> Result<(), JsValue> {
+        let rule = TemplateRule::parse(name, from, to).map_err(calc_error_to_js)?;
+        self.config.rewrite_rules.push(CustomRule::Template(rule));
+        Ok(())
+    }
+
+    /// Remove every rule added with `addRewriteRule`
+    pub fn clear_rewrite_rules(&mut self) {
+        self.config.rewrite_rules.clear();
+    }
+
+    /// The rules added with `addRewriteRule`, as an array of `{ name, from, to }` objects
+    #[wasm_bindgen(getter)]
+    pub fn rewrite_rules(&self) -> js_sys::Array {
+        self.config
+            .rewrite_rules
+            .iter()
+            .filter_map(|rule| match rule {
+                CustomRule::Template(rule) => Some(rule),
+                CustomRule::Native(_) => None,
+            })
+            .map(|rule| {
+                let obj = js_sys::Object::new();
+                let set = |key: &str, value: &str| {
+                    js_sys::Reflect::set(&obj, &key.into(), &value.into()).unwrap();
+                };
+                set("name", rule.name());
+                set("from", rule.from());
+                set("to", rule.to());
+                JsValue::from(obj)
+            })
+            .collect()
+    }
+
+    /// Whether solves find every solution, which they don't with a `beam_width`, so the UI can
+    /// flag the results as possibly incomplete
+    #[wasm_bindgen(getter)]
+    pub fn exhaustive(&self) -> bool {
+        self.config.is_exhaustive()
+    }
+
+    /// Whether to rank balanced solutions like `(a + b) * (c - d)` ahead of chains, which
+    /// younger players find easier to follow
+    #[wasm_bindgen(getter)]
+    pub fn prefer_balanced(&self) -> bool {
+        self.config.prefer_balanced
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_prefer_balanced(&mut self, value: bool) {
+        self.config.prefer_balanced = value;
+    }
+
+    /// Whether to only return the solutions that use the fewest operations
+    #[wasm_bindgen(getter)]
+    pub fn fewest_operations(&self) -> bool {
+        self.config.fewest_operations
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_fewest_operations(&mut self, value: bool) {
+        self.config.fewest_operations = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_results(&self) -> Option<usize> {
+        self.config.max_results
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_results(&mut self, value: Option<usize>) {
+        self.config.max_results = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn limits(&self) -> InputLimits {
+        InputLimits(self.config.limits)
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_limits(&mut self, value: &InputLimits) {
+        self.config.limits = value.0;
+    }
+
+    /// Take an array of numbers and return an array of strings for all the possible solutions.
+    /// Throws a `CalcError` if the inputs are outside of the limits.
+    pub fn solve(&mut self, inputs: &[i32]) -> Result<js_sys::Array, JsValue> {
+        Ok(self
+            .find_solutions(inputs)?
+            .iter()
+            .map(|solution| JsValue::from_str(&solution.text))
+            .collect())
+    }
+
+    /// Same as `solve`, except the solutions are encoded into a `Uint8Array` along with their
+    /// complexity. When solving in a worker, send the array's buffer to the main thread as a
+    /// transferable (`postMessage(bytes, [bytes.buffer])`) to avoid copying, and read it there
+    /// with `decode_solutions`. Throws a `CalcError` if the inputs are outside of the limits.
+    pub fn solve_encoded(&mut self, inputs: &[i32]) -> Result<Vec<u8>, JsValue> {
+        Ok(transfer::encode(&self.find_solutions(inputs)?))
+    }
+
+    /// Same as `solve`, except each solution is an object with the solution `text`, its
+    /// `complexity`, the input `slots` that each number in the text came from (from left to right), which is
+    /// useful for highlighting the inputs, a `fingerprint` of 16 hex digits that's the
+    /// same for equivalent solutions across sessions and versions, and the `techniques` that
+    /// the solution relies on (`"power"`, `"division"`, `"identity"` or `"largeIntermediate"`)
+    /// for filtering. `spans` has the `{ start, end }` range of the text that each node of the
+    /// expression covers (in pre-order, so the first one is the whole text), for highlighting
+    /// sub-expressions on hover.
+    pub fn solve_with_slots(&mut self, inputs: &[i32]) -> Result<js_sys::Array, JsValue> {
+        let found = self.find_solutions(inputs)?;
+        let solutions = found.iter().map(|solution| {
+            let slots: js_sys::Array = leaf_slots(&solution.expression, inputs)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|slot| JsValue::from(slot as u32))
+                .collect();
+
+            let obj = solution_to_js(solution);
+            js_sys::Reflect::set(&obj, &"slots".into(), &slots).unwrap();
+            let spans: js_sys::Array = node_spans(&solution.expression)
+                .iter()
+                .map(|span| JsValue::from(span_to_js(span)))
+                .collect();
+            js_sys::Reflect::set(&obj, &"spans".into(), &spans).unwrap();
+            JsValue::from(obj)
+        });
+
+        Ok(solutions.collect())
+    }
+
+    /// Call `callback` with the text of each solution as soon as it's found, so the page can
+    /// show the simplest solutions before the search finishes. Streaming stops early, along
+    /// with the search, if the callback returns `false` or calls `cancel()`. Returns the number
+    /// of solutions passed to the callback.
+    ///
+    /// If the inputs are already cached, the cached solutions are streamed in their sorted
+    /// order. Otherwise they come out in generation order like `iter()`, which is roughly
+    /// simplest first but not sorted (and not by the comparator), and they aren't cached.
+    ///
+    /// The solver is borrowed for the whole stream, so the callback can only call the methods
+    /// that don't change it (e.g. `cancel()` or `stats()`). Setting an option from the callback
+    /// throws.
+    pub fn solve_stream(
+        &self,
+        inputs: &[i32],
+        callback: &js_sys::Function,
+    ) -> Result<u32, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        self.cancelled.set(false);
+        self.stats.borrow_mut().solves += 1;
+
+        // The texts are cloned out of the cache, so that the cache isn't borrowed while the
+        // callback runs
+        let cached = self.cache.borrow_mut().touch(inputs, &self.config);
+        let texts: Box<dyn Iterator<Item = String>> = if cached {
+            self.stats.borrow_mut().cache_hits += 1;
+            let texts: Vec<_> = self
+                .cache
+                .borrow()
+                .most_recent()
+                .iter()
+                .map(|solution| solution.text.clone())
+                .collect();
+            Box::new(texts.into_iter())
+        } else {
+            let solutions = solve_lazy(inputs, &self.config).map_err(calc_error_to_js)?;
+            Box::new(solutions.map(|solution| solution.text))
+        };
+
+        let mut sent = 0;
+        for text in texts {
+            let keep_going = callback.call1(&JsValue::NULL, &JsValue::from_str(&text))?;
+            sent += 1;
+
+            if self.cancelled.get() || keep_going == JsValue::FALSE {
+                break;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Stop the current `solve_stream` after the solution that is being handled. This takes
+    /// the solver by shared reference, so it can be called from the stream's callback.
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    /// The key that the solution cache keeps the inputs' solutions under with the current
+    /// options, as 16 hex digits, so a cache in js (e.g. in IndexedDB or on a server) can use
+    /// exactly the same key. The inputs' order is part of the key, as reordering the inputs can
+    /// change the solutions.
+    pub fn cache_key(&self, inputs: &[i32]) -> String {
+        format!("{:016x}", make_ten_core::cache_key(inputs, &self.config))
+    }
+
+    /// Debug mode, where solves count why each candidate expression was rejected (e.g.
+    /// `"nonExactDivision"` or `"duplicate"`) into `stats().rejections`, for tuning the pruning
+    /// rules. Solves are slower in debug mode, and puzzles that are already cached aren't
+    /// solved again so they don't add to the counts.
+    #[wasm_bindgen(getter)]
+    pub fn debug(&self) -> bool {
+        self.debug
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_debug(&mut self, value: bool) {
+        self.debug = value;
+    }
+
+    /// How many puzzles the solution cache remembers (32 by default, and at least 1)
+    #[wasm_bindgen(getter)]
+    pub fn cache_capacity(&self) -> usize {
+        self.cache.borrow().capacity()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_cache_capacity(&mut self, value: usize) {
+        let evicted = self.cache.get_mut().set_capacity(value);
+        self.stats.get_mut().cache_evictions += evicted as u32;
+    }
+
+    /// Get the solver's counters as an object with the number of `solves`, `cacheHits`,
+    /// `cacheMisses` and `cacheEvictions`, the current `cacheSize` (in puzzles), the total
+    /// number of `solutions` found, and the number of candidates that were `rejections` for
+    /// each reason in debug mode (an object keyed by reason, e.g. `{ nonExactDivision: 120,
+    /// negativeSubtraction: 84, overflow: 0, duplicate: 31, ... }`)
+    pub fn stats(&self) -> js_sys::Object {
+        let obj = js_sys::Object::new();
+        let set = |key: &str, value: u32| {
+            js_sys::Reflect::set(&obj, &key.into(), &value.into()).unwrap();
+        };
+        let stats = self.stats.borrow();
+        set("solves", stats.solves);
+        set("cacheHits", stats.cache_hits);
+        set("cacheMisses", stats.cache_misses);
+        set("cacheEvictions", stats.cache_evictions);
+        set("cacheSize", self.cache.borrow().len() as u32);
+        set("solutions", stats.solutions);
+
+        let rejections = js_sys::Object::new();
+        for (reason, count) in stats.rejections.iter() {
+            js_sys::Reflect::set(&rejections, &reason.name().into(), &(count as f64).into())
+                .unwrap();
+        }
+        js_sys::Reflect::set(&obj, &"rejections".into(), &rejections).unwrap();
+        obj
+    }
+
+    /// Get a `SolutionIterator` that finds the solutions one at a time as `next()` is called.
+    /// The solutions come out in generation order, which is roughly simplest first but not
+    /// sorted, and they aren't cached.
+    /// Throws a `CalcError` if the inputs are outside of the limits.
+    pub fn iter(&mut self, inputs: &[i32]) -> Result<SolutionIterator, JsValue> {
+        self.stats.get_mut().solves += 1;
+
+        let solutions = solve_lazy(inputs, &self.config).map_err(calc_error_to_js)?;
+        Ok(SolutionIterator(solutions))
+    }
+
+    /// Start a `SolverSession` that solves the inputs a little at a time with `step()`, so the
+    /// main thread can keep rendering without a worker. The session has its own copy of the
+    /// config, and its solutions aren't cached. Throws a `CalcError` if the inputs are outside
+    /// of the limits.
+    pub fn session(&mut self, inputs: &[i32]) -> Result<SolverSession, JsValue> {
+        self.stats.get_mut().solves += 1;
+
+        let config = self.config.resolved();
+        config
+            .validate(inputs)
+            .map_err(|err| calc_error_to_js(err.into()))?;
+        Ok(SolverSession {
+            inputs: inputs.to_vec(),
+            config: self.config.clone(),
+            iter: None,
+            found: Vec::new(),
+            ranked: None,
+            telemetry: None,
+        })
+    }
+
+    /// Check why the inputs have no solutions, returning `null` if they do have solutions.
+    /// Otherwise returns an object with the number of `operationsTried`, the `closest`
+    /// values that were reached (an array of `{ value, text }`, closest first), and the
+    /// `exhaustedOperations` (an array of the operator subsets, e.g. `["+", "+-"]`) that
+    /// can't reach the target either. Throws a `CalcError` if the inputs are outside of the limits.
+    pub fn unsolvable_report(&self, inputs: &[i32]) -> Result<JsValue, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let report = match make_ten_core::unsolvable_report(inputs, &self.config)
+            .map_err(calc_error_to_js)?
+        {
+            Some(report) => report,
+            None => return Ok(JsValue::NULL),
+        };
+
+        let closest: js_sys::Array = report
+            .closest
+            .into_iter()
+            .map(|near_miss| {
+                let obj = js_sys::Object::new();
+                js_sys::Reflect::set(&obj, &"value".into(), &near_miss.value.into()).unwrap();
+                js_sys::Reflect::set(&obj, &"text".into(), &near_miss.text.into()).unwrap();
+                JsValue::from(obj)
+            })
+            .collect();
+
+        let exhausted: js_sys::Array = report
+            .exhausted_operations
+            .iter()
+            .map(|ops| JsValue::from(ops.iter().map(|op| op.symbol()).collect::<String>()))
+            .collect();
+
+        let obj = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &obj,
+            &"operationsTried".into(),
+            &(report.operations_tried as f64).into(),
+        )
+        .unwrap();
+        js_sys::Reflect::set(&obj, &"closest".into(), &closest).unwrap();
+        js_sys::Reflect::set(&obj, &"exhaustedOperations".into(), &exhausted).unwrap();
+        Ok(obj.into())
+    }
+
+    /// Find the solution that's closest to a wrong `answer` (in the same format as the
+    /// solutions), for a "you were so close" hint. Returns `null` if the answer already reaches
+    /// the target or there are no solutions, otherwise an object with the closest `solution`
+    /// text, the edit `distance` to it, the `part` of the answer that differs, and the
+    /// `replacement` for that part in the solution. Throws a `CalcError` if the inputs are
+    /// outside of the limits or the answer can't be parsed.
+    pub fn hint(&self, inputs: &[i32], answer: &str) -> Result<JsValue, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let answer = parse_expression(answer).map_err(calc_error_to_js)?;
+        let hint =
+            match nearest_solution(&answer, inputs, &self.config).map_err(calc_error_to_js)? {
+                Some(hint) => hint,
+                None => return Ok(JsValue::NULL),
+            };
+
+        let obj = js_sys::Object::new();
+        js_sys::Reflect::set(&obj, &"solution".into(), &hint.solution.text.into()).unwrap();
+        js_sys::Reflect::set(&obj, &"distance".into(), &(hint.distance as u32).into()).unwrap();
+        js_sys::Reflect::set(&obj, &"part".into(), &hint.part.to_text().into()).unwrap();
+        js_sys::Reflect::set(
+            &obj,
+            &"replacement".into(),
+            &hint.replacement.to_text().into(),
+        )
+        .unwrap();
+        Ok(obj.into())
+    }
+
+    /// Group the solutions that are within `max_distance` edits of each other (see
+    /// `edit_distance`), returning an array of groups where each group is an array of solution
+    /// texts. The groups, and the solutions in them, stay in the usual solution order.
+    /// Throws a `CalcError` if the inputs are outside of the limits.
+    pub fn clusters(
+        &mut self,
+        inputs: &[i32],
+        max_distance: u32,
+    ) -> Result<js_sys::Array, JsValue> {
+        let solutions = self.find_solutions(inputs)?;
+
+        Ok(cluster_solutions(&solutions, max_distance as usize)
+            .into_iter()
+            .map(|cluster| {
+                let texts: js_sys::Array = cluster
+                    .into_iter()
+                    .map(|index| JsValue::from_str(&solutions[index].text))
+                    .collect();
+                JsValue::from(texts)
+            })
+            .collect())
+    }
+
+    /// Solve the inputs with this solver's config and with `other`'s, and compare the solutions
+    /// by their canonical form, e.g. to check what a change to the options gains or loses.
+    /// Returns an object with the solutions found `onlyHere` and `onlyOther` (arrays of objects
+    /// like the ones from `solve`), and the number of `shared` solutions. Every solution is
+    /// compared, whatever `max_results` is, and the results aren't cached. Throws a
+    /// `CalcError` if the inputs are outside of either solver's limits.
+    pub fn compare(&self, other: &Solver, inputs: &[i32]) -> Result<js_sys::Object, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let diff =
+            compare_configs(inputs, &self.config, &other.config).map_err(calc_error_to_js)?;
+        let to_array = |solutions: &[Solution]| -> js_sys::Array {
+            solutions
+                .iter()
+                .map(|solution| JsValue::from(solution_to_js(solution)))
+                .collect()
+        };
+
+        let obj = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+        };
+        set("onlyHere", &to_array(&diff.only_first));
+        set("onlyOther", &to_array(&diff.only_second));
+        set("shared", &(diff.shared as u32).into());
+        Ok(obj)
+    }
+
+    /// Solve the inputs and check that every solution reaches the target, uses each input
+    /// once, parses back from its own text and isn't equivalent to another solution, e.g. from
+    /// the browser console with `new Solver().self_test([1, 2, 3, 4])`. Returns an object with
+    /// whether it `passed`, the number of `solutionsChecked`, and the `violations` (an array of
+    /// `{ invariant, text, detail }`). The solutions aren't cached. Throws a `CalcError` if the
+    /// inputs are outside of the limits.
+    pub fn self_test(&self, inputs: &[i32]) -> Result<js_sys::Object, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let report = self_test(inputs, &self.config).map_err(calc_error_to_js)?;
+
+        let violations: js_sys::Array = report
+            .violations
+            .iter()
+            .map(|violation| {
+                let obj = js_sys::Object::new();
+                let set = |key: &str, value: &JsValue| {
+                    js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+                };
+                set("invariant", &violation.invariant.name().into());
+                set("text", &violation.text.as_str().into());
+                set("detail", &violation.detail.as_str().into());
+                JsValue::from(obj)
+            })
+            .collect();
+
+        let obj = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+        };
+        set("passed", &report.passed().into());
+        set(
+            "solutionsChecked",
+            &(report.solutions_checked as u32).into(),
+        );
+        set("violations", &violations);
+        Ok(obj)
+    }
+
+    /// Check that solving the inputs finds every distinct solution by comparing it against a
+    /// brute force over every expression, for up to 5 numbers, e.g. from the browser console
+    /// with `new Solver().audit_completeness([4, 2, 2])`. Only solves in the `"exhaustive"` mode
+    /// should pass. Returns an object with whether it `passed`, the number of `solutionsFound`
+    /// and `expressionsChecked`, and the text of the `missed` and `extra` solutions. Throws a
+    /// `CalcError` if there are too many inputs or they're outside of the limits.
+    pub fn audit_completeness(&self, inputs: &[i32]) -> Result<js_sys::Object, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let report = audit_completeness(inputs, &self.config).map_err(calc_error_to_js)?;
+        let to_array = |texts: &[String]| -> js_sys::Array {
+            texts
+                .iter()
+                .map(|text| JsValue::from(text.as_str()))
+                .collect()
+        };
+
+        let obj = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+        };
+        set("passed", &report.passed().into());
+        set("solutionsFound", &(report.solutions_found as u32).into());
+        set(
+            "expressionsChecked",
+            &(report.expressions_checked as u32).into(),
+        );
+        set("missed", &to_array(&report.missed));
+        set("extra", &to_array(&report.extra));
+        Ok(obj)
+    }
+
+    /// Export every distinct solution in a stable textual form, sorted by fingerprint rather
+    /// than ranked, so the solver's output can be checked in for a release and compared with
+    /// the next one with `diff_solution_sets`. The solutions aren't cached. Throws a
+    /// `CalcError` if the inputs are outside of the limits.
+    pub fn export_solution_set(&self, inputs: &[i32]) -> Result<String, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        export_solution_set(inputs, &self.config).map_err(calc_error_to_js)
+    }
+
+    /// Pick a solution to feature on the daily puzzle's reveal screen, leaning towards the
+    /// medium complexity ones, returning `null` if there are no solutions. The same `seed`
+    /// always picks the same solution. Returns an object like the ones from `solve`. Throws a
+    /// `CalcError` if the inputs are outside of the limits.
+    pub fn featured_solution(&self, inputs: &[i32], seed: u32) -> Result<JsValue, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        match featured_solution(inputs, &self.config, seed as u64) {
+            Ok(solution) => Ok(solution_to_js(&solution).into()),
+            Err(CalcError::Unsolvable) => Ok(JsValue::NULL),
+            Err(err) => Err(calc_error_to_js(err)),
+        }
+    }
+
+    /// Find one good solution (usually the simplest) without finding all of them, which is
+    /// much faster than `solve` for bigger puzzles. Returns an object like the ones from
+    /// `solve`, or `null` if there are no solutions. The results aren't cached. Throws a
+    /// `CalcError` if the inputs are outside of the limits.
+    pub fn quick_solution(&self, inputs: &[i32]) -> Result<JsValue, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        match quick_solution(inputs, &self.config) {
+            Ok(solution) => Ok(solution_to_js(&solution).into()),
+            Err(CalcError::Unsolvable) => Ok(JsValue::NULL),
+            Err(err) => Err(calc_error_to_js(err)),
+        }
+    }
+
+    /// Check if the inputs have any solutions, as quickly as `quick_solution`. Throws a
+    /// `CalcError` if the inputs are outside of the limits.
+    pub fn is_solvable(&self, inputs: &[i32]) -> Result<bool, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        is_solvable(inputs, &self.config).map_err(calc_error_to_js)
+    }
+
+    /// Rate how hard the inputs are, returning `null` if they have no solutions. Otherwise
+    /// returns an object with the `rating` from 0 (trivial) to 100 (very hard), the
+    /// `complexityPoints` and `scarcityPoints` that it's made up of, the `solutionCount`, and
+    /// the `factors` that make it hard (`"fewSolutions"`, `"requiresPower"`,
+    /// `"largeIntermediates"` or `"deepNesting"`). Throws a `CalcError` if the inputs are
+    /// outside of the limits.
+    pub fn difficulty(&self, inputs: &[i32]) -> Result<JsValue, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let breakdown = match difficulty_breakdown(inputs, &self.config) {
+            Ok(breakdown) => breakdown,
+            Err(CalcError::Unsolvable) => return Ok(JsValue::NULL),
+            Err(err) => return Err(calc_error_to_js(err)),
+        };
+
+        let factors: js_sys::Array = breakdown
+            .factors
+            .iter()
+            .map(|factor| JsValue::from(factor.name()))
+            .collect();
+
+        let obj = js_sys::Object::new();
+        let set = |key: &str, value: &JsValue| {
+            js_sys::Reflect::set(&obj, &key.into(), value).unwrap();
+        };
+        set("rating", &breakdown.rating.into());
+        set("complexityPoints", &breakdown.complexity_points.into());
+        set("scarcityPoints", &breakdown.scarcity_points.into());
+        set("solutionCount", &(breakdown.solution_count as u32).into());
+        set("factors", &factors);
+        Ok(obj.into())
+    }
+
+    /// Write the solutions as CSV text, with a row for each solution's expression, value,
+    /// complexity, depth and the operators it uses. Throws a `CalcError` if the inputs are
+    /// outside of the limits.
+    pub fn export_csv(&mut self, inputs: &[i32]) -> Result<String, JsValue> {
+        Ok(solutions_to_csv(&self.find_solutions(inputs)?))
+    }
+
+    /// Find the distinct expressions that come within `delta` of the target without making it,
+    /// closest and simplest first (at most `max_results` of them). Returns an array of objects
+    /// with each expression's `value` and `text`. The results aren't cached. Throws a
+    /// `CalcError` if the inputs are outside of the limits.
+    pub fn near_misses(&self, inputs: &[i32], delta: u32) -> Result<js_sys::Array, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let misses = near_misses(inputs, &self.config, delta).map_err(calc_error_to_js)?;
+
+        Ok(misses
+            .into_iter()
+            .map(|miss| {
+                let obj = js_sys::Object::new();
+                js_sys::Reflect::set(&obj, &"value".into(), &miss.value.into()).unwrap();
+                js_sys::Reflect::set(&obj, &"text".into(), &miss.text.into()).unwrap();
+                JsValue::from(obj)
+            })
+            .collect())
+    }
+
+    /// Find every value that can be made from the inputs (ignoring the target), for exploring
+    /// what the numbers can do. Returns an array of objects with each `value` (smallest first)
+    /// and the `text` of the simplest expression that makes it. The results aren't cached.
+    /// Throws a `CalcError` if the inputs are outside of the limits.
+    pub fn reachable_values(&self, inputs: &[i32]) -> Result<js_sys::Array, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        let values = reachable_values(inputs, &self.config).map_err(calc_error_to_js)?;
+
+        Ok(values
+            .into_iter()
+            .map(|reachable| {
+                let obj = js_sys::Object::new();
+                js_sys::Reflect::set(&obj, &"value".into(), &reachable.value.into()).unwrap();
+                js_sys::Reflect::set(&obj, &"text".into(), &reachable.text.into()).unwrap();
+                JsValue::from(obj)
+            })
+            .collect())
+    }
+
+    /// Count how many of the solutions use each operation, returning an object with the
+    /// `solutionCount` and the `operations` as an object from each operator's symbol to the
+    /// number of solutions that use it. Throws a `CalcError` if the inputs are outside of the
+    /// limits.
+    pub fn operation_usage(&mut self, inputs: &[i32]) -> Result<js_sys::Object, JsValue> {
+        let solutions = self.find_solutions(inputs)?;
+
+        let operations = js_sys::Object::new();
+        for (op, count) in operation_usage(&solutions) {
+            let symbol = JsValue::from(op.symbol().to_string());
+            js_sys::Reflect::set(&operations, &symbol, &(count as u32).into()).unwrap();
+        }
+
+        let obj = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &obj,
+            &"solutionCount".into(),
+            &(solutions.len() as u32).into(),
+        )
+        .unwrap();
+        js_sys::Reflect::set(&obj, &"operations".into(), &operations).unwrap();
+        Ok(obj)
+    }
+
+    /// Work out which puzzles starting with `prefix` are solvable, returning a 10x10 array of
+    /// booleans where `grid[a][b]` is whether the prefix followed by the digits `a` and `b` is
+    /// solvable. The results aren't cached. Throws a `CalcError` if the puzzles are outside of
+    /// the limits.
+    pub fn solvability_grid(&self, prefix: &[i32]) -> Result<js_sys::Array, JsValue> {
+        let _context = PanicContext::enter(prefix, &self.config);
+        let grid = solvability_grid(prefix, &self.config).map_err(calc_error_to_js)?;
+
+        Ok(grid
+            .iter()
+            .map(|row| {
+                let row: js_sys::Array = row
+                    .iter()
+                    .map(|&solvable| JsValue::from(solvable))
+                    .collect();
+                JsValue::from(row)
+            })
+            .collect())
+    }
+
+    /// Export the cached solutions as bytes (a `Uint8Array`), e.g. to store in IndexedDB and
+    /// import on the next page load with `import_cache`
+    pub fn export_cache(&self) -> Vec<u8> {
+        self.cache.borrow().export()
+    }
+
+    /// Import solutions that were exported with `export_cache`, returning how many puzzles
+    /// were imported. Throws an `Error` if the data isn't a valid export from this version.
+    pub fn import_cache(&mut self, bytes: &[u8]) -> Result<u32, JsValue> {
+        let imported = self
+            .cache
+            .get_mut()
+            .import(bytes)
+            .map_err(|message| js_sys::Error::new(&message))?;
+        Ok(imported as u32)
+    }
+
+    /// Remove all the cached solutions
+    pub fn clear_cache(&mut self) {
+        self.cache.get_mut().clear();
+    }
+
+    /// Rank the solutions with a js comparator instead of by complexity, e.g. to experiment
+    /// with a different ranking without a new release. It's called with two objects with the
+    /// `text`, `complexity`, `fingerprint` and `techniques` of each solution, and returns a
+    /// number like the comparator for `Array.prototype.sort`. Every solution is ranked before
+    /// `max_results` is applied. Pass `undefined` to go back to the built in order.
+    ///
+    /// The cache is cleared, since the cached solutions were ranked with the old comparator.
+    /// If the comparator throws, the solve throws the same error.
+    pub fn set_comparator(&mut self, comparator: Option<js_sys::Function>) {
+        self.comparator = comparator;
+        self.cache.get_mut().clear();
+    }
+}
+
+impl Solver {
+    /// Get the solutions for the inputs from the cache, or run the solver and cache them,
+    /// converting any input errors into js errors
+    fn find_solutions(&self, inputs: &[i32]) -> Result<Ref<'_, [Solution]>, JsValue> {
+        let _context = PanicContext::enter(inputs, &self.config);
+        // Neither is borrowed while solving, as the js comparator can call back into the solver
+        self.stats.borrow_mut().solves += 1;
+        let cached = self.cache.borrow_mut().touch(inputs, &self.config);
+        if cached {
+            self.stats.borrow_mut().cache_hits += 1;
+        } else {
+            let solutions = match &self.comparator {
+                Some(comparator) => self.solve_with_comparator(inputs, comparator)?,
+                None if self.debug => {
+                    let (solutions, rejections) =
+                        solve_with_rejections(inputs, &self.config).map_err(calc_error_to_js)?;
+                    self.stats.borrow_mut().rejections.add(&rejections);
+                    solutions
+                }
+                None => solve(inputs, &self.config)
+                    .map_err(calc_error_to_js)?
+                    .collect(),
+            };
+            let mut stats = self.stats.borrow_mut();
+            stats.cache_misses += 1;
+            stats.solutions += solutions.len() as u32;
+
+            let evicted =
+                self.cache
+                    .borrow_mut()
+                    .insert(inputs.to_vec(), self.config.clone(), solutions);
+            stats.cache_evictions += evicted as u32;
+        }
+
+        Ok(Ref::map(self.cache.borrow(), SolutionCache::most_recent))
+    }
+
+    /// Solve and sort all of the solutions with the js comparator, then apply `max_results`
+    fn solve_with_comparator(
+        &self,
+        inputs: &[i32],
+        comparator: &js_sys::Function,
+    ) -> Result<Vec<Solution>, JsValue> {
+        let config = SolverConfig {
+            max_results: None,
+            ..self.config.clone()
+        };
+        let solutions: Vec<_> = solve(inputs, &config).map_err(calc_error_to_js)?.collect();
+
+        // The objects are sorted by js's own `Array.prototype.sort`, so that the comparator is
+        // called the way js code expects and anything it throws propagates out of the sort
+        let objects: js_sys::Array = solutions.iter().map(solution_to_js).collect();
+        // Sorting moves the same objects around, so they're mapped back to their solutions
+        let indices = js_sys::Map::new();
+        for (index, obj) in objects.iter().enumerate() {
+            indices.set(&obj, &JsValue::from(index as u32));
+        }
+        let sort: js_sys::Function = js_sys::Reflect::get(&objects, &"sort".into())?.into();
+        sort.call1(&objects, comparator)?;
+
+        let mut solutions: Vec<_> = solutions.into_iter().map(Some).collect();
+        Ok(objects
+            .iter()
+            .take(self.config.max_results.unwrap_or(usize::MAX))
+            .map(|obj| {
+                let index = indices
+                    .get(&obj)
+                    .as_f64()
+                    .expect("Every object has an index");
+                solutions[index as usize]
+                    .take()
+                    .expect("The sort doesn't duplicate objects")
+            })
+            .collect())
+    }
+}
+
+/// A lazy iterator (callable from js) over the solutions for some inputs, created with
+/// `Solver.iter()`. The generator state stays alive inside wasm between calls to `next()`.
+#[wasm_bindgen]
+pub struct SolutionIterator(SolutionIter);
+
+/// Pass a sample of the iterator's candidates to a js callback, see `SolutionIter::observe`.
+/// Errors thrown by the callback are ignored, so a broken developer tool can't break solving.
+fn observe_with_js(iter: &mut SolutionIter, callback: js_sys::Function, fraction: f64) {
+    iter.observe(fraction, move |candidate| {
+        let obj = js_sys::Object::new();
+        let value = match candidate.value {
+            Some(value) => JsValue::from(value),
+            None => JsValue::NULL,
+        };
+        js_sys::Reflect::set(&obj, &"value".into(), &value).unwrap();
+        js_sys::Reflect::set(&obj, &"outcome".into(), &candidate.outcome.name().into()).unwrap();
+        let _ = callback.call1(&JsValue::NULL, &obj);
+    });
+}
+
+#[wasm_bindgen]
+impl SolutionIterator {
+    /// Get the text of the next solution, or `undefined` once there are no more solutions
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<String> {
+        self.0.next().map(|solution| solution.text)
+    }
+
+    /// Get the text of up to `count` more solutions, which is cheaper than calling `next()`
+    /// for each one. Returns an empty array once there are no more solutions.
+    pub fn next_batch(&mut self, count: usize) -> js_sys::Array {
+        self.0
+            .by_ref()
+            .take(count)
+            .map(|solution| JsValue::from(solution.text))
+            .collect()
+    }
+
+    /// Call `callback` with a random `fraction` (from 0 to 1) of the candidate expressions that
+    /// are tried from now on, as `{ value, outcome }` objects, for visualizing the search. The
+    /// `value` is `null` if the candidate was pruned before it had one, and the `outcome` is
+    /// one of "pruned", "missedTarget", "duplicate", "tooDeep", "tooManyOperators" or
+    /// "accepted".
+    pub fn observe(&mut self, callback: js_sys::Function, fraction: f64) {
+        observe_with_js(&mut self.0, callback, fraction);
+    }
+}
+
+/// How many operations `SolverSession.step()` tries between checks of the clock, which is
+/// well under a millisecond of work
+const SESSION_WORK_PER_CHECK: usize = 20_000;
+
+/// The start of a `SolverSession.snapshot()`, followed by the format version
+const SNAPSHOT_MAGIC: &[u8; 4] = b"MTSS";
+/// Only bumped when the format of a released version changes
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// A solve (callable from js) that's done a little at a time with `step()`, created with
+/// `Solver.session()`. The generator state stays inside wasm between steps, so the solve can
+/// be interleaved with rendering on the main thread. It can also be saved with `snapshot()`
+/// and picked up again with `SolverSession.restore()`, e.g. in a new worker.
+#[wasm_bindgen]
+pub struct SolverSession {
+    inputs: Vec<i32>,
+    config: SolverConfig,
+    /// The generator, which is only created in the first step as building it takes a while
+    iter: Option<SolutionIter>,
+    found: Vec<Solution>,
+    /// The solutions in their final order, once they've all been found
+    ranked: Option<Vec<Solution>>,
+    /// The callback and fraction from `observe`, which is kept for when the generator is made
+    telemetry: Option<(js_sys::Function, f64)>,
+}
+
+#[wasm_bindgen]
+impl SolverSession {
+    /// Work on the solve for about `ms_budget` milliseconds (the first step also sets up the
+    /// generator, which can take longer for big puzzles), returning true once it's finished
+    pub fn step(&mut self, ms_budget: f64) -> Result<bool, JsValue> {
+        if self.ranked.is_some() {
+            return Ok(true);
+        }
+
+        let _context = PanicContext::enter(&self.inputs, &self.config);
+        let start = js_sys::Date::now();
+        let iter = match &mut self.iter {
+            Some(iter) => iter,
+            None => {
+                let config = SolverConfig {
+                    max_results: None,
+                    ..self.config.clone()
+                };
+                let mut iter = solve_lazy(&self.inputs, &config).map_err(calc_error_to_js)?;
+                if let Some((callback, fraction)) = &self.telemetry {
+                    observe_with_js(&mut iter, callback.clone(), *fraction);
+                }
+                self.iter.insert(iter)
+            }
+        };
+
+        while !iter.is_finished() {
+            self.found.extend(iter.step(SESSION_WORK_PER_CHECK));
+            if js_sys::Date::now() - start >= ms_budget {
+                break;
+            }
+        }
+
+        if iter.is_finished() {
+            let found = std::mem::take(&mut self.found);
+            self.ranked = Some(rank_solutions(found, &self.config));
+            self.iter = None;
+        }
+        Ok(self.ranked.is_some())
+    }
+
+    /// Whether every solution has been found
+    #[wasm_bindgen(getter)]
+    pub fn finished(&self) -> bool {
+        self.ranked.is_some()
+    }
+
+    /// How many solutions have been found so far (or in total, up to `max_results`, once it's
+    /// finished)
+    #[wasm_bindgen(getter)]
+    pub fn found(&self) -> usize {
+        self.ranked.as_ref().unwrap_or(&self.found).len()
+    }
+
+    /// Save the progress of the solve as bytes, which `SolverSession.restore()` can pick up
+    /// from, e.g. after the worker running it is killed
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(SNAPSHOT_MAGIC);
+        cache::write_u32(&mut out, SNAPSHOT_VERSION);
+        cache::write_inputs(&mut out, &self.inputs);
+        cache::write_config(&mut out, &self.config);
+
+        match (&self.ranked, &self.iter) {
+            (Some(ranked), _) => {
+                out.push(2);
+                cache::write_solutions(&mut out, ranked);
+            }
+            (None, Some(iter)) => {
+                out.push(1);
+                let cursor = iter.cursor();
+                for index in [cursor.split, cursor.right, cursor.left, cursor.operation] {
+                    cache::write_u32(&mut out, index as u32);
+                }
+                out.push(cursor.reversed as u8);
+                cache::write_u32(&mut out, iter.checked() as u32);
+                cache::write_solutions(&mut out, &self.found);
+                // `resume_lazy` recognises the solutions by their original canonical forms
+                for solution in &self.found {
+                    cache::write_canonical(&mut out, &solution.canonical);
+                }
+            }
+            (None, None) => out.push(0),
+        }
+
+        out
+    }
+
+    /// Pick up a solve from a `snapshot()`. Solves that were in progress set up the generator
+    /// again straight away, which takes about as long as the first `step()` did. Throws an
+    /// `Error` if the bytes aren't a snapshot.
+    pub fn restore(bytes: &[u8]) -> Result<SolverSession, JsValue> {
+        SolverSession::read_snapshot(bytes).map_err(|err| js_sys::Error::new(&err).into())
+    }
+
+    /// The text of the solutions found so far, in the order they were found, or once it's
+    /// finished all of the solutions in the same order as `Solver.solve()`
+    pub fn solutions(&self) -> js_sys::Array {
+        self.ranked
+            .as_ref()
+            .unwrap_or(&self.found)
+            .iter()
+            .map(|solution| JsValue::from(solution.text.as_str()))
+            .collect()
+    }
+
+    /// Call `callback` with a random `fraction` (from 0 to 1) of the candidate expressions that
+    /// the rest of the solve tries, like `SolutionIterator.observe()`
+    pub fn observe(&mut self, callback: js_sys::Function, fraction: f64) {
+        if let Some(iter) = &mut self.iter {
+            observe_with_js(iter, callback.clone(), fraction);
+        }
+        self.telemetry = Some((callback, fraction));
+    }
+
+    /// The text of the `n` best solutions found so far, in the same order as `Solver.solve()`,
+    /// without stopping the solve, so a list can be refined as more solutions are found. Once
+    /// it's finished, these are the first `n` solutions.
+    pub fn current_best(&self, n: usize) -> js_sys::Array {
+        let best = match &self.ranked {
+            Some(ranked) => ranked.iter().take(n).cloned().collect(),
+            None => {
+                let max_results = self.config.max_results.unwrap_or(usize::MAX).min(n);
+                let config = SolverConfig {
+                    max_results: Some(max_results),
+                    ..self.config.clone()
+                };
+                rank_solutions(self.found.clone(), &config)
+            }
+        };
+
+        best.into_iter()
+            .map(|solution| JsValue::from(solution.text))
+            .collect()
+    }
+}
+
+impl SolverSession {
+    fn read_snapshot(bytes: &[u8]) -> Result<SolverSession, String> {
+        let mut reader = cache::Reader::new(bytes);
+        if reader.take(4)? != SNAPSHOT_MAGIC {
+            return Err("The data isn't a solver snapshot".to_string());
+        }
+        let version = reader.u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version {}", version));
+        }
+
+        let inputs = reader.inputs()?;
+        let config = reader.config()?;
+        let mut session = SolverSession {
+            inputs,
+            config,
+            iter: None,
+            found: Vec::new(),
+            ranked: None,
+            telemetry: None,
+        };
+
+        match reader.take(1)?[0] {
+            0 => {}
+            1 => {
+                let cursor = Cursor {
+                    split: reader.len()?,
+                    right: reader.len()?,
+                    left: reader.len()?,
+                    operation: reader.len()?,
+                    reversed: reader.take(1)?[0] != 0,
+                };
+                let checked = reader.len()?;
+                session.found = reader.solutions(&session.config)?;
+                for solution in &mut session.found {
+                    solution.canonical = reader.canonical()?;
+                    solution.fingerprint = solution.canonical.fingerprint();
+                }
+
+                let config = SolverConfig {
+                    max_results: None,
+                    ..session.config.clone()
+                };
+                let iter = resume_lazy(&session.inputs, &config, cursor, checked, &session.found)
+                    .map_err(|err| err.to_string())?;
+                session.iter = Some(iter);
+            }
+            2 => session.ranked = Some(reader.solutions(&session.config)?),
+            state => return Err(format!("Unknown session state: {}", state)),
+        }
+
+        Ok(session)
+    }
+}
+
+/// An expression that can be edited from js, e.g. to let users change a solution and see its
+/// value update live. Nodes are identified by their position in a pre-order walk, see
+/// `nodes()`. Edits that would make the expression invalid throw a `CalcError` (with a `kind`
+/// of `"InvalidEdit"` or `"Overflow"`) and leave the expression unchanged.
+#[wasm_bindgen]
+pub struct ExpressionHandle(make_ten_core::maths::EvaluatedExpr);
+
+#[wasm_bindgen]
+impl ExpressionHandle {
+    /// Parse an expression, e.g. a solution's text. Throws a `CalcError` with a `span` if it
+    /// can't be parsed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<ExpressionHandle, JsValue> {
+        Ok(ExpressionHandle(
+            parse_expression(text).map_err(calc_error_to_js)?,
+        ))
+    }
+
+    /// The expression as text
+    pub fn text(&self) -> String {
+        self.0.to_text()
+    }
+
+    /// The value of the expression
+    pub fn value(&self) -> i32 {
+        self.0.value()
+    }
+
+    /// Get every node of the expression in pre-order, as objects with the node's `id`, its
+    /// `text` and `value`, the `span` of `{ start, end }` that it covers in `text()`, and the
+    /// `operator` symbol if it's an operation
+    pub fn nodes(&self) -> js_sys::Array {
+        expression_nodes(&self.0)
+            .into_iter()
+            .zip(node_spans(&self.0))
+            .enumerate()
+            .map(|(id, (node, span))| {
+                let obj = js_sys::Object::new();
+                js_sys::Reflect::set(&obj, &"id".into(), &(id as u32).into()).unwrap();
+                js_sys::Reflect::set(&obj, &"span".into(), &span_to_js(&span)).unwrap();
+                js_sys::Reflect::set(&obj, &"text".into(), &node.to_text().into()).unwrap();
+                js_sys::Reflect::set(&obj, &"value".into(), &node.value().into()).unwrap();
+                if let Expression::Op(op) = &**node {
+                    let symbol = JsValue::from(op.kind.symbol().to_string());
+                    js_sys::Reflect::set(&obj, &"operator".into(), &symbol).unwrap();
+                }
+                JsValue::from(obj)
+            })
+            .collect()
+    }
+
+    /// Replace the operator of an operation node with the operator with the symbol `op`
+    pub fn swap_operator(&mut self, node_id: u32, op: &str) -> Result<(), JsValue> {
+        let mut chars = op.chars();
+        let kind = match (
+            chars.next().and_then(OperationKind::from_symbol),
+            chars.next(),
+        ) {
+            (Some(kind), None) => kind,
+            _ => return Err(js_sys::Error::new(&format!("Unknown operator: {}", op)).into()),
+        };
+
+        self.0 = swap_operator(&self.0, node_id as usize, kind).map_err(calc_error_to_js)?;
+        Ok(())
+    }
+
+    /// Swap the left and right operands of an operation node
+    pub fn swap_children(&mut self, node_id: u32) -> Result<(), JsValue> {
+        self.0 = swap_children(&self.0, node_id as usize).map_err(calc_error_to_js)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restoring_checks_the_cursor() {
+        let inputs = vec![1, 2, 3, 4];
+        let config = SolverConfig::default();
+        let mut iter = solve_lazy(&inputs, &config).unwrap();
+        let found = SolutionIter::step(&mut iter, 10);
+        let session = SolverSession {
+            inputs,
+            config,
+            iter: Some(iter),
+            found,
+            ranked: None,
+            telemetry: None,
+        };
+        let snapshot = session.snapshot();
+        let restored = SolverSession::read_snapshot(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+
+        // The cursor comes after the header and the session's state
+        let mut header = SNAPSHOT_MAGIC.to_vec();
+        cache::write_u32(&mut header, SNAPSHOT_VERSION);
+        cache::write_inputs(&mut header, &session.inputs);
+        cache::write_config(&mut header, &session.config);
+        for field in 0..4 {
+            let mut tampered = snapshot.clone();
+            let at = header.len() + 1 + field * 4;
+            tampered[at..at + 4].copy_from_slice(&1000u32.to_le_bytes());
+            assert!(SolverSession::read_snapshot(&tampered).is_err());
+        }
+    }
+}
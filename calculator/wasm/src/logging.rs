@@ -0,0 +1,58 @@
+//! A `log` backend that writes to the browser console, so the solver's logs can be turned on
+//! from js with `set_log_level`
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    fn console_info(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(s: &str);
+}
+
+struct ConsoleLogger;
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = format!("[{}] {}", record.target(), record.args());
+        match record.level() {
+            Level::Error => console_error(&message),
+            Level::Warn => console_warn(&message),
+            Level::Info => console_info(&message),
+            Level::Debug | Level::Trace => console_debug(&message),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Set which logs get written to the console, one of `"off"`, `"error"`, `"warn"`, `"info"`,
+/// `"debug"` or `"trace"`. Logging is off until this is called, and it can be changed at any time.
+#[wasm_bindgen]
+pub fn set_log_level(level: &str) -> Result<(), JsValue> {
+    let level: LevelFilter = level
+        .parse()
+        .map_err(|_| js_sys::Error::new(&format!("Unknown log level: {}", level)))?;
+
+    // This only fails if the logger is already installed, which is fine
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! A panic hook that records why the solver panicked, so the js glue can turn the opaque
+//! `unreachable` trap (a `WebAssembly.RuntimeError`) into a js `Error` named `SolverPanic`
+//! with `take_last_panic()`. The error has the panic `message`, the `location` in the source,
+//! and the `inputs` and `configFingerprint` of the solve that was running (if any), so bug
+//! reports from the browser have enough to reproduce them.
+//!
+//! The hook returns normally rather than throwing from inside it, as std only finishes
+//! handling a panic once the hook returns. Throwing from the hook would leave std thinking a
+//! panic is still in progress, so the next panic anywhere in the module would abort. The
+//! `Solver` that panicked should still be thrown away, as the panic skipped the rest of its
+//! call without cleaning up.
+//!
+//! ```js
+//! try {
+//!   solver.solve(inputs);
+//! } catch (e) {
+//!   throw take_last_panic() ?? e;
+//! }
+//! ```
+
+use std::cell::RefCell;
+
+use make_ten_core::SolverConfig;
+use wasm_bindgen::prelude::*;
+
+/// A panic that the hook recorded, see `take_last_panic`
+struct PanicRecord {
+    message: String,
+    location: Option<String>,
+    /// The inputs and config fingerprint of the solve that was running
+    context: Option<(Vec<i32>, u64)>,
+}
+
+thread_local! {
+    /// The inputs and config fingerprint of the solve that's running
+    static CONTEXT: RefCell<Option<(Vec<i32>, u64)>> = const { RefCell::new(None) };
+    /// The most recent panic that hasn't been taken yet
+    static LAST_PANIC: RefCell<Option<PanicRecord>> = const { RefCell::new(None) };
+}
+
+/// Marks a solve as running until it's dropped, so a panic can report what was being solved
+pub(crate) struct PanicContext;
+
+impl PanicContext {
+    pub fn enter(inputs: &[i32], config: &SolverConfig) -> PanicContext {
+        CONTEXT
+            .with(|context| *context.borrow_mut() = Some((inputs.to_vec(), config.fingerprint())));
+        PanicContext
+    }
+}
+
+impl Drop for PanicContext {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| *context.borrow_mut() = None);
+    }
+}
+
+/// Record the panic for `take_last_panic`
+fn record_panic(message: &str, location: Option<String>) {
+    // The panic could have happened while the context or the last panic were borrowed
+    let context = CONTEXT.with(|context| context.try_borrow().ok().and_then(|c| c.clone()));
+    let record = PanicRecord {
+        message: message.to_string(),
+        location,
+        context,
+    };
+    LAST_PANIC.with(|last| {
+        if let Ok(mut last) = last.try_borrow_mut() {
+            *last = Some(record);
+        }
+    });
+}
+
+/// Take the most recent panic as a js `Error` named `SolverPanic`, or `undefined` if nothing
+/// has panicked since the last call. Call this after catching a `WebAssembly.RuntimeError`
+/// from the solver and throw the error it returns instead, see the module docs.
+#[wasm_bindgen]
+pub fn take_last_panic() -> Option<js_sys::Error> {
+    let record = LAST_PANIC.with(|last| last.borrow_mut().take())?;
+
+    let error = js_sys::Error::new(&format!("The solver panicked: {}", record.message));
+    error.set_name("SolverPanic");
+    let set = |key: &str, value: &JsValue| {
+        js_sys::Reflect::set(&error, &key.into(), value).unwrap();
+    };
+    set("message", &record.message.as_str().into());
+    if let Some(location) = record.location {
+        set("location", &location.into());
+    }
+    if let Some((inputs, fingerprint)) = record.context {
+        let inputs: js_sys::Array = inputs.into_iter().map(JsValue::from).collect();
+        set("inputs", &inputs);
+        set("configFingerprint", &format!("{:016x}", fingerprint).into());
+    }
+    Some(error)
+}
+
+/// Install the panic hook when the module is loaded
+#[wasm_bindgen(start)]
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = match (
+            payload.downcast_ref::<&str>(),
+            payload.downcast_ref::<String>(),
+        ) {
+            (Some(message), _) => message,
+            (_, Some(message)) => message.as_str(),
+            _ => "Unknown panic",
+        };
+        record_panic(
+            message,
+            info.location().map(|location| location.to_string()),
+        );
+    }));
+}
@@ -0,0 +1,46 @@
+//! A compact binary encoding of solutions. A worker can send the encoded bytes to the main
+//! thread by transferring their `ArrayBuffer`, which is much cheaper than structured cloning
+//! a big array of strings. The encoding is a u32 count of solutions, then for each solution
+//! its u32 complexity, the u32 byte length of its text, and the UTF-8 text. Numbers are little
+//! endian.
+
+use make_ten_core::Solution;
+
+pub fn encode(solutions: &[Solution]) -> Vec<u8> {
+    let size = 4 + solutions
+        .iter()
+        .map(|solution| 8 + solution.text.len())
+        .sum::<usize>();
+    let mut out = Vec::with_capacity(size);
+
+    out.extend_from_slice(&(solutions.len() as u32).to_le_bytes());
+    for solution in solutions {
+        out.extend_from_slice(&solution.complexity.to_le_bytes());
+        out.extend_from_slice(&(solution.text.len() as u32).to_le_bytes());
+        out.extend_from_slice(solution.text.as_bytes());
+    }
+
+    out
+}
+
+/// Decode the solutions from `encode` into pairs of text and complexity, or None if the bytes
+/// aren't a valid encoding
+pub fn decode(bytes: &[u8]) -> Option<Vec<(&str, u32)>> {
+    let mut pos: usize = 0;
+    let mut take = |len: usize| {
+        let taken = bytes.get(pos..pos.checked_add(len)?)?;
+        pos += len;
+        Some(taken)
+    };
+
+    let count = u32::from_le_bytes(take(4)?.try_into().unwrap());
+    let mut solutions = Vec::new();
+    for _ in 0..count {
+        let complexity = u32::from_le_bytes(take(4)?.try_into().unwrap());
+        let len = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+        let text = std::str::from_utf8(take(len)?).ok()?;
+        solutions.push((text, complexity));
+    }
+
+    Some(solutions)
+}
@@ -3,19 +3,31 @@ import Head from "next/head";
 import Image from "next/image";
 import styles from "../styles/Home.module.css";
 
-import * as test from "../calculator/pkg/calculator_bg";
+import { Solver, take_last_panic } from "../calculator/pkg/calculator_bg";
 import { useEffect, useMemo, useState } from "react";
 import Box from "@mui/material/Box";
 import { TextField } from "@mui/material";
 
 const Home: NextPage = () => {
   const [text, setText] = useState("");
+  const solver = useMemo(() => new Solver(), []);
 
   const results = useMemo(() => {
+    if (text.length === 0) {
+      return [];
+    }
+
     let numbers = text.split("").map(Number);
-    const vals = test.generate_solutions(new Int32Array(numbers)) as string[];
-    return vals;
-  }, [text]);
+    try {
+      const vals = solver.solve(new Int32Array(numbers)) as string[];
+      return vals;
+    } catch (e) {
+      // The inputs were rejected by the solver's validation, or the solver panicked, in
+      // which case the panic has more detail than the trap
+      console.error(take_last_panic() ?? e);
+      return [];
+    }
+  }, [solver, text]);
 
   return (
     <Box